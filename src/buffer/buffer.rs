@@ -1,10 +1,16 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
 use std::io::prelude::*;
-use std::io::BufReader;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 
-use crate::buffer::{Error, Viewport};
+use crate::buffer::{
+    byte_index, display_width, grapheme_start, next_grapheme, prev_grapheme, wrap_columns,
+    BufferOptions, Error, FileFormat, History, Register, RegisterKind, Snapshot, Viewport,
+};
 use crate::keybinding::{InsertDirection, ModeParams};
 
 // ╭──────────────────────────────────────╮
@@ -45,6 +51,16 @@ impl fmt::Display for Mode {
     }
 }
 
+// The kinds of visual selections. Line selections always cover whole lines, and block selections
+// cover the same columns on every line.
+#[derive(Debug, Default, Eq, Hash, PartialEq, Clone, Copy)]
+pub enum VisualKind {
+    #[default]
+    Char,
+    Line,
+    Block,
+}
+
 // ╭──────────────────────────────────────╮
 // │ Buffer Structs                       │
 // ╰──────────────────────────────────────╯
@@ -56,6 +72,16 @@ pub struct Cursor {
     pub desired_x: usize, // If line is shorter than x, the original x is stored here.
 }
 
+// An insert on every line of a block selection. The text typed on the first line is copied to the
+// other lines when Insert mode is left.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub struct BlockInsert {
+    pub top: usize,
+    pub bottom: usize,
+    pub x: usize,
+    pub line_len: usize,
+}
+
 // Holds the states of the buffer. These states tell the editor if the buffer can be edited and/or
 // closed.
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
@@ -101,6 +127,14 @@ pub struct CommandLine {
     pub cursor: Cursor,
 }
 
+// The lines read from a file, and the line endings they had.
+struct FileLines {
+    lines: Vec<String>,
+    file_format: FileFormat,
+    end_of_line: bool,
+    mixed_line_endings: bool,
+}
+
 // The main buffer struct. Holds all the information related to the buffer
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub struct Buffer {
@@ -114,6 +148,18 @@ pub struct Buffer {
     pub state: BufferState,
     pub command_line: CommandLine,
     pub visual_start: Option<Cursor>,
+    pub visual_kind: VisualKind,
+    pub marks: BTreeMap<char, Cursor>,
+    pub history: History,
+    pub options: BufferOptions,
+    pub block_insert: Option<BlockInsert>,
+    pub saved_hash: u64,
+    pub modified: bool,
+    pub changed: bool,
+    pub read_only: bool,
+    pub mixed_line_endings: bool,
+    pub loading: bool,
+    pub mode_before_minibuffer: Option<Mode>,
 }
 
 impl Buffer {
@@ -123,6 +169,7 @@ impl Buffer {
         path: Option<PathBuf>,
         kind: BufferKind,
         height: usize,
+        scrolloff: usize,
         state: BufferState,
     ) -> Self {
         let content = if content.is_empty() {
@@ -130,6 +177,7 @@ impl Buffer {
         } else {
             content
         };
+        let saved_hash = content_hash(&content);
 
         Buffer {
             title,
@@ -137,17 +185,29 @@ impl Buffer {
             path,
             kind,
             cursor: Cursor::default(),
-            viewport: Viewport::new(height - 2),
+            viewport: Viewport::new(height - 2, scrolloff),
             mode: Mode::Normal,
             state,
             command_line: CommandLine::default(),
             visual_start: None,
+            visual_kind: VisualKind::Char,
+            marks: BTreeMap::new(),
+            history: History::default(),
+            options: BufferOptions::default(),
+            block_insert: None,
+            mode_before_minibuffer: None,
+            saved_hash,
+            modified: false,
+            changed: false,
+            read_only: false,
+            mixed_line_endings: false,
+            loading: false,
         }
     }
 
     // The scratch buffer is similar to the one in Emacs. It's a free buffer with no file to save
     // to, meant to test configuration options (when that's available).
-    pub fn scratch(height: usize) -> Self {
+    pub fn scratch(height: usize, scrolloff: usize) -> Self {
         Buffer {
             title: "*Scratch*".to_string(),
             content: vec![
@@ -159,45 +219,118 @@ impl Buffer {
             path: None,
             kind: BufferKind::Normal,
             cursor: Cursor::default(),
-            viewport: Viewport::new(height - 2),
+            viewport: Viewport::new(height - 2, scrolloff),
             mode: Mode::Normal,
             state: BufferState::scratch(),
             command_line: CommandLine::default(),
             visual_start: None,
+            visual_kind: VisualKind::Char,
+            marks: BTreeMap::new(),
+            history: History::default(),
+            options: BufferOptions::default(),
+            block_insert: None,
+            mode_before_minibuffer: None,
+            saved_hash: 0,
+            modified: false,
+            changed: false,
+            read_only: false,
+            mixed_line_endings: false,
+            loading: false,
         }
     }
 
     // The buffer list is similar to the one in Emacs. It's a list of the open buffers and when one
     // is pressed the editor switches to that buffer.
-    pub fn buffer_list(height: usize) -> Self {
+    pub fn buffer_list(height: usize, scrolloff: usize) -> Self {
         Buffer {
             title: "*Buffers*".to_string(),
             content: vec![String::new()],
             path: None,
             kind: BufferKind::BufferList,
             cursor: Cursor::default(),
-            viewport: Viewport::new(height - 2),
+            viewport: Viewport::new(height - 2, scrolloff),
             mode: Mode::Normal,
             state: BufferState::locked(),
             command_line: CommandLine::default(),
             visual_start: None,
+            visual_kind: VisualKind::Char,
+            marks: BTreeMap::new(),
+            history: History::default(),
+            options: BufferOptions::default(),
+            block_insert: None,
+            mode_before_minibuffer: None,
+            saved_hash: 0,
+            modified: false,
+            changed: false,
+            read_only: false,
+            mixed_line_endings: false,
+            loading: false,
         }
     }
 
-    pub async fn from_file(path: PathBuf, height: usize) -> Result<Self> {
-        let mut content = String::new();
+    // A placeholder shown while the file is read in the background, which can't be edited. It's
+    // replaced by the buffer of the file once that's done.
+    pub fn loading(path: PathBuf, height: usize, scrolloff: usize) -> Self {
+        let title = match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => "[NO NAME]".to_string(),
+        };
+
+        let mut buffer = Buffer::new(
+            title.clone(),
+            vec![format!("Loading {}…", title)],
+            Some(path),
+            BufferKind::Normal,
+            height,
+            scrolloff,
+            BufferState::new(true, false),
+        );
+        buffer.loading = true;
+
+        buffer
+    }
+
+    // A buffer with the changes rescued from a crash, bound to the file they came from. It counts
+    // as modified until it's written, whatever the file contains.
+    pub fn recovered(
+        title: String,
+        content: Vec<String>,
+        path: Option<PathBuf>,
+        height: usize,
+        scrolloff: usize,
+    ) -> Self {
+        let mut buffer = Buffer::new(
+            title,
+            content,
+            path,
+            BufferKind::Normal,
+            height,
+            scrolloff,
+            BufferState::default(),
+        );
+        buffer.saved_hash = !buffer.saved_hash;
+        buffer.modified = true;
+        buffer.options.expand_tab = !indented_with_tabs(&buffer.content);
+
+        buffer
+    }
 
-        let file = File::open(path.clone())?;
-        let mut buf_reader = BufReader::new(file);
+    pub async fn from_file(path: PathBuf, height: usize, scrolloff: usize) -> Result<Self> {
+        let FileLines {
+            lines: content,
+            file_format,
+            end_of_line,
+            mixed_line_endings,
+        } = read_lines(&path).await?;
         // If it can't find the name of the file, it won't display an empty string
         let mut file_name = "[NO NAME]".to_string();
 
-        buf_reader.read_to_string(&mut content)?;
-
         if let Some(name_osstr) = path.file_name() {
             file_name = name_osstr.to_string_lossy().into_owned();
         }
-        let content: Vec<String> = content.split("\n").map(|line| line.to_string()).collect();
+        let expand_tab = !indented_with_tabs(&content);
+        let saved_hash = content_hash(&content);
+        let read_only = is_read_only(&path);
 
         Ok(Buffer {
             title: file_name,
@@ -205,36 +338,188 @@ impl Buffer {
             path: Some(path),
             kind: BufferKind::Normal,
             cursor: Cursor::default(),
-            viewport: Viewport::new(height - 2),
+            viewport: Viewport::new(height - 2, scrolloff),
             mode: Mode::Normal,
             state: BufferState::default(),
             command_line: CommandLine::default(),
             visual_start: None,
+            visual_kind: VisualKind::Char,
+            marks: BTreeMap::new(),
+            history: History::default(),
+            options: BufferOptions {
+                expand_tab,
+                file_format,
+                end_of_line,
+                ..BufferOptions::default()
+            },
+            block_insert: None,
+            mode_before_minibuffer: None,
+            saved_hash,
+            modified: false,
+            changed: false,
+            read_only,
+            mixed_line_endings,
+            loading: false,
         })
     }
 
-    // Writes the buffer content to it's source file, if there is one. It's async as to not disable
+    // Writes the buffer content to the given path, or to it's source file if there is none. Writing
+    // to a new path makes it the source file of the buffer. Read-only files are only written when
+    // forced, by making them writable for the duration of the write, and the same goes for
+    // creating the directories of the path. It's async as to not disable
     // the editor in case something happens.
-    pub async fn write_buffer(&mut self) -> Result<()> {
+    pub async fn write_buffer(&mut self, path: Option<PathBuf>, force: bool) -> Result<()> {
         if !self.state.mutable {
-            return Err(Error::FileNotFoundError);
+            return Err(Error::ReadOnlyError);
+        }
+
+        let path = match path.or_else(|| self.path.clone()) {
+            Some(path) => path,
+            None => return Err(Error::NoFileNameError),
+        };
+
+        let read_only = path.exists() && is_read_only(&path);
+
+        if read_only && !force {
+            return Err(Error::ReadOnlyFileError);
+        }
+
+        // Missing directories are only created when forced, so a typo doesn't create them
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            if !dir.exists() {
+                if !force {
+                    return Err(Error::NoDirectoryError);
+                }
+
+                fs::create_dir_all(dir).map_err(write_error)?;
+            }
+        }
+
+        if self.options.trim_whitespace {
+            self.trim_trailing_whitespace();
+        }
+
+        let line_ending = self.options.file_format.line_ending();
+        let mut content_str = self.content.join(line_ending);
+
+        // The last line only ends in a newline if the file had one, or it's forced by 'fixeol'. An
+        // empty buffer is written as an empty file either way.
+        if (self.options.end_of_line || self.options.fix_end_of_line) && !content_str.is_empty() {
+            content_str.push_str(line_ending);
+        }
+
+        let permissions = fs::metadata(&path).map(|metadata| metadata.permissions());
+
+        if let (true, Ok(permissions)) = (read_only, &permissions) {
+            let mut writable = permissions.clone();
+            writable.set_mode(permissions.mode() | 0o200);
+            fs::set_permissions(&path, writable).map_err(write_error)?;
         }
 
-        if let Some(path) = &self.path {
-            let content_str = self.content.join("\n");
-            let content_b = content_str.as_bytes();
-            let mut file = File::create(&path)?;
+        let written =
+            File::create(&path).and_then(|mut file| file.write_all(content_str.as_bytes()));
+
+        // The permissions are restored even if the write failed
+        if let (true, Ok(permissions)) = (read_only, permissions) {
+            fs::set_permissions(&path, permissions).map_err(write_error)?;
+        }
+
+        written.map_err(write_error)?;
+
+        if self.path.as_ref() != Some(&path) {
+            if let Some(name_osstr) = path.file_name() {
+                self.title = name_osstr.to_string_lossy().into_owned();
+            }
 
-            file.write_all(content_b)?;
+            self.path = Some(path.clone());
+            self.state = BufferState::default();
         }
 
+        self.read_only = is_read_only(&path);
+        self.saved_hash = content_hash(&self.content);
+        self.modified = false;
+        self.changed = false;
+
         Ok(())
     }
 
+    // Removes the spaces and tabs at the end of every line. With 'fixendofline' on, the empty
+    // lines at the end of the buffer go as well, so the file ends in exactly one newline.
+    pub fn trim_trailing_whitespace(&mut self) {
+        self.save_undo_state();
+
+        for line in self.content.iter_mut() {
+            line.truncate(line.trim_end_matches([' ', '\t']).len());
+        }
+
+        if self.options.fix_end_of_line {
+            while self.content.len() > 1 && self.content[self.content.len() - 1].is_empty() {
+                self.content.pop();
+            }
+        }
+
+        self.history.discard_unchanged(&self.content);
+
+        self.cursor.y = self.cursor.y.min(self.content.len() - 1);
+        self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+    }
+
+    // Replaces the content with the recovered changes, which can be undone like any other change.
+    pub fn recover(&mut self, content: Vec<String>) {
+        self.save_undo_state();
+
+        self.content = if content.is_empty() {
+            vec![String::new()]
+        } else {
+            content
+        };
+        self.history.discard_unchanged(&self.content);
+
+        self.cursor.y = self.cursor.y.min(self.content.len() - 1);
+        self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+    }
+
     pub fn switch_mode(&mut self, mode: ModeParams) {
+        // The minibuffer opens on top of the current mode, which is left as it is so escaping can
+        // return to it. Switching to any other mode leaves that mode as well.
+        if mode == ModeParams::Minibuffer {
+            if self.mode != Mode::Minibuffer {
+                self.mode_before_minibuffer = Some(self.mode);
+            }
+
+            self.mode = Mode::Minibuffer;
+            return;
+        }
+
+        if let Some(previous) = self.mode_before_minibuffer.take() {
+            self.mode = previous;
+        }
+
+        // Switching between the visual kinds keeps the selection, and switching to the same kind
+        // leaves Visual mode, like in Vim
+        if let (Mode::Visual, ModeParams::Visual { kind }) = (self.mode, &mode) {
+            if *kind != self.visual_kind {
+                self.visual_kind = *kind;
+                return;
+            }
+
+            return self.switch_mode(ModeParams::Normal);
+        }
+
         // Makes sure to reset the visual cursors and command line values
         match self.mode {
             Mode::Visual => self.visual_start = None,
+            // Entering Insert mode records an undo step, which is dropped if nothing was typed
+            Mode::Insert => {
+                self.finish_block_insert();
+                self.history.discard_unchanged(&self.content);
+
+                // The cursor counts characters again outside of Insert mode
+                let line = &self.content[self.cursor.y];
+                self.cursor.x = line[..self.cursor.x.min(line.len())].chars().count();
+            }
             Mode::Command => {
                 self.command_line.prefix = String::new();
                 self.command_line.input = String::new();
@@ -244,8 +529,9 @@ impl Buffer {
         }
 
         match mode {
-            ModeParams::Visual => {
+            ModeParams::Visual { kind } => {
                 self.visual_start = Some(self.cursor);
+                self.visual_kind = kind;
                 self.mode = Mode::Visual;
             }
             ModeParams::Command { prefix, input } => {
@@ -258,6 +544,13 @@ impl Buffer {
             }
             ModeParams::Insert { insert_direction } => {
                 if self.state.mutable {
+                    // Everything typed until Insert mode is left is undone in one step
+                    self.save_undo_state();
+
+                    // The cursor counts bytes in Insert mode, so it can be used to index the line
+                    let line = &self.content[self.cursor.y];
+                    self.cursor.x = byte_index(line, self.cursor.x);
+
                     match insert_direction {
                         InsertDirection::Beginning => {
                             if let Some(index) = self.content[self.cursor.y]
@@ -270,9 +563,7 @@ impl Buffer {
                         }
                         InsertDirection::Before => {}
                         InsertDirection::After => {
-                            if self.content[self.cursor.y].len() > self.cursor.x {
-                                self.cursor.x += 1;
-                            }
+                            self.cursor.x = next_grapheme(line, self.cursor.x);
                         }
                         InsertDirection::End => self.cursor.x = self.content[self.cursor.y].len(),
                     }
@@ -280,40 +571,664 @@ impl Buffer {
                     self.mode = Mode::Insert;
                 }
             }
-            ModeParams::Normal => self.mode = Mode::Normal,
+            ModeParams::Normal => {
+                self.mode = Mode::Normal;
+
+                // Leaving Insert mode at the end of a line puts the cursor back on the last
+                // character.
+                self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+                self.cursor.desired_x = self.cursor.x;
+            }
             ModeParams::Minibuffer => self.mode = Mode::Minibuffer,
         }
     }
 
+    // Returns to the mode the minibuffer was opened from, with its selection still in place.
+    pub fn leave_minibuffer(&mut self) {
+        self.mode = self.mode_before_minibuffer.take().unwrap_or(Mode::Normal);
+    }
+
+    // Returns the last column the cursor can be on. Insert mode allows the cursor to sit right after
+    // the last character, while the other modes keep it on the last character.
+    pub fn max_cursor_x(&self) -> usize {
+        let line = &self.content[self.cursor.y];
+
+        match self.mode {
+            Mode::Insert => line.len(),
+            _ => line[..prev_grapheme(line, line.len())].chars().count(),
+        }
+    }
+
+    // Returns the byte index of the cursor in its line. The cursor's x counts bytes in Insert mode
+    // and characters in the other modes.
+    pub fn cursor_index(&self) -> usize {
+        let line = &self.content[self.cursor.y];
+
+        match self.mode {
+            Mode::Insert => self.cursor.x.min(line.len()),
+            _ => byte_index(line, self.cursor.x),
+        }
+    }
+
+    // Moves the cursor to the byte index of its line, in the unit the current mode counts in.
+    pub fn set_cursor_index(&mut self, index: usize) {
+        let line = &self.content[self.cursor.y];
+        let index = grapheme_start(line, index);
+
+        self.cursor.x = match self.mode {
+            Mode::Insert => index,
+            _ => line[..index].chars().count(),
+        };
+    }
+
+    // Stores the cursor position under the given name. Only lowercase letters are valid names.
+    pub fn set_mark(&mut self, name: char) -> Result<()> {
+        if !name.is_ascii_lowercase() {
+            return Err(Error::InvalidMarkError);
+        }
+
+        self.marks.insert(name, self.cursor);
+
+        Ok(())
+    }
+
+    // Moves the cursor to the mark with the given name. The position is clamped to the buffer in
+    // case lines were removed since the mark was set. The position before the jump is stored in
+    // the '`' mark, so '``' jumps back and forth.
+    pub fn goto_mark(&mut self, name: char) -> Result<()> {
+        let mark = match self.marks.get(&name) {
+            Some(mark) => *mark,
+            None => return Err(Error::MarkNotSetError),
+        };
+
+        self.marks.insert('`', self.cursor);
+
+        self.cursor.y = mark.y.min(self.content.len() - 1);
+        self.cursor.x = mark.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+
+        Ok(())
+    }
+
+    // Returns the start and end of the visual selection in order. The end is exclusive, so the
+    // character under the end of the selection is included. Line selections go from the start of
+    // the first line to the end of the last line, and block selections go from the top left to the
+    // bottom right corner.
+    pub fn selection(&self) -> Option<(Cursor, Cursor)> {
+        let start = self.visual_start?;
+
+        let (top, bottom) =
+            if start.y < self.cursor.y || (start.y == self.cursor.y && start.x <= self.cursor.x) {
+                (start, self.cursor)
+            } else {
+                (self.cursor, start)
+            };
+
+        // Block selections cover the columns between the start and the cursor on every line
+        if self.visual_kind == VisualKind::Block {
+            let left = start.x.min(self.cursor.x);
+            let right = start.x.max(self.cursor.x) + 1;
+
+            return Some((
+                Cursor {
+                    x: left,
+                    y: top.y,
+                    desired_x: left,
+                },
+                Cursor {
+                    x: right,
+                    y: bottom.y,
+                    desired_x: right,
+                },
+            ));
+        }
+
+        // Line selections cover the lines from their start to their end
+        if self.visual_kind == VisualKind::Line {
+            let end_x = self.content[bottom.y].chars().count();
+
+            return Some((
+                Cursor {
+                    x: 0,
+                    y: top.y,
+                    desired_x: 0,
+                },
+                Cursor {
+                    x: end_x,
+                    y: bottom.y,
+                    desired_x: end_x,
+                },
+            ));
+        }
+
+        let end_x = (bottom.x + 1).min(self.content[bottom.y].chars().count());
+
+        Some((
+            top,
+            Cursor {
+                x: end_x,
+                y: bottom.y,
+                desired_x: end_x,
+            },
+        ))
+    }
+
+    // Returns the text between the two positions, with the end being exclusive. The start has to
+    // come before the end.
+    pub fn text_in_range(&self, start: Cursor, end: Cursor) -> Vec<String> {
+        let start_x = byte_index(&self.content[start.y], start.x);
+        let end_x = byte_index(&self.content[end.y], end.x);
+
+        if start.y == end.y {
+            return vec![self.content[start.y][start_x..end_x].to_string()];
+        }
+
+        let mut text = vec![self.content[start.y][start_x..].to_string()];
+        text.extend_from_slice(&self.content[start.y + 1..end.y]);
+        text.push(self.content[end.y][..end_x].to_string());
+
+        text
+    }
+
+    // Removes the text between the two positions, with the end being exclusive, and joins what's
+    // left of the first and last line.
+    pub fn delete_range(&mut self, start: Cursor, end: Cursor) {
+        let start_x = byte_index(&self.content[start.y], start.x);
+        let end_x = byte_index(&self.content[end.y], end.x);
+
+        let remaining = self.content[end.y][end_x..].to_string();
+
+        self.content[start.y].truncate(start_x);
+        self.content[start.y].push_str(&remaining);
+        self.content.drain(start.y + 1..=end.y);
+    }
+
+    // Copies the given amount of lines, starting at the current line. The amount is clamped to the
+    // lines that are left.
+    pub fn yank_lines(&self, count: usize) -> Register {
+        let end = (self.cursor.y + count).min(self.content.len());
+
+        Register::new(
+            self.content[self.cursor.y..end].to_vec(),
+            RegisterKind::Linewise,
+        )
+    }
+
+    // Copies the character under the cursor, if there is one.
+    pub fn yank_char(&self) -> Option<Register> {
+        let line = &self.content[self.cursor.y];
+
+        line.chars()
+            .nth(self.cursor.x)
+            .map(|c| Register::new(vec![c.to_string()], RegisterKind::Charwise))
+    }
+
+    // Copies the visual selection. Line selections are copied as whole lines.
+    pub fn yank_selection(&self) -> Option<Register> {
+        let (start, end) = self.selection()?;
+
+        match self.visual_kind {
+            VisualKind::Char => Some(Register::new(
+                self.text_in_range(start, end),
+                RegisterKind::Charwise,
+            )),
+            VisualKind::Line => Some(Register::new(
+                self.content[start.y..=end.y].to_vec(),
+                RegisterKind::Linewise,
+            )),
+            VisualKind::Block => Some(Register::new(
+                self.content[start.y..=end.y]
+                    .iter()
+                    .map(|line| line.chars().skip(start.x).take(end.x - start.x).collect())
+                    .collect(),
+                RegisterKind::Blockwise,
+            )),
+        }
+    }
+
+    // Returns the line comment prefix for the file type, based on the file extension.
+    pub fn comment_prefix(&self) -> &'static str {
+        let extension = self
+            .path
+            .as_ref()
+            .and_then(|path| path.extension())
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+
+        match extension.as_deref() {
+            Some("py" | "toml" | "sh" | "bash" | "zsh" | "yaml" | "yml" | "rb" | "pl" | "conf") => {
+                "#"
+            }
+            Some("lua" | "sql" | "hs") => "--",
+            Some("vim") => "\"",
+            Some("lisp" | "el" | "clj" | "scm") => ";;",
+            Some("tex") => "%",
+            _ => "//",
+        }
+    }
+
+    // Records the current state so the next change can be undone. The cursor is recorded in
+    // characters, as it's restored in Normal mode, while it counts bytes in Insert mode.
+    pub fn save_undo_state(&mut self) {
+        let mut cursor = self.cursor;
+
+        if self.mode == Mode::Insert {
+            cursor.x = self.content[cursor.y][..cursor.x].chars().count();
+        }
+
+        self.history.record(&self.content, cursor);
+        self.changed = true;
+    }
+
+    // Ends the change typed in Insert mode and starts a new one, so the text typed before and after
+    // moving the cursor is undone separately, like in Vim.
+    pub fn break_undo_sequence(&mut self) {
+        if self.mode == Mode::Insert && self.state.mutable {
+            self.history.discard_unchanged(&self.content);
+            self.save_undo_state();
+        }
+    }
+
+    // Reverts the last change and puts the cursor where it happened.
+    pub fn undo(&mut self) -> Result<()> {
+        let content = self.content.clone();
+
+        match self.history.undo(content) {
+            Some(snapshot) => {
+                self.restore(snapshot);
+                Ok(())
+            }
+            None => Err(Error::NoUndoError),
+        }
+    }
+
+    // Reapplies the last undone change.
+    pub fn redo(&mut self) -> Result<()> {
+        let content = self.content.clone();
+
+        match self.history.redo(content) {
+            Some(snapshot) => {
+                self.restore(snapshot);
+                Ok(())
+            }
+            None => Err(Error::NoRedoError),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.content = snapshot.content;
+        self.changed = true;
+
+        self.cursor.y = snapshot.cursor.y.min(self.content.len() - 1);
+        self.cursor.x = snapshot.cursor.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Enters Insert mode at the start or the end of the selection, like 'I' and 'A' in Visual
+    // mode. In block selections, the typed text is inserted on every selected line.
+    pub fn start_visual_insert(&mut self, append: bool) {
+        let Some((start, end)) = self.selection() else {
+            return;
+        };
+
+        if !self.state.mutable {
+            return;
+        }
+
+        let kind = self.visual_kind;
+        self.switch_mode(ModeParams::Normal);
+
+        if kind == VisualKind::Block {
+            let x = if append { end.x } else { start.x };
+            let line_len = self.content[start.y].chars().count();
+
+            self.cursor.y = start.y;
+            self.cursor.x = x.min(line_len);
+            self.block_insert = Some(BlockInsert {
+                top: start.y,
+                bottom: end.y,
+                x: self.cursor.x,
+                line_len,
+            });
+        } else {
+            let position = if append { end } else { start };
+
+            self.cursor.y = position.y;
+            self.cursor.x = position.x;
+        }
+
+        self.cursor.desired_x = self.cursor.x;
+
+        self.switch_mode(ModeParams::Insert {
+            insert_direction: InsertDirection::Before,
+        });
+    }
+
+    // Copies the text typed on the first line of a block insert to the other lines of the block.
+    // Lines that are too short to reach the block are skipped, and nothing is copied if the typed
+    // text spans multiple lines.
+    fn finish_block_insert(&mut self) {
+        let Some(insert) = self.block_insert.take() else {
+            return;
+        };
+
+        if self.cursor.y != insert.top {
+            return;
+        }
+
+        let line = &self.content[insert.top];
+        let typed_len = line.chars().count().saturating_sub(insert.line_len);
+        let typed: String = line.chars().skip(insert.x).take(typed_len).collect();
+
+        for y in insert.top + 1..=insert.bottom {
+            let line = &mut self.content[y];
+
+            if line.chars().count() >= insert.x {
+                line.insert_str(byte_index(line, insert.x), &typed);
+            }
+        }
+    }
+
+    // Returns the name of the mode shown in the statusline.
+    pub fn mode_name(&self) -> String {
+        match (self.mode, self.visual_kind) {
+            (Mode::Visual, VisualKind::Line) => "VISUAL LINE".to_string(),
+            (Mode::Visual, VisualKind::Block) => "VISUAL BLOCK".to_string(),
+            (mode, _) => mode.to_string(),
+        }
+    }
+
     // Returns the current command from the command line.
     pub fn get_command(&mut self) -> &str {
         &self.command_line.input
     }
 
     pub async fn load_file(&mut self, path: &PathBuf) -> Result<()> {
-        // Checks if the path points to a file.
-        if path.is_file() {
-            let mut content = String::new();
+        let file_lines = read_lines(path).await?;
 
-            let file = File::open(&path)?;
-            let mut buf_reader = BufReader::new(&file);
-            buf_reader.read_to_string(&mut content)?;
+        self.content = file_lines.lines;
+        self.options.file_format = file_lines.file_format;
+        self.options.end_of_line = file_lines.end_of_line;
+        self.mixed_line_endings = file_lines.mixed_line_endings;
 
-            // If the program can't fetch the name of the file, it's displayed like this.
-            self.title = "[NO NAME]".to_string();
+        // If the program can't fetch the name of the file, it's displayed like this.
+        self.title = "[NO NAME]".to_string();
 
-            // Tries to fetch the file name.
-            if let Some(name_osstr) = Path::new(&path).file_name() {
-                self.title = name_osstr.to_string_lossy().into_owned();
-            }
+        // Tries to fetch the file name.
+        if let Some(name_osstr) = Path::new(&path).file_name() {
+            self.title = name_osstr.to_string_lossy().into_owned();
+        }
 
-            self.content = content.split("\n").map(|line| line.to_string()).collect();
+        self.options.expand_tab = !indented_with_tabs(&self.content);
+        self.saved_hash = content_hash(&self.content);
+        self.modified = false;
+        self.changed = false;
+        self.read_only = is_read_only(path);
+        self.history.clear();
 
-            self.path = Some(path.clone());
+        // The cursor is kept where it was, as far as the new content allows
+        self.visual_start = None;
+        self.cursor.y = self.cursor.y.min(self.content.len() - 1);
+        self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        self.viewport.adjust(self.cursor.y, self.content.len());
+
+        self.path = Some(path.clone());
+
+        Ok(())
+    }
+
+    // Inserts the lines below the cursor line as one undo step, and moves the cursor to the first
+    // of them.
+    pub fn insert_lines_below(&mut self, lines: Vec<String>) -> Result<()> {
+        if !self.state.mutable {
+            return Err(Error::ReadOnlyError);
+        }
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        self.save_undo_state();
+
+        let y = self.cursor.y + 1;
+        self.content.splice(y..y, lines);
+
+        self.cursor.y = y;
+        self.cursor.x = 0;
+        self.viewport.adjust(self.cursor.y, self.content.len());
 
-            Ok(())
+        Ok(())
+    }
+
+    // Reads the file into the buffer below the cursor line, like ':r' in Vim.
+    pub async fn read_file(&mut self, path: &Path) -> Result<()> {
+        let lines = read_lines(path).await?.lines;
+
+        self.insert_lines_below(lines)
+    }
+
+    // Tells the user that the line endings of the file were mixed, since writing it converts them.
+    pub fn line_ending_note(&self) -> Option<String> {
+        self.mixed_line_endings.then(|| {
+            format!(
+                "Mixed line endings, written as {}",
+                self.options.file_format.name()
+            )
+        })
+    }
+
+    // Checks if the content has changed since it was loaded or last written. A buffer without a
+    // file counts as modified once it's edited, as the changes would be lost. The buffers the
+    // editor owns, like the scratch buffer, are never saved, so they're never modified. The content
+    // is only hashed when it may have changed since the flag was last updated.
+    pub fn is_modified(&self) -> bool {
+        if self.changed {
+            self.state.killable && content_hash(&self.content) != self.saved_hash
         } else {
-            Err(Error::FileNotFoundError)
+            self.modified
+        }
+    }
+
+    // Caches the modified flag, so it isn't computed again until the content changes. Every edit
+    // outside Insert mode saves an undo state first, which marks the content as changed, while the
+    // edits in Insert mode are marked by the editor.
+    pub fn update_modified(&mut self) {
+        if self.changed {
+            self.modified = self.is_modified();
+            self.changed = false;
+        }
+    }
+
+    // Returns the title with a '[+]' after it when the buffer has unsaved changes.
+    pub fn display_title(&self) -> String {
+        if self.is_modified() {
+            format!("{} [+]", self.title)
+        } else {
+            self.title.clone()
+        }
+    }
+
+    // Returns the path of the file with the home directory shortened to '~', or the title for
+    // buffers without a file.
+    pub fn display_path(&self) -> String {
+        let Some(path) = &self.path else {
+            return self.title.clone();
+        };
+
+        match std::env::var("HOME") {
+            Ok(home) if !home.is_empty() => match path.strip_prefix(&home) {
+                Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+                Ok(rest) => format!("~/{}", rest.display()),
+                Err(_) => path.display().to_string(),
+            },
+            _ => path.display().to_string(),
+        }
+    }
+
+    // Returns the file type, named after the file extension.
+    pub fn file_type(&self) -> Option<String> {
+        let extension = self
+            .path
+            .as_ref()
+            .and_then(|path| path.extension())
+            .map(|extension| extension.to_string_lossy().to_lowercase())?;
+
+        let name = match extension.as_str() {
+            "rs" => "rust",
+            "py" => "python",
+            "js" | "mjs" | "cjs" => "javascript",
+            "ts" => "typescript",
+            "rb" => "ruby",
+            "sh" | "bash" | "zsh" => "shell",
+            "md" => "markdown",
+            "yml" => "yaml",
+            "h" => "c",
+            "hpp" | "cc" | "cxx" => "cpp",
+            "hs" => "haskell",
+            "el" => "elisp",
+            "tex" => "latex",
+            extension => extension,
+        };
+
+        Some(name.to_string())
+    }
+
+    // Returns the column the cursor is displayed at, with tabs expanded to the next tab stop.
+    pub fn cursor_column(&self) -> usize {
+        let line = &self.content[self.cursor.y];
+
+        display_width(&line[..self.cursor_index()], self.options.tab_width)
+    }
+
+    // Returns the columns the rows of the line start at when the lines wrap at the width. The
+    // cell after the line only gets a row of its own when the cursor can be on it, in Insert mode.
+    pub fn line_rows(&self, y: usize, width: usize) -> Vec<usize> {
+        let line = &self.content[y];
+        let mut rows = wrap_columns(line, width, self.options.tab_width);
+
+        let at_line_end = self.mode == Mode::Insert && self.cursor.y == y;
+
+        if rows.len() > 1
+            && !at_line_end
+            && rows[rows.len() - 1] == display_width(line, self.options.tab_width)
+        {
+            rows.pop();
         }
+
+        rows
     }
+
+    // Returns the row of its line the cursor is on when the lines wrap at the width, and the
+    // column of the cursor within that row.
+    pub fn cursor_row(&self, width: usize) -> (usize, usize) {
+        let column = self.cursor_column();
+        let rows = self.line_rows(self.cursor.y, width);
+        let row = rows.iter().rposition(|start| *start <= column).unwrap_or(0);
+
+        (row, column - rows[row])
+    }
+
+    // Scrolls long lines so the cursor stays visible, or scrolls down far enough for the cursor's
+    // row to be visible when the lines wrap at the width.
+    pub fn scroll_to_cursor(&mut self, width: usize, wrap: bool) {
+        if !wrap {
+            self.viewport.adjust_horizontal(self.cursor_column(), width);
+            return;
+        }
+
+        let (cursor_row, _) = self.cursor_row(width);
+        let rows: Vec<usize> = (self.viewport.top..self.cursor.y)
+            .map(|y| self.line_rows(y, width).len())
+            .collect();
+        let top = self.viewport.top;
+
+        self.viewport.left = 0;
+        self.viewport
+            .adjust_wrapped(self.cursor.y, cursor_row, |y| rows[y - top]);
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Buffer Helpers                       │
+// ╰──────────────────────────────────────╯
+
+// Reads the lines of a file, which is shared by everything that loads files into buffers. The
+// carriage returns of CRLF line endings are left out of the lines, and the line ending most of
+// the lines use becomes the one the file is written with. The newline at the end of the file
+// doesn't start another line, but is remembered so the file is written the same way.
+async fn read_lines(path: &Path) -> Result<FileLines> {
+    let is_file = tokio::fs::metadata(path)
+        .await
+        .is_ok_and(|metadata| metadata.is_file());
+
+    if !is_file {
+        return Err(Error::FileNotFoundError);
+    }
+
+    // The file is read by tokio on a blocking thread, so a large file doesn't hold up the runtime
+    let content = tokio::fs::read_to_string(path).await?;
+
+    let mut lines: Vec<String> = content.split("\n").map(|line| line.to_string()).collect();
+    let last = lines.len() - 1;
+    let mut dos_lines = 0;
+
+    // The last line has no line ending
+    for line in &mut lines[..last] {
+        if line.ends_with('\r') {
+            line.pop();
+            dos_lines += 1;
+        }
+    }
+
+    let file_format = if dos_lines * 2 > last {
+        FileFormat::Dos
+    } else {
+        FileFormat::Unix
+    };
+
+    let end_of_line = lines.len() > 1 && lines[last].is_empty();
+
+    if end_of_line {
+        lines.pop();
+    }
+
+    Ok(FileLines {
+        lines,
+        file_format,
+        end_of_line,
+        mixed_line_endings: dos_lines > 0 && dos_lines < last,
+    })
+}
+
+// Checks if the file can't be written to, by opening it for writing without changing it.
+fn is_read_only(path: &Path) -> bool {
+    OpenOptions::new().append(true).open(path).is_err()
+}
+
+// Turns a failed write into an error that tells the user what went wrong.
+fn write_error(error: std::io::Error) -> Error {
+    match error.kind() {
+        std::io::ErrorKind::PermissionDenied => Error::PermissionDeniedError,
+        _ => Error::IoError(error),
+    }
+}
+
+// Hashes the content, so it can be compared to the saved content without keeping a copy of it.
+fn content_hash(content: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+// Checks if more lines are indented with tabs than with spaces, so files indented with tabs keep
+// being indented with tabs.
+fn indented_with_tabs(content: &[String]) -> bool {
+    let tabs = content.iter().filter(|line| line.starts_with('\t')).count();
+    let spaces = content.iter().filter(|line| line.starts_with(' ')).count();
+
+    tabs > spaces
 }
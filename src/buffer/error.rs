@@ -15,6 +15,16 @@ pub enum Error {
     ConvertToPathError,
     ReadDirectoryError,
     NoMatchError,
+    InvalidMarkError,
+    MarkNotSetError,
+    NoUndoError,
+    NoRedoError,
+    NoTextObjectError,
+    NoFileNameError,
+    ReadOnlyError,
+    ReadOnlyFileError,
+    PermissionDeniedError,
+    NoDirectoryError,
     IoError(std::io::Error),
 }
 
@@ -52,6 +62,29 @@ impl fmt::Display for Error {
             Error::NoMatchError => {
                 write!(f, "NoMatchError: Input did not match any of the entries")
             }
+            Error::InvalidMarkError => write!(f, "InvalidMarkError: Marks are named a-z"),
+            Error::MarkNotSetError => write!(f, "MarkNotSetError: Mark is not set"),
+            Error::NoUndoError => write!(f, "NoUndoError: Already at oldest change"),
+            Error::NoRedoError => write!(f, "NoRedoError: Already at newest change"),
+            Error::NoFileNameError => write!(f, "NoFileNameError: No file name"),
+            Error::ReadOnlyError => write!(f, "ReadOnlyError: Buffer is read-only"),
+            Error::ReadOnlyFileError => write!(
+                f,
+                "ReadOnlyFileError: File is read-only (add ! to override)"
+            ),
+            Error::NoDirectoryError => write!(
+                f,
+                "NoDirectoryError: Directory doesn't exist (add ! to create it)"
+            ),
+            Error::PermissionDeniedError => {
+                write!(f, "PermissionDeniedError: No permission to write the file")
+            }
+            Error::NoTextObjectError => {
+                write!(
+                    f,
+                    "NoTextObjectError: No matching text object around the cursor"
+                )
+            }
             Error::IoError(e) => write!(f, "{}", e),
         }
     }
@@ -0,0 +1,89 @@
+use crate::buffer::Cursor;
+
+// ╭──────────────────────────────────────╮
+// │ History Structs                      │
+// ╰──────────────────────────────────────╯
+
+// The buffer content and cursor position from before a change.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Snapshot {
+    pub content: Vec<String>,
+    pub cursor: Cursor,
+}
+
+// Holds the undo and redo stacks of a buffer.
+#[derive(Debug, Default, Eq, PartialEq, Hash, Clone)]
+pub struct History {
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+}
+
+impl History {
+    // Stores the state from before a change. The undone changes can't be redone after a new
+    // change, so the redo stack is cleared.
+    pub fn record(&mut self, content: &[String], cursor: Cursor) {
+        self.undo_stack.push(Snapshot {
+            content: content.to_vec(),
+            cursor,
+        });
+        self.redo_stack.clear();
+    }
+
+    // Removes the last recorded state if the content is still the same, so changes that didn't
+    // change anything don't take up an undo step.
+    pub fn discard_unchanged(&mut self, content: &[String]) {
+        if self
+            .undo_stack
+            .last()
+            .is_some_and(|snapshot| snapshot.content == content)
+        {
+            self.undo_stack.pop();
+        }
+    }
+
+    // Returns the state from before the last change. The current content is moved to the redo
+    // stack, with the cursor placed where the change happened.
+    pub fn undo(&mut self, content: Vec<String>) -> Option<Snapshot> {
+        let snapshot = self.undo_stack.pop()?;
+
+        self.redo_stack.push(Snapshot {
+            content,
+            cursor: snapshot.cursor,
+        });
+
+        Some(snapshot)
+    }
+
+    // Returns the state from before the last undo, and moves the current content back to the undo
+    // stack.
+    pub fn redo(&mut self, content: Vec<String>) -> Option<Snapshot> {
+        let snapshot = self.redo_stack.pop()?;
+
+        self.undo_stack.push(Snapshot {
+            content,
+            cursor: snapshot.cursor,
+        });
+
+        Some(snapshot)
+    }
+
+    // Returns the amount of changes that can be undone.
+    pub fn len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo_stack.is_empty()
+    }
+
+    // Merges the changes recorded after the given amount of changes into one, so they're undone
+    // together.
+    pub fn squash(&mut self, len: usize) {
+        self.undo_stack.truncate(len + 1);
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
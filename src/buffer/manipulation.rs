@@ -1,5 +1,10 @@
-use crate::buffer::{Buffer, CommandLine, Error, Minibuffer, MinibufferKind, Mode};
-use crate::keybinding::{ModeParams, NewLineDirection};
+use crate::buffer::{
+    byte_index, display_width, motion_range, next_grapheme, prev_grapheme, Buffer, CommandLine,
+    Error, Minibuffer, MinibufferKind, Mode, Register, RegisterKind, VisualKind,
+};
+use crate::keybinding::{
+    CaseChange, LineRange, ModeParams, Motion, NewLineDirection, Substitution,
+};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -8,7 +13,7 @@ pub trait Manipulation {
     fn add_tab(&mut self) -> Result<()>;
     fn new_line(&mut self, direction: NewLineDirection);
     fn remove_char(&mut self) -> Result<()>;
-    fn delete_line(&mut self);
+    fn delete_line(&mut self, count: usize) -> Option<Register>;
 }
 
 // TODO: Implement Manipulation for Command Line.
@@ -18,8 +23,26 @@ impl Manipulation for Buffer {
         // Minimizes repetetive code by editing the current line from either source.
         match self.mode {
             Mode::Insert => {
-                self.content[self.cursor.y].insert(self.cursor.x, character);
-                self.cursor.x += 1;
+                let line = &mut self.content[self.cursor.y];
+                let prev_char = line[..self.cursor.x].chars().next_back();
+                let next_char = line[self.cursor.x..].chars().next();
+
+                // Typing the closing character of a pair moves over it instead.
+                let skip = self.options.auto_pairs
+                    && next_char == Some(character)
+                    && is_closing(character);
+
+                if !skip {
+                    line.insert(self.cursor.x, character);
+
+                    if self.options.auto_pairs {
+                        if let Some(closing) = closing_pair(character, prev_char, next_char) {
+                            line.insert(self.cursor.x + character.len_utf8(), closing);
+                        }
+                    }
+                }
+
+                self.cursor.x += character.len_utf8();
             }
             Mode::Command => {
                 self.command_line.add_char(character)?;
@@ -30,14 +53,24 @@ impl Manipulation for Buffer {
         Ok(())
     }
 
+    // Inserts a tab character, or spaces up to the next tab stop if tabs are expanded.
     fn add_tab(&mut self) -> Result<()> {
-        let mut spaces = 4;
-
-        while (self.cursor.x + spaces) % 4 != 0 {
-            spaces -= 1;
+        if !self.options.expand_tab {
+            return self.add_char('\t');
         }
 
-        for _ in 0..spaces {
+        let column = match self.mode {
+            Mode::Insert => {
+                let line = &self.content[self.cursor.y];
+                display_width(
+                    line.get(..self.cursor.x).unwrap_or(line),
+                    self.options.tab_width,
+                )
+            }
+            _ => self.cursor.x,
+        };
+
+        for _ in 0..self.options.tab_width - column % self.options.tab_width {
             self.add_char(' ')?;
         }
 
@@ -55,6 +88,9 @@ impl Manipulation for Buffer {
             }
             Mode::Normal => {
                 if self.state.mutable {
+                    // The new line and the text typed on it are undone together
+                    self.save_undo_state();
+
                     match direction {
                         NewLineDirection::Under => {
                             self.content.insert(self.cursor.y + 1, String::new());
@@ -78,10 +114,43 @@ impl Manipulation for Buffer {
     fn remove_char(&mut self) -> Result<()> {
         match self.mode {
             Mode::Insert => {
-                if self.cursor.x > 0 {
-                    self.content[self.cursor.y].remove(self.cursor.x - 1);
+                let before = &self.content[self.cursor.y][..self.cursor.x];
+
+                // Inside the indentation, spaces are removed back to the previous tab stop, like
+                // they're added by add_tab.
+                if before.ends_with(' ') && before.trim_start().is_empty() {
+                    let column = display_width(before, self.options.tab_width);
+                    let stop = (column - 1) / self.options.tab_width * self.options.tab_width;
+                    let spaces = before
+                        .chars()
+                        .rev()
+                        .take_while(|c| *c == ' ')
+                        .count()
+                        .min(column - stop);
+
+                    self.cursor.x -= spaces;
+                    self.content[self.cursor.y]
+                        .replace_range(self.cursor.x..self.cursor.x + spaces, "");
+                } else if !before.is_empty() {
+                    // The whole grapheme before the cursor is removed, so a character isn't
+                    // split from its combining marks
+                    let line = &mut self.content[self.cursor.y];
+                    let start = prev_grapheme(line, self.cursor.x);
+                    let removed: String = line.drain(start..self.cursor.x).collect();
+                    self.cursor.x = start;
+
+                    // Removing the opening character of an empty pair removes both.
+                    let mut removed_chars = removed.chars();
 
-                    self.cursor.x -= 1;
+                    if let (Some(removed), None) = (removed_chars.next(), removed_chars.next()) {
+                        if self.options.auto_pairs
+                            && is_opening(removed)
+                            && line[self.cursor.x..].chars().next()
+                                == closing_pair(removed, None, None)
+                        {
+                            line.remove(self.cursor.x);
+                        }
+                    }
                 } else if self.cursor.y > 0 {
                     let current_line = self.content.remove(self.cursor.y);
 
@@ -93,81 +162,53 @@ impl Manipulation for Buffer {
             // Removes the character under the cursor, like 'x' in Neovim.
             Mode::Normal => {
                 if self.state.mutable {
-                    if self.cursor.x < self.content[self.cursor.y].len() {
-                        self.content[self.cursor.y].remove(self.cursor.x);
+                    let line = &self.content[self.cursor.y];
 
-                        if !self.content[self.cursor.y].is_empty()
-                            && self.cursor.x >= self.content[self.cursor.y].len() - 1
-                        {
-                            self.cursor.x -= 1;
-                        }
-                    } else if self.cursor.x == self.content[self.cursor.y].len()
-                        && !self.content[self.cursor.y].is_empty()
-                    {
-                        self.cursor.x -= 1;
+                    if self.cursor.x < line.chars().count() {
+                        let index = byte_index(line, self.cursor.x);
+                        let end = next_grapheme(line, index);
+
+                        self.save_undo_state();
+                        self.content[self.cursor.y].replace_range(index..end, "");
                     }
+
+                    // Keeps the cursor on the last character if the removed one was at the end.
+                    self.cursor.x = self.cursor.x.min(self.max_cursor_x());
                 }
             }
             // Removes the selected characters.
             Mode::Visual => {
-                if let Some(start) = self.visual_start {
+                if let Some((start, end)) = self.selection() {
                     if self.state.mutable {
-                        // Determine the top and bottom positions.
-                        let (top, bottom) = if start.y < self.cursor.y
-                            || (start.y == self.cursor.y && start.x <= self.cursor.x)
-                        {
-                            (start, self.cursor)
-                        } else {
-                            (self.cursor, start)
-                        };
-
-                        // Ensure indices are within bounds.
-                        if top.y >= self.content.len() || bottom.y >= self.content.len() {
-                            return Ok(()); // Early return for invalid indices.
-                        }
+                        self.save_undo_state();
 
-                        // Handle multi-line and single-line selection.
-                        if top.y == bottom.y {
-                            // Single-line selection.
-                            let line = &self.content[top.y];
-                            let new_line = if bottom.x < line.len() {
-                                let before = &line[..top.x];
-                                let after = &line[bottom.x..];
-                                format!("{}{}", before, after)
-                            } else {
-                                line[..top.x].to_string()
-                            };
-                            self.content[top.y] = new_line;
-                        } else {
-                            // Multi-line selection.
-
-                            // Check if the bottom line is fully selected.
-                            if bottom.x == 0 || bottom.x >= self.content[bottom.y].len() {
-                                self.content.remove(bottom.y);
-                            } else {
-                                // Modify the bottom line after the selection end.
-                                let bottom_line = &self.content[bottom.y];
-                                self.content[bottom.y] = bottom_line[bottom.x..].to_string();
-                            }
+                        match self.visual_kind {
+                            VisualKind::Char => self.delete_range(start, end),
+                            // Line selections remove the lines themselves
+                            VisualKind::Line => {
+                                self.content.drain(start.y..=end.y);
 
-                            // Remove all lines inbetween.
-                            for _ in (top.y + 1..bottom.y).rev() {
-                                self.content.remove(top.y + 1);
+                                if self.content.is_empty() {
+                                    self.content.push(String::new());
+                                }
                             }
-
-                            // Check if the top line is fully selected.
-                            if top.x == 0 && top.y < self.content.len() && self.content.len() > 1 {
-                                self.content.remove(top.y);
-                            } else {
-                                // Modify the top line up to the selection start.
-                                let top_line = &self.content[top.y];
-                                self.content[top.y] = top_line[..top.x].to_string();
+                            // Block selections remove the same columns from every line
+                            VisualKind::Block => {
+                                for y in start.y..=end.y {
+                                    self.content[y] = self.content[y]
+                                        .chars()
+                                        .enumerate()
+                                        .filter(|(x, _)| *x < start.x || *x >= end.x)
+                                        .map(|(_, c)| c)
+                                        .collect();
+                                }
                             }
                         }
 
-                        // Update the cursor and switch back to normal mode.
-                        self.cursor.x = top.x;
-                        self.cursor.y = top.y;
+                        // Update the cursor and switch back to normal mode, which also clamps the
+                        // cursor to the shortened line.
+                        self.cursor.x = start.x;
+                        self.cursor.y = start.y.min(self.content.len() - 1);
                         self.switch_mode(ModeParams::Normal);
                     }
                 }
@@ -179,21 +220,787 @@ impl Manipulation for Buffer {
         Ok(())
     }
 
-    // Deletes the current line.
-    fn delete_line(&mut self) {
+    // Deletes the given amount of lines, starting at the current line. The amount is clamped to the
+    // lines that are left. Returns the deleted lines so they can be stored in the register.
+    fn delete_line(&mut self, count: usize) -> Option<Register> {
+        if !self.state.mutable {
+            return None;
+        }
+
+        let register = self.yank_lines(count);
+        let end = (self.cursor.y + count).min(self.content.len());
+
+        self.save_undo_state();
+        self.content.drain(self.cursor.y..end);
+
+        if self.content.is_empty() {
+            self.content.push(String::new());
+        }
+
+        self.cursor.y = self.cursor.y.min(self.content.len() - 1);
+        self.cursor.x = 0;
+        self.cursor.desired_x = 0;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+
+        Some(register)
+    }
+}
+
+impl Buffer {
+    // Removes the character after the cursor in Insert mode, like the Delete key. At the end of the
+    // line the next line is joined up instead. Returns the removed text so it can be stored in the
+    // register.
+    pub fn delete_forward(&mut self) -> Option<Register> {
+        if self.mode != Mode::Insert {
+            return None;
+        }
+
+        let line = &mut self.content[self.cursor.y];
+
+        if self.cursor.x < line.len() {
+            let end = next_grapheme(line, self.cursor.x);
+            let removed: String = line.drain(self.cursor.x..end).collect();
+
+            Some(Register::new(vec![removed], RegisterKind::Charwise))
+        } else if self.cursor.y + 1 < self.content.len() {
+            let next_line = self.content.remove(self.cursor.y + 1);
+            self.content[self.cursor.y].push_str(&next_line);
+
+            // The removed line break is stored as an empty line on either side.
+            Some(Register::new(
+                vec![String::new(), String::new()],
+                RegisterKind::Charwise,
+            ))
+        } else {
+            None
+        }
+    }
+
+    // Removes the word before the cursor in Insert mode, like Ctrl-w in Vim. Whitespace between the
+    // word and the cursor is removed with it. At the start of the line, the line is joined to the
+    // previous one like with backspace.
+    pub fn delete_word_before(&mut self) -> Result<()> {
+        if self.mode != Mode::Insert {
+            return Err(Error::WrongModeError);
+        }
+
+        if self.cursor.x == 0 {
+            return self.remove_char();
+        }
+
+        let before = self.content[self.cursor.y][..self.cursor.x].trim_end();
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+        let start = match before.chars().next_back() {
+            Some(last) => {
+                let word_len: usize = before
+                    .chars()
+                    .rev()
+                    .take_while(|c| !c.is_whitespace() && is_word(*c) == is_word(last))
+                    .map(char::len_utf8)
+                    .sum();
+
+                before.len() - word_len
+            }
+            None => 0,
+        };
+
+        self.content[self.cursor.y].replace_range(start..self.cursor.x, "");
+        self.cursor.x = start;
+
+        Ok(())
+    }
+
+    // Removes the text between the indentation and the cursor in Insert mode, like Ctrl-u in Vim.
+    // Inside the indentation, everything before the cursor is removed, and at the start of the line
+    // the line is joined to the previous one.
+    pub fn delete_to_line_start(&mut self) -> Result<()> {
+        if self.mode != Mode::Insert {
+            return Err(Error::WrongModeError);
+        }
+
+        if self.cursor.x == 0 {
+            return self.remove_char();
+        }
+
+        let line = &self.content[self.cursor.y];
+        let indent = line.len() - line.trim_start().len();
+        let start = if self.cursor.x > indent { indent } else { 0 };
+
+        self.content[self.cursor.y].replace_range(start..self.cursor.x, "");
+        self.cursor.x = start;
+
+        Ok(())
+    }
+
+    // Deletes the text the motion covers, like 'dw' in Vim. Returns the deleted text so it can be
+    // stored in the register.
+    pub fn delete_motion(&mut self, motion: Motion) -> Result<Option<Register>> {
+        if !self.state.mutable {
+            return Ok(None);
+        }
+
+        let (start, end) =
+            motion_range(&self.content, self.cursor, motion).ok_or(Error::NoTextObjectError)?;
+
+        if start == end {
+            return Ok(None);
+        }
+
+        let text = self.text_in_range(start, end);
+
+        self.save_undo_state();
+        self.delete_range(start, end);
+
+        self.cursor.y = start.y;
+        self.cursor.x = start.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+
+        Ok(Some(Register::new(text, RegisterKind::Charwise)))
+    }
+
+    // Deletes the text the motion covers and enters Insert mode, like 'cw' in Vim. Returns the
+    // deleted text so it can be stored in the register.
+    pub fn change_motion(&mut self, motion: Motion) -> Result<Option<Register>> {
+        if !self.state.mutable {
+            return Ok(None);
+        }
+
+        let (start, mut end) =
+            motion_range(&self.content, self.cursor, motion).ok_or(Error::NoTextObjectError)?;
+
+        // Like in Vim, 'cw' on a word only changes the word, not the whitespace after it.
+        if motion == Motion::WordForward && start.y == end.y {
+            let line = &self.content[start.y];
+
+            if line[byte_index(line, start.x)..].starts_with(|c: char| !c.is_whitespace()) {
+                end.x = line[..byte_index(line, end.x)]
+                    .trim_end()
+                    .chars()
+                    .count()
+                    .max(start.x);
+            }
+        }
+
+        // The mode is set directly instead of through switch_mode, so the deletion and the typed
+        // text are undone together.
+        self.save_undo_state();
+        self.mode = Mode::Insert;
+
+        let register = if start != end {
+            let text = self.text_in_range(start, end);
+            self.delete_range(start, end);
+
+            Some(Register::new(text, RegisterKind::Charwise))
+        } else {
+            None
+        };
+
+        // The cursor counts bytes in Insert mode
+        self.cursor.y = start.y;
+        self.cursor.x = byte_index(&self.content[start.y], start.x);
+        self.cursor.desired_x = self.cursor.x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+
+        Ok(register)
+    }
+
+    // Clears the current line except for its indentation and enters Insert mode, like 'cc' in Vim.
+    // Returns the whole line so it can be stored in the register.
+    pub fn change_line(&mut self) -> Option<Register> {
+        if !self.state.mutable {
+            return None;
+        }
+
+        let register = self.yank_lines(1);
+        let line = &self.content[self.cursor.y];
+        let indent_len = line.len() - line.trim_start().len();
+
+        self.save_undo_state();
+        self.mode = Mode::Insert;
+
+        self.content[self.cursor.y].truncate(indent_len);
+
+        self.cursor.x = indent_len;
+        self.cursor.desired_x = indent_len;
+
+        Some(register)
+    }
+
+    // Replaces the character under the cursor without leaving Normal mode, like 'r' in Vim. A
+    // newline splits the line instead, with the character under the cursor removed.
+    pub fn replace_char(&mut self, character: char) {
+        if !self.state.mutable {
+            return;
+        }
+
+        let line = &self.content[self.cursor.y];
+
+        if self.cursor.x >= line.chars().count() {
+            return;
+        }
+
+        // The whole grapheme under the cursor is replaced, combining marks included
+        let index = byte_index(line, self.cursor.x);
+        let end = next_grapheme(line, index);
+
+        self.save_undo_state();
+
+        if character == '\n' {
+            let remaining = self.content[self.cursor.y].split_off(end);
+            self.content[self.cursor.y].truncate(index);
+            self.content.insert(self.cursor.y + 1, remaining);
+
+            self.cursor.y += 1;
+            self.cursor.x = 0;
+            self.cursor.desired_x = 0;
+
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        } else {
+            self.content[self.cursor.y].replace_range(index..end, &character.to_string());
+        }
+    }
+
+    // Moves the current line, or all the selected lines in Visual mode, one line up or down. The
+    // cursor and the selection follow the moved lines, and nothing happens at the top or bottom of
+    // the buffer.
+    pub fn move_lines(&mut self, down: bool) {
+        let (top, bottom) = self.line_range();
+
+        if !self.state.mutable || (down && bottom + 1 >= self.content.len()) || (!down && top == 0)
+        {
+            return;
+        }
+
+        self.save_undo_state();
+
+        if down {
+            let line = self.content.remove(bottom + 1);
+            self.content.insert(top, line);
+            self.cursor.y += 1;
+        } else {
+            let line = self.content.remove(top - 1);
+            self.content.insert(bottom, line);
+            self.cursor.y -= 1;
+        }
+
+        if let Some(visual_start) = &mut self.visual_start {
+            visual_start.y = if down {
+                visual_start.y + 1
+            } else {
+                visual_start.y - 1
+            };
+        }
+
+        self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Joins the line below onto the current line, or all the selected lines in Visual mode, like
+    // 'J' in Vim. With spaces, the leading whitespace of the joined line is replaced by a single
+    // space, otherwise the lines are joined as they are, like 'gJ'. The cursor is placed at the
+    // last join point.
+    pub fn join_line(&mut self, spaces: bool) {
+        let (top, bottom) = self.line_range();
+        let joins = (bottom - top).max(1);
+
+        if self.state.mutable && top + 1 < self.content.len() {
+            self.save_undo_state();
+
+            let joins = joins.min(self.content.len() - 1 - top);
+            let mut join_x = 0;
+
+            for _ in 0..joins {
+                let next_line = self.content.remove(top + 1);
+                let line = &mut self.content[top];
+
+                join_x = line.chars().count();
+
+                if spaces {
+                    let next_line = next_line.trim_start();
+
+                    if !line.is_empty()
+                        && !line.ends_with(char::is_whitespace)
+                        && !next_line.is_empty()
+                    {
+                        line.push(' ');
+                    }
+
+                    line.push_str(next_line);
+                } else {
+                    line.push_str(&next_line);
+                }
+            }
+
+            self.cursor.y = top;
+            self.cursor.x = join_x;
+
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+
+        self.finish_line_edit();
+    }
+
+    // Adds a level of indentation to the current line, or all the selected lines in Visual mode.
+    // Empty lines are left as they are. The level is a tab unless tabs are expanded, like in
+    // dedent_line where a tab counts as a full level.
+    pub fn indent_line(&mut self, width: usize) {
+        let (top, bottom) = self.line_range();
+        let indent = if self.options.expand_tab {
+            " ".repeat(width)
+        } else {
+            "\t".to_string()
+        };
+
         if self.state.mutable {
-            if self.content.len() > 1 {
-                self.content.remove(self.cursor.y);
+            self.save_undo_state();
 
-                if self.cursor.y > self.content.len() - 1 {
-                    self.cursor.y -= 1;
+            for y in top..=bottom {
+                if !self.content[y].is_empty() {
+                    self.content[y].insert_str(0, &indent);
+
+                    if y == self.cursor.y {
+                        self.cursor.x += indent.len();
+                    }
                 }
+            }
+
+            self.history.discard_unchanged(&self.content);
+        }
+
+        self.finish_line_edit();
+    }
+
+    // Removes a level of indentation from the current line, or all the selected lines in Visual
+    // mode. Lines with less indentation than a full level lose what they have, and a tab counts as
+    // a full level.
+    pub fn dedent_line(&mut self, width: usize) {
+        let (top, bottom) = self.line_range();
+
+        if self.state.mutable {
+            self.save_undo_state();
+
+            for y in top..=bottom {
+                let line = &mut self.content[y];
+
+                let removed = if line.starts_with('\t') {
+                    1
+                } else {
+                    line.chars().take(width).take_while(|c| *c == ' ').count()
+                };
+
+                line.drain(..removed);
+
+                if y == self.cursor.y {
+                    self.cursor.x = self.cursor.x.saturating_sub(removed);
+                }
+            }
+
+            self.history.discard_unchanged(&self.content);
+        }
+
+        self.finish_line_edit();
+    }
+
+    // Changes the case of the selection in Visual mode, or of the character under the cursor
+    // before moving to the next one, like '~' in Vim.
+    pub fn change_case(&mut self, case: CaseChange) {
+        if !self.state.mutable {
+            return;
+        }
+
+        let (start, end) = match self.selection() {
+            Some(range) if self.mode == Mode::Visual => range,
+            // The character under the cursor is changed with its combining marks
+            _ => {
+                let line = &self.content[self.cursor.y];
+                let mut end = self.cursor;
+                end.x = line[..next_grapheme(line, self.cursor_index())]
+                    .chars()
+                    .count();
+
+                (self.cursor, end)
+            }
+        };
+
+        self.save_undo_state();
+
+        // The lines are rebuilt character by character, since the case change can alter the
+        // length of a character.
+        let block = self.mode == Mode::Visual && self.visual_kind == VisualKind::Block;
+
+        for y in start.y..=end.y {
+            let from = if y == start.y || block { start.x } else { 0 };
+            let to = if y == end.y || block {
+                end.x
+            } else {
+                usize::MAX
+            };
+
+            self.content[y] = self.content[y]
+                .chars()
+                .enumerate()
+                .map(|(x, c)| {
+                    if x >= from && x < to {
+                        convert_case(c, case)
+                    } else {
+                        c.to_string()
+                    }
+                })
+                .collect();
+        }
+
+        self.history.discard_unchanged(&self.content);
+
+        if self.mode == Mode::Visual {
+            self.cursor.x = start.x;
+            self.cursor.y = start.y;
+            self.switch_mode(ModeParams::Normal);
+        } else {
+            self.cursor.x = end.x.min(self.max_cursor_x());
+        }
+
+        self.cursor.desired_x = self.cursor.x;
+    }
+
+    // Comments out the current line, or all the selected lines in Visual mode. If all of them are
+    // already commented, the comments are removed instead. The comment prefix is placed at the
+    // smallest indentation of the lines, and blank lines are left as they are.
+    pub fn toggle_comment(&mut self) {
+        let (top, bottom) = self.line_range();
+        let prefix = self.comment_prefix();
+
+        let lines: Vec<usize> = (top..=bottom)
+            .filter(|y| !self.content[*y].trim().is_empty())
+            .collect();
+
+        if self.state.mutable && !lines.is_empty() {
+            self.save_undo_state();
+
+            let commented = lines
+                .iter()
+                .all(|y| self.content[*y].trim_start().starts_with(prefix));
+
+            if commented {
+                for y in lines {
+                    let line = &mut self.content[y];
+                    let start = line.len() - line.trim_start().len();
+
+                    // The space after the prefix is removed with it.
+                    let end = if line[start + prefix.len()..].starts_with(' ') {
+                        start + prefix.len() + 1
+                    } else {
+                        start + prefix.len()
+                    };
+
+                    line.drain(start..end);
+
+                    // The indentation is measured in bytes, while the cursor counts characters
+                    let start_x = line[..start].chars().count();
+
+                    if y == self.cursor.y && self.cursor.x > start_x {
+                        self.cursor.x = self.cursor.x.saturating_sub(end - start).max(start_x);
+                    }
+                }
+            } else {
+                let indent = lines
+                    .iter()
+                    .map(|y| self.content[*y].len() - self.content[*y].trim_start().len())
+                    .min()
+                    .unwrap_or(0);
+
+                let comment = format!("{} ", prefix);
+
+                for y in lines {
+                    self.content[y].insert_str(indent, &comment);
+
+                    let indent_x = self.content[y][..indent].chars().count();
+
+                    if y == self.cursor.y && self.cursor.x >= indent_x {
+                        self.cursor.x += comment.len();
+                    }
+                }
+            }
+        }
+
+        self.finish_line_edit();
+    }
+
+    // Wraps the selected lines in Visual mode, or the paragraph the cursor is in, to the text width.
+    // The continuation lines get the indentation of the first line, and words longer than the width
+    // are put on their own line. The cursor is placed on the first line.
+    pub fn reflow(&mut self) {
+        let (mut top, mut bottom) = self.line_range();
+
+        // Outside Visual mode, the paragraph goes up to the surrounding blank lines.
+        if self.mode != Mode::Visual {
+            if self.content[top].trim().is_empty() {
+                return;
+            }
+
+            while top > 0 && !self.content[top - 1].trim().is_empty() {
+                top -= 1;
+            }
+
+            while bottom + 1 < self.content.len() && !self.content[bottom + 1].trim().is_empty() {
+                bottom += 1;
+            }
+        }
+
+        if self.state.mutable {
+            let first = &self.content[top];
+            let indent = first[..first.len() - first.trim_start().len()].to_string();
+            let indent_width = display_width(&indent, self.options.tab_width);
+
+            let mut lines: Vec<String> = Vec::new();
+            let mut line = String::new();
+            let mut width = 0;
+
+            for word in self.content[top..=bottom]
+                .iter()
+                .flat_map(|line| line.split_whitespace())
+            {
+                let word_width = word.chars().count();
+
+                if !line.is_empty() && width + 1 + word_width > self.options.text_width {
+                    lines.push(std::mem::take(&mut line));
+                }
+
+                if line.is_empty() {
+                    line = format!("{}{}", indent, word);
+                    width = indent_width + word_width;
+                } else {
+                    line.push(' ');
+                    line.push_str(word);
+                    width += 1 + word_width;
+                }
+            }
+
+            // Blank lines have no words, so they're reflowed into a single blank line.
+            if !line.is_empty() || lines.is_empty() {
+                lines.push(line);
+            }
+
+            self.save_undo_state();
+            self.content.splice(top..=bottom, lines);
+            self.history.discard_unchanged(&self.content);
+
+            self.cursor.y = top;
+            self.cursor.x = indent.chars().count();
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+
+        self.finish_line_edit();
+    }
+
+    // Replaces the pattern with the replacement on the lines in the range. Returns the amount of
+    // replacements and the amount of lines they were made on. The cursor is placed at the start of
+    // the last changed line.
+    pub fn substitute(&mut self, substitution: &Substitution) -> (usize, usize) {
+        if !self.state.mutable {
+            return (0, 0);
+        }
+
+        let last = self.content.len() - 1;
+        let (top, bottom) = match substitution.range {
+            LineRange::Current => (self.cursor.y, self.cursor.y),
+            LineRange::All => (0, last),
+            LineRange::Lines(start, end) => (
+                start.saturating_sub(1).min(last),
+                end.saturating_sub(1).min(last),
+            ),
+        };
+
+        let mut replacements = 0;
+        let mut lines = 0;
+
+        self.save_undo_state();
+
+        for y in top..=bottom {
+            let line = &mut self.content[y];
+            let matches = line.matches(substitution.pattern.as_str()).count();
+
+            if matches == 0 {
+                continue;
+            }
+
+            if substitution.global {
+                *line = line.replace(&substitution.pattern, &substitution.replacement);
+                replacements += matches;
             } else {
-                self.content[self.cursor.y] = String::new();
+                *line = line.replacen(&substitution.pattern, &substitution.replacement, 1);
+                replacements += 1;
             }
 
+            lines += 1;
+            self.cursor.y = y;
             self.cursor.x = 0;
         }
+
+        self.history.discard_unchanged(&self.content);
+        self.cursor.desired_x = self.cursor.x;
+
+        (replacements, lines)
+    }
+
+    // Pastes the register after or before the cursor. Line-wise text is pasted as new lines under
+    // or above the current line, character-wise text is pasted into the current line. The cursor
+    // is placed at the start of the pasted text.
+    pub fn paste(&mut self, register: &Register, before: bool) {
+        if !self.state.mutable || register.is_empty() {
+            return;
+        }
+
+        self.save_undo_state();
+
+        match register.kind {
+            RegisterKind::Linewise => {
+                let y = if before {
+                    self.cursor.y
+                } else {
+                    self.cursor.y + 1
+                };
+
+                self.content.splice(y..y, register.text.iter().cloned());
+
+                self.cursor.y = y;
+                self.cursor.x = 0;
+            }
+            RegisterKind::Charwise => {
+                let index = self.cursor_index();
+                let line = &mut self.content[self.cursor.y];
+
+                // Pasting after the cursor means after the character under it.
+                let x = if before {
+                    index
+                } else {
+                    next_grapheme(line, index)
+                };
+
+                let remaining = line.split_off(x);
+                line.push_str(&register.text[0]);
+
+                // Multi-line text splits the current line, with the rest of it ending up after
+                // the last pasted line.
+                let last_y = self.cursor.y + register.text.len() - 1;
+
+                self.content.splice(
+                    self.cursor.y + 1..self.cursor.y + 1,
+                    register.text[1..].iter().cloned(),
+                );
+                self.content[last_y].push_str(&remaining);
+
+                self.set_cursor_index(x);
+            }
+            // Each line of the block is pasted into the same column of the following lines, which
+            // are added or padded with spaces if needed.
+            RegisterKind::Blockwise => {
+                let line = &self.content[self.cursor.y];
+                let index = if before {
+                    self.cursor_index()
+                } else {
+                    next_grapheme(line, self.cursor_index())
+                };
+                let x = line[..index].chars().count();
+
+                for (offset, block_line) in register.text.iter().enumerate() {
+                    let y = self.cursor.y + offset;
+
+                    if y >= self.content.len() {
+                        self.content.push(String::new());
+                    }
+
+                    let line = &mut self.content[y];
+                    let line_len = line.chars().count();
+
+                    if line_len < x {
+                        line.push_str(&" ".repeat(x - line_len));
+                    }
+
+                    line.insert_str(byte_index(line, x), block_line);
+                }
+
+                self.set_cursor_index(byte_index(&self.content[self.cursor.y], x));
+            }
+        }
+
+        self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Replaces the visual selection with the register, as one undo step. Line selections are
+    // replaced by whole lines, and line-wise text replacing part of a line splits the line around
+    // it. Returns the replaced text so it can be stored in the register.
+    pub fn paste_over_selection(&mut self, register: &Register) -> Option<Register> {
+        let (start, end) = self.selection()?;
+        let replaced = self.yank_selection()?;
+        let kind = self.visual_kind;
+
+        if !self.state.mutable || register.is_empty() {
+            return None;
+        }
+
+        let undo_len = self.history.len();
+        let line_count = self.content.len();
+
+        // Removes the selection and leaves Visual mode.
+        self.remove_char().ok()?;
+
+        match (kind, register.kind) {
+            (VisualKind::Line, _) => {
+                let lines = Register::new(register.text.clone(), RegisterKind::Linewise);
+
+                // If the selection went to the end of the buffer, there are no lines left to
+                // paste above.
+                self.paste(&lines, start.y < self.content.len());
+
+                // If every line was replaced, the empty line left by the deletion goes too.
+                if end.y - start.y + 1 == line_count {
+                    self.content.pop();
+                }
+            }
+            (_, RegisterKind::Linewise) => {
+                let line = &mut self.content[self.cursor.y];
+                let remaining = line.split_off(byte_index(line, start.x));
+
+                self.content.insert(self.cursor.y + 1, remaining);
+                self.paste(register, false);
+            }
+            _ => {
+                self.cursor.x = start.x;
+                self.paste(register, true);
+            }
+        }
+
+        self.history.squash(undo_len);
+
+        Some(replaced)
+    }
+
+    // Returns the first and last line to act on, which is the selected lines in Visual mode and the
+    // current line otherwise.
+    fn line_range(&self) -> (usize, usize) {
+        match self.selection() {
+            Some((start, end)) if self.mode == Mode::Visual => (start.y, end.y),
+            _ => (self.cursor.y, self.cursor.y),
+        }
+    }
+
+    // Leaves Visual mode after editing whole lines and keeps the cursor on the line.
+    fn finish_line_edit(&mut self) {
+        if self.mode == Mode::Visual {
+            self.switch_mode(ModeParams::Normal);
+        } else {
+            self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        }
+
+        self.cursor.desired_x = self.cursor.x;
     }
 }
 
@@ -226,39 +1033,36 @@ impl Manipulation for CommandLine {
         unreachable!()
     }
 
-    fn delete_line(&mut self) {
+    fn delete_line(&mut self, _count: usize) -> Option<Register> {
         unreachable!()
     }
 }
 
 impl Manipulation for Minibuffer {
     fn add_char(&mut self, character: char) -> Result<()> {
-        let matched_len = self.matched_input.len();
+        let index = self.input_index();
 
-        self.input.insert(self.cursor.x - matched_len, character);
+        self.input.insert(index, character);
         self.cursor.x += 1;
 
         Ok(())
     }
 
+    // Backspace on an empty input goes up a directory, which leaves the root in place.
     fn remove_char(&mut self) -> Result<()> {
-        let matched_len = self.matched_input.len();
-
         if self.input.is_empty() {
-            if self.matched_input.pop().is_some() {
-                match &mut self.kind {
-                    MinibufferKind::File(path) => {
-                        path.pop();
-                    }
-                    _ => {}
+            if let MinibufferKind::File(path) = &mut self.kind {
+                if self.matched_input.len() > 1 {
+                    self.matched_input.pop();
+                    path.pop();
+                    self.cursor.y = 0;
                 }
             }
-        } else {
-            self.input.remove(self.cursor.x - matched_len - 1);
-        }
-
-        if self.cursor.x > 0 {
+        } else if self.cursor.x > 0 {
             self.cursor.x -= 1;
+
+            let index = self.input_index();
+            self.input.remove(index);
         }
 
         Ok(())
@@ -272,7 +1076,46 @@ impl Manipulation for Minibuffer {
         unreachable!()
     }
 
-    fn delete_line(&mut self) {
+    fn delete_line(&mut self, _count: usize) -> Option<Register> {
         unreachable!()
     }
 }
+
+// ╭──────────────────────────────────────╮
+// │ Manipulation Helpers                 │
+// ╰──────────────────────────────────────╯
+
+// The characters that are closed automatically in Insert mode, and their closing characters.
+const PAIRS: [(char, char); 5] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+
+fn is_opening(c: char) -> bool {
+    PAIRS.iter().any(|(opening, _)| *opening == c)
+}
+
+fn is_closing(c: char) -> bool {
+    PAIRS.iter().any(|(_, closing)| *closing == c)
+}
+
+// Returns the character that closes the typed character, if it should be closed. Quotes typed
+// right after or before a word aren't closed, since they're most likely apostrophes or closing
+// quotes, and brackets aren't closed right before a word.
+fn closing_pair(c: char, prev_char: Option<char>, next_char: Option<char>) -> Option<char> {
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let (_, closing) = PAIRS.iter().find(|(opening, _)| *opening == c)?;
+
+    if is_word_char(next_char) || (c == *closing && is_word_char(prev_char)) {
+        None
+    } else {
+        Some(*closing)
+    }
+}
+
+// Returns the character with its case changed. Characters without case are returned as they are.
+fn convert_case(c: char, case: CaseChange) -> String {
+    match case {
+        CaseChange::Toggle if c.is_lowercase() => c.to_uppercase().to_string(),
+        CaseChange::Toggle => c.to_lowercase().to_string(),
+        CaseChange::Lower => c.to_lowercase().to_string(),
+        CaseChange::Upper => c.to_uppercase().to_string(),
+    }
+}
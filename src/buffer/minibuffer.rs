@@ -1,9 +1,11 @@
 use std::env;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use crate::buffer::{Cursor, Error};
+use crate::buffer::{Cursor, Error, Viewport};
 use crate::keybinding::Action;
+use crate::utils::expand_home;
 
 // ╭──────────────────────────────────────╮
 // │ Minibuffer Types                     │
@@ -11,6 +13,20 @@ use crate::keybinding::Action;
 
 type Result<T> = std::result::Result<T, Error>;
 
+// ╭──────────────────────────────────────╮
+// │ Minibuffer Constants                 │
+// ╰──────────────────────────────────────╯
+
+// The most candidates shown at once, the rest are scrolled to
+const MAX_HEIGHT: usize = 10;
+
+// The preview of the selected file only reads the start of it
+const PREVIEW_LINES: usize = 40;
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+// The entry that goes up a directory when finding a file
+const PARENT_DIR: &str = "..";
+
 // ╭──────────────────────────────────────╮
 // │ Minibuffer Structs                   │
 // ╰──────────────────────────────────────╯
@@ -20,130 +36,285 @@ pub enum MinibufferKind {
     #[default]
     Nop,
     File(PathBuf),
-    Buffer(Vec<String>),
+    // The index and title of every buffer, in the order they're listed
+    Buffer(Vec<(usize, String)>),
+    Recent(Vec<String>),
+}
+
+// An entry in the candidate list. Directories are told apart so they can be shown differently,
+// and buffers keep their index so buffers with the same title can't be mixed up.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Candidate {
+    pub name: String,
+    pub is_dir: bool,
+    pub buffer: Option<usize>,
+}
+
+impl Candidate {
+    fn new(name: &str) -> Self {
+        Candidate {
+            name: name.to_string(),
+            is_dir: false,
+            buffer: None,
+        }
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Minibuffer {
     pub cursor: Cursor,
     pub input: String,
     pub matched_input: Vec<String>,
     pub prefix: String,
-    pub content: Vec<String>,
+    pub content: Vec<Candidate>,
     pub kind: MinibufferKind,
+    pub viewport: Viewport,
+    pub preview: Option<Vec<String>>,
+    pub error: Option<String>,
+}
+
+impl Default for Minibuffer {
+    fn default() -> Self {
+        Minibuffer {
+            cursor: Cursor::default(),
+            input: String::new(),
+            matched_input: Vec::new(),
+            prefix: String::new(),
+            content: Vec::new(),
+            kind: MinibufferKind::default(),
+            viewport: Viewport::new(MAX_HEIGHT, 0),
+            preview: None,
+            error: None,
+        }
+    }
 }
 
 impl Minibuffer {
+    // Fits the candidate list in the terminal, taking up at most half of it.
+    pub fn resize(&mut self, terminal_height: usize) {
+        self.viewport.height = MAX_HEIGHT.min(terminal_height / 2).max(1);
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
     pub fn fill(&mut self) -> Result<()> {
-        let runtime = tokio::runtime::Runtime::new()?;
-        let mut matches: Vec<String> = Vec::new();
+        let mut matches: Vec<Candidate> = Vec::new();
 
         match &mut self.kind {
             MinibufferKind::File(ref mut path) => {
+                let mut names: Vec<String> = Vec::new();
+
                 if path.display().to_string().is_empty() {
                     *path = env::current_dir()?;
-                    matches = runtime.block_on(read_dir(&path))?;
+                    names = read_dir(path)?;
 
                     for dir in path.into_iter() {
                         self.matched_input.push(dir.to_string_lossy().to_string());
                     }
 
                     self.prefix = "Find File:".to_string();
-                    self.cursor.x = self.matched_input.len();
+                    self.cursor.x = 0;
                 } else {
-                    let entries = runtime.block_on(read_dir(&path))?;
+                    // A file the input matched before is left, so editing the input matches again
+                    if path.is_file() {
+                        path.pop();
+                    }
+
+                    // A path typed into the input replaces the one that was built up so far
+                    if let Some((dir, input)) = typed_dir(path, &self.input) {
+                        *path = dir;
+
+                        self.matched_input = path
+                            .iter()
+                            .map(|dir| dir.to_string_lossy().to_string())
+                            .collect();
+                        self.input = input;
+                        self.cursor.x = self.input.chars().count();
+                        self.cursor.y = 0;
+                    }
+
+                    let entries = read_dir(path)?;
 
                     for entry in entries {
                         if entry == self.input {
                             path.push(&entry);
 
                             if path.is_file() {
-                                matches.push(entry);
+                                names.push(entry);
                                 break;
                             }
 
                             self.matched_input.push(entry);
                             self.input.clear();
-                            self.cursor.x = self.matched_input.len();
+                            self.cursor.x = 0;
+                            self.cursor.y = 0;
                             self.fill()?;
 
                             return Ok(());
-                        } else if entry.contains(&self.input) {
-                            matches.push(entry);
+                        } else if contains(&entry, &self.input) {
+                            names.push(entry);
                         }
                     }
                 }
 
-                let mut dirs: Vec<String> = Vec::new();
-                let mut files: Vec<String> = Vec::new();
+                // Directories come before files, and both are sorted by name
+                matches = names
+                    .iter()
+                    .map(|name| Candidate {
+                        name: name.to_string(),
+                        is_dir: Path::new(&format!("{}/{}", path.display(), name)).is_dir(),
+                        buffer: None,
+                    })
+                    .collect();
 
-                for entry in &matches {
-                    if Path::new(&format!("{}/{}", path.display(), entry)).is_dir() {
-                        dirs.push(entry.to_string());
-                    } else {
-                        files.push(entry.to_string());
-                    }
-                }
-
-                dirs.sort();
-                files.sort();
+                matches.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
 
-                matches.clear();
-                matches.append(&mut dirs);
-                matches.append(&mut files);
+                // The parent directory is always listed first, except at the root
+                if path.parent().is_some() {
+                    matches.insert(
+                        0,
+                        Candidate {
+                            name: PARENT_DIR.to_string(),
+                            is_dir: true,
+                            buffer: None,
+                        },
+                    );
+                }
             }
+            // The buffers keep their order, so the most recently used buffer comes first
             MinibufferKind::Buffer(buffer_list) => {
                 self.prefix = "Find Buffer:".to_string();
 
-                for entry in buffer_list {
-                    if entry.contains(&self.input) {
-                        matches.push(entry.to_string());
+                for (index, title) in buffer_list {
+                    if contains(title, &self.input) {
+                        matches.push(Candidate {
+                            buffer: Some(*index),
+                            ..Candidate::new(title)
+                        });
                     }
                 }
+            }
+            // The recent files keep their order, so the newest file comes first
+            MinibufferKind::Recent(files) => {
+                self.prefix = "Recent File:".to_string();
 
-                matches.sort();
+                for file in files {
+                    if fuzzy_match(file, &self.input) {
+                        matches.push(Candidate::new(file));
+                    }
+                }
             }
             _ => {}
         }
 
         self.content = matches;
 
+        // The selection stays on the list when it gets shorter
+        self.cursor.y = self.cursor.y.min(self.content.len().saturating_sub(1));
+        self.viewport.adjust(self.cursor.y, self.content.len());
+
+        // Finding a file previews the selected candidate, or the file the input has matched
+        self.preview = match &self.kind {
+            MinibufferKind::File(path) if path.is_file() => Some(read_preview(path)),
+            MinibufferKind::File(path) => self
+                .content
+                .get(self.cursor.y)
+                .map(|entry| read_preview(&path.join(&entry.name))),
+            _ => None,
+        };
+
         Ok(())
     }
 
     pub fn append(&mut self) {
         if let Some(item) = self.content.get(self.cursor.y) {
-            self.cursor.x += item.len() - self.input.len();
-            self.input = item.to_string();
+            self.input = item.name.clone();
+            self.cursor.x = self.input.chars().count();
         }
     }
 
+    // Returns the byte index of the cursor in the input. The cursor is kept in characters of the
+    // input, the matched part of the path in front of it can't be edited.
+    pub fn input_index(&self) -> usize {
+        self.input
+            .char_indices()
+            .nth(self.cursor.x)
+            .map_or(self.input.len(), |(index, _)| index)
+    }
+
+    // Completes the input right away when there's only one candidate for it, not counting the
+    // parent directory, and moves the selection otherwise.
+    pub fn complete_or_cycle(&mut self, next: bool) {
+        if !self.append_single() {
+            self.cycle(next);
+        }
+    }
+
+    fn append_single(&mut self) -> bool {
+        let mut candidates = self
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.name != PARENT_DIR);
+
+        match (candidates.next(), candidates.next()) {
+            (Some((index, _)), None) => {
+                self.cursor.y = index;
+                self.append();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Moves the selection to the next or previous candidate, wrapping around at the ends.
+    pub fn cycle(&mut self, next: bool) {
+        let len = self.content.len();
+
+        if len == 0 {
+            return;
+        }
+
+        self.cursor.y = if next {
+            (self.cursor.y + 1) % len
+        } else {
+            (self.cursor.y + len - 1) % len
+        };
+        self.viewport.adjust(self.cursor.y, len);
+    }
+
     pub fn execute(&mut self) -> Result<Option<Action>> {
         match &self.kind {
             MinibufferKind::File(path) => {
                 if path.is_file() {
                     return Ok(Some(Action::OpenFile(path.clone())));
                 }
-            }
-            MinibufferKind::Buffer(buffer_list) => {
-                let item: &String = if self.content.len() > 1 {
-                    if let Some(item) = &self.content.get(self.cursor.y) {
-                        item
-                    } else {
-                        &String::from("")
-                    }
-                } else {
-                    &self.input
-                };
 
-                for (num, entry) in buffer_list.iter().enumerate() {
-                    if entry.contains(item) {
-                        return Ok(Some(Action::OpenBuffer(num)));
-                    }
+                // A bare '~' goes to the home directory when the input is filled again
+                if self.input == "~" {
+                    self.input.push('/');
+                    self.cursor.x += 1;
+                    return Ok(None);
                 }
 
-                return Err(Error::NoMatchError);
+                // A name that doesn't exist in the directory opens a new file
+                if path.is_dir() && !self.input.is_empty() && !path.join(&self.input).exists() {
+                    return Ok(Some(Action::OpenFile(path.join(&self.input))));
+                }
+            }
+            MinibufferKind::Buffer(_) => {
+                return match self
+                    .content
+                    .get(self.cursor.y)
+                    .and_then(|candidate| candidate.buffer)
+                {
+                    Some(index) => Ok(Some(Action::OpenBuffer(index))),
+                    None => Err(Error::NoMatchError),
+                };
             }
+            MinibufferKind::Recent(_) => match self.content.get(self.cursor.y) {
+                Some(file) => return Ok(Some(Action::OpenFile(PathBuf::from(&file.name)))),
+                None => return Err(Error::NoMatchError),
+            },
             _ => {}
         }
 
@@ -151,7 +322,107 @@ impl Minibuffer {
     }
 }
 
-async fn read_dir(path: &PathBuf) -> Result<Vec<String>> {
+// ╭──────────────────────────────────────╮
+// │ Minibuffer Helpers                   │
+// ╰──────────────────────────────────────╯
+
+// Matching ignores case unless the input has an uppercase letter in it, so 'cargo' matches
+// 'Cargo.toml' but 'Cargo' doesn't match 'cargo.lock'.
+fn ignore_case(input: &str) -> bool {
+    !input.chars().any(char::is_uppercase)
+}
+
+fn chars_eq(a: char, b: char, ignore_case: bool) -> bool {
+    if ignore_case {
+        a.to_lowercase().eq(b.to_lowercase())
+    } else {
+        a == b
+    }
+}
+
+// Reads a directory out of the find-file input. '..' goes up a directory, and an input starting
+// with '/' or '~/' is a path from the root or the home directory. Whatever comes after the last '/'
+// is returned as the new input. A bare '~' is turned into '~/' on enter instead, since it could
+// still be the start of '~/'.
+fn typed_dir(path: &Path, input: &str) -> Option<(PathBuf, String)> {
+    if input == PARENT_DIR {
+        return Some((path.parent().unwrap_or(path).to_path_buf(), String::new()));
+    }
+
+    if !input.starts_with('/') && !input.starts_with("~/") {
+        return None;
+    }
+
+    let full = expand_home(input);
+    let full = full.to_str().filter(|full| full.starts_with('/'))?;
+
+    let (dir, rest) = full.split_at(full.rfind('/')? + 1);
+    let dir = PathBuf::from(dir);
+
+    if !dir.is_dir() {
+        return None;
+    }
+
+    Some((dir, rest.to_string()))
+}
+
+// Checks if the input appears in the entry.
+fn contains(entry: &str, input: &str) -> bool {
+    if !ignore_case(input) {
+        return entry.contains(input);
+    }
+
+    entry.to_lowercase().contains(&input.to_lowercase())
+}
+
+// Checks if the characters of the input appear in the entry in the same order, so 'mnrs' matches
+// 'main.rs'.
+fn fuzzy_match(entry: &str, input: &str) -> bool {
+    let ignore_case = ignore_case(input);
+    let mut chars = entry.chars();
+
+    input
+        .chars()
+        .all(|c| chars.any(|entry_c| chars_eq(entry_c, c, ignore_case)))
+}
+
+// Reads the first lines of a file, or the entries of a directory. Files with a null byte in them
+// are taken to be binary and aren't shown, and errors are shown in place of the content.
+fn read_preview(path: &Path) -> Vec<String> {
+    if path.is_dir() {
+        return match read_dir(path) {
+            Ok(mut entries) => {
+                entries.sort();
+                entries.truncate(PREVIEW_LINES);
+                entries
+            }
+            Err(error) => vec![error.to_string()],
+        };
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+
+    let read = match fs::File::open(path) {
+        Ok(file) => file.take(PREVIEW_BYTES).read_to_end(&mut bytes),
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = read {
+        return vec![error.to_string()];
+    }
+
+    if bytes.contains(&0) {
+        return vec!["[binary file]".to_string()];
+    }
+
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .take(PREVIEW_LINES)
+        .map(|line| line.replace('\t', "    "))
+        .collect()
+}
+
+fn read_dir(path: &Path) -> Result<Vec<String>> {
     let mut entries: Vec<String> = Vec::new();
 
     for entry in fs::read_dir(path)? {
@@ -4,14 +4,20 @@
 
 pub mod buffer;
 pub mod error;
+pub mod history;
 pub mod manipulation;
 pub mod minibuffer;
 pub mod navigation;
+pub mod options;
+pub mod register;
 pub mod viewport;
 
 pub use buffer::*;
 pub use error::*;
+pub use history::*;
 pub use manipulation::*;
 pub use minibuffer::*;
 pub use navigation::*;
+pub use options::*;
+pub use register::*;
 pub use viewport::*;
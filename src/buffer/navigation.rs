@@ -1,15 +1,40 @@
-use crate::buffer::{Buffer, CommandLine, Minibuffer, Mode};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::buffer::{Buffer, CommandLine, Cursor, Minibuffer, Mode};
+use crate::keybinding::{
+    CharSearch, Motion, Search, SearchDirection, TextObject, ViewportPosition,
+};
+
+// ╭──────────────────────────────────────╮
+// │ Navigation Enums                     │
+// ╰──────────────────────────────────────╯
+
+// The classes a character can belong to when moving word by word. Like in Vim, a run of
+// punctuation counts as its own word, unless moving by WORD where only whitespace separates words.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum CharClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+// ╭──────────────────────────────────────╮
+// │ Navigation Trait                     │
+// ╰──────────────────────────────────────╯
 
 pub trait Navigation {
     fn move_cursor(&mut self, x: i32, y: i32);
     fn move_cursor_to_top(&mut self);
     fn move_cursor_to_bot(&mut self);
+    fn move_word_forward(&mut self);
+    fn move_word_backward(&mut self);
 }
 
 impl Navigation for Buffer {
     fn move_cursor(&mut self, x: i32, y: i32) {
         match self.mode {
-            Mode::Normal | Mode::Visual { .. } => {
+            Mode::Normal | Mode::Visual | Mode::Insert => {
                 // Sets the new y value.
                 // Clamp is used to make sure it doesn't exceed the length of the line or 0.
                 let new_y =
@@ -21,16 +46,29 @@ impl Navigation for Buffer {
 
                 // Checks if cursor is moved horiozontally.
                 // If not, it checks if x is larger than the current lines length and adjusts accordingly.
+                // The cursor moves by graphemes, so it skips over combining marks and the parts
+                // of emoji sequences.
                 if x != 0 {
-                    let current_line_len = self.content[self.cursor.y].len();
-                    let new_x =
-                        (self.cursor.x as i32 + x).clamp(0, current_line_len as i32) as usize;
+                    let line = &self.content[self.cursor.y];
+                    let mut index = self.cursor_index();
 
-                    self.cursor.x = new_x;
-                    self.cursor.desired_x = new_x;
+                    for _ in 0..x.unsigned_abs() {
+                        index = if x > 0 {
+                            next_grapheme(line, index)
+                        } else {
+                            prev_grapheme(line, index)
+                        };
+                    }
+
+                    self.set_cursor_index(index);
+                    self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+                    self.cursor.desired_x = self.cursor.x;
                 } else {
-                    let current_line_len = self.content[self.cursor.y].len();
-                    self.cursor.x = self.cursor.desired_x.min(current_line_len);
+                    self.cursor.x = self.cursor.desired_x.min(self.max_cursor_x());
+                    self.set_cursor_index(grapheme_start(
+                        &self.content[self.cursor.y],
+                        self.cursor_index(),
+                    ));
                 }
             }
             Mode::Command => {
@@ -53,6 +91,373 @@ impl Navigation for Buffer {
 
         self.viewport.adjust(self.cursor.y, self.content.len());
     }
+
+    // Moves the cursor to the start of the next word, like 'w' in Vim.
+    fn move_word_forward(&mut self) {
+        if let Some((y, x)) = next_word_start(&self.content, self.cursor.y, self.cursor.x, false) {
+            self.cursor.y = y;
+            self.cursor.x = x;
+            self.cursor.desired_x = x;
+
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+    }
+
+    // Moves the cursor to the start of the current or previous word, like 'b' in Vim.
+    fn move_word_backward(&mut self) {
+        let (y, x) = prev_word_start(&self.content, self.cursor.y, self.cursor.x, false);
+
+        self.cursor.y = y;
+        self.cursor.x = x;
+        self.cursor.desired_x = x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+}
+
+impl Buffer {
+    // Moves the cursor to the end of the current or next word, like 'e' in Vim.
+    pub fn move_word_end(&mut self) {
+        if let Some((y, x)) = next_word_end(&self.content, self.cursor.y, self.cursor.x, false) {
+            self.cursor.y = y;
+            self.cursor.x = x;
+            self.cursor.desired_x = x;
+
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+    }
+
+    // Moves the cursor to the start of the next WORD, like 'W' in Vim.
+    pub fn move_big_word_forward(&mut self) {
+        if let Some((y, x)) = next_word_start(&self.content, self.cursor.y, self.cursor.x, true) {
+            self.cursor.y = y;
+            self.cursor.x = x;
+            self.cursor.desired_x = x;
+
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+    }
+
+    // Moves the cursor to the start of the current or previous WORD, like 'B' in Vim.
+    pub fn move_big_word_backward(&mut self) {
+        let (y, x) = prev_word_start(&self.content, self.cursor.y, self.cursor.x, true);
+
+        self.cursor.y = y;
+        self.cursor.x = x;
+        self.cursor.desired_x = x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    pub fn move_to_line_start(&mut self) {
+        match self.mode {
+            Mode::Command => self.command_line.move_to_line_start(),
+            _ => {
+                self.cursor.x = 0;
+                self.cursor.desired_x = 0;
+            }
+        }
+    }
+
+    // Moves the cursor to the last character of the line, or past it in insert mode. The desired x
+    // is set to the maximum so the cursor sticks to the end of the line when moving vertically
+    // afterwards.
+    pub fn move_to_line_end(&mut self) {
+        match self.mode {
+            Mode::Command => self.command_line.move_to_line_end(),
+            _ => {
+                self.cursor.x = self.max_cursor_x();
+                self.cursor.desired_x = usize::MAX;
+            }
+        }
+    }
+
+    // Moves the cursor to the first non-whitespace character of the line, or to the start of the
+    // line if there is none.
+    pub fn move_to_first_non_blank(&mut self) {
+        let x = self.content[self.cursor.y]
+            .chars()
+            .position(|c| !c.is_whitespace())
+            .unwrap_or(0);
+
+        self.cursor.x = x;
+        self.cursor.desired_x = x;
+    }
+
+    // Moves the cursor to the given line. The line number starts at 1 and is clamped to the
+    // buffer's length, so both 0 and 1 go to the first line.
+    pub fn move_to_line(&mut self, line: usize) {
+        self.cursor.x = 0;
+        self.cursor.desired_x = 0;
+        self.cursor.y = line.saturating_sub(1).min(self.content.len() - 1);
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Moves the cursor to the given percentage of the buffer, like '50%' in Vim. The old position
+    // is stored in the '`' mark so it can be jumped back to.
+    pub fn move_to_percentage(&mut self, percent: usize) {
+        self.marks.insert('`', self.cursor);
+
+        let line = (self.content.len() * percent.min(100)).div_ceil(100);
+
+        self.move_to_line(line);
+        self.move_to_first_non_blank();
+    }
+
+    // Moves the cursor to the next or previous occurrence of the character on the current line,
+    // or next to it for till searches. A repeated till search skips an occurrence right next to the
+    // cursor so it keeps moving. The cursor stays put if there is no such occurrence.
+    pub fn find_char(&mut self, search: CharSearch, repeat: bool) {
+        let line: Vec<char> = self.content[self.cursor.y].chars().collect();
+        let skip = if search.till && repeat { 2 } else { 1 };
+
+        let found = match search.direction {
+            SearchDirection::Forward => line
+                .iter()
+                .enumerate()
+                .skip(self.cursor.x + skip)
+                .find(|(_, c)| **c == search.character)
+                .map(|(x, _)| if search.till { x - 1 } else { x }),
+            SearchDirection::Backward => line
+                .iter()
+                .enumerate()
+                .take(self.cursor.x.saturating_sub(skip - 1))
+                .rev()
+                .find(|(_, c)| **c == search.character)
+                .map(|(x, _)| if search.till { x + 1 } else { x }),
+        };
+
+        if let Some(x) = found {
+            self.cursor.x = x;
+            self.cursor.desired_x = x;
+        }
+    }
+
+    // Jumps to the bracket matching the one under the cursor, like '%' in Vim. If the cursor isn't
+    // on a bracket, the first bracket after it on the line is used instead.
+    pub fn move_to_matching_bracket(&mut self) {
+        if let Some((y, x)) = matching_bracket(&self.content, self.cursor.y, self.cursor.x) {
+            self.cursor.y = y;
+            self.cursor.x = x;
+            self.cursor.desired_x = x;
+
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+    }
+
+    // Moves the cursor to the next or previous screen row when the lines wrap at the width, like
+    // 'gj' and 'gk' in Vim. The cursor keeps its column within the row where the row is long
+    // enough. Without a width the lines don't wrap, so the cursor moves by lines instead.
+    pub fn move_display_row(&mut self, down: bool, width: usize) {
+        if width == 0 {
+            return self.move_cursor(0, if down { 1 } else { -1 });
+        }
+
+        let (row, offset) = self.cursor_row(width);
+        let row_count = self.line_rows(self.cursor.y, width).len();
+
+        let (y, row) = if down && row + 1 < row_count {
+            (self.cursor.y, row + 1)
+        } else if down && self.cursor.y + 1 < self.content.len() {
+            (self.cursor.y + 1, 0)
+        } else if !down && row > 0 {
+            (self.cursor.y, row - 1)
+        } else if !down && self.cursor.y > 0 {
+            (
+                self.cursor.y - 1,
+                self.line_rows(self.cursor.y - 1, width).len() - 1,
+            )
+        } else {
+            return;
+        };
+
+        // A shorter row takes the cursor to its last column
+        let tab_width = self.options.tab_width;
+        let line = &self.content[y];
+        let rows = self.line_rows(y, width);
+        let row_end = rows
+            .get(row + 1)
+            .copied()
+            .unwrap_or(display_width(line, tab_width) + 1);
+        let mut index = column_index(line, (rows[row] + offset).min(row_end - 1), tab_width);
+
+        // A tab split over two rows starts on the row above, so moving down onto the rest of it
+        // goes past it instead of staying put
+        if down && y == self.cursor.y && index == self.cursor_index() {
+            index = next_grapheme(line, index);
+        }
+
+        self.cursor.y = y;
+        self.set_cursor_index(index);
+        self.cursor.x = self.cursor.x.min(self.max_cursor_x());
+        self.cursor.desired_x = self.cursor.x;
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Moves the cursor and the viewport by half a page, so the cursor stays on the same screen row
+    // when possible.
+    pub fn scroll_half_page(&mut self, down: bool) {
+        let lines = (self.viewport.height / 2).max(1);
+
+        if down {
+            self.cursor.y = (self.cursor.y + lines).min(self.content.len() - 1);
+            self.viewport.scroll_down(lines, self.content.len());
+        } else {
+            self.cursor.y = self.cursor.y.saturating_sub(lines);
+            self.viewport.scroll_up(lines);
+        }
+
+        self.cursor.x = self.cursor.desired_x.min(self.max_cursor_x());
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Moves the viewport by a full page and places the cursor on the first visible line when going
+    // down, or the last visible line when going up. If the viewport can't move any further, the
+    // cursor goes to the first or last line of the buffer instead.
+    pub fn scroll_page(&mut self, down: bool) {
+        let lines = self.viewport.height.max(1);
+        let top = self.viewport.top;
+
+        if down {
+            self.viewport.scroll_down(lines, self.content.len());
+
+            self.cursor.y = if self.viewport.top == top {
+                self.content.len() - 1
+            } else {
+                self.viewport.top
+            };
+        } else {
+            self.viewport.scroll_up(lines);
+
+            self.cursor.y = if self.viewport.top == top {
+                0
+            } else {
+                (self.viewport.bottom() - 1).min(self.content.len() - 1)
+            };
+        }
+
+        self.cursor.x = self.cursor.desired_x.min(self.max_cursor_x());
+
+        self.viewport.adjust(self.cursor.y, self.content.len());
+    }
+
+    // Scrolls the viewport by one line without moving the cursor, like Ctrl-e and Ctrl-y in Vim.
+    // The cursor is only pulled along when it would end up outside the viewport or its margin.
+    pub fn scroll_line(&mut self, down: bool) {
+        let content_len = self.content.len();
+
+        if down {
+            self.viewport.scroll_down(1, content_len);
+        } else {
+            self.viewport.scroll_up(1);
+        }
+
+        let scrolloff = self
+            .viewport
+            .scrolloff
+            .min(self.viewport.height.saturating_sub(1) / 2);
+
+        // The margin isn't kept at the very top and bottom of the buffer.
+        let min_y = if self.viewport.top == 0 {
+            0
+        } else {
+            self.viewport.top + scrolloff
+        };
+        let max_y = if self.viewport.bottom() >= content_len {
+            content_len - 1
+        } else {
+            self.viewport.bottom() - 1 - scrolloff
+        };
+
+        self.cursor.y = self.cursor.y.clamp(min_y, max_y.max(min_y));
+        self.cursor.x = self.cursor.desired_x.min(self.max_cursor_x());
+    }
+
+    // Scrolls the viewport so the cursor line ends up at the given position, without moving the
+    // cursor.
+    pub fn align_viewport(&mut self, position: ViewportPosition) {
+        match position {
+            ViewportPosition::Center => self.viewport.center_on(self.cursor.y, self.content.len()),
+            ViewportPosition::Top => self.viewport.top_on(self.cursor.y, self.content.len()),
+            ViewportPosition::Bottom => self.viewport.bottom_on(self.cursor.y, self.content.len()),
+        }
+    }
+
+    // Moves the cursor to the top, middle or bottom line of the viewport, like 'H', 'M' and 'L' in
+    // Vim. Only lines with content are taken into account.
+    pub fn move_to_viewport(&mut self, position: ViewportPosition) {
+        let top = self.viewport.top;
+        let bottom = (self.viewport.bottom() - 1).min(self.content.len() - 1);
+
+        self.cursor.y = match position {
+            ViewportPosition::Top => top,
+            ViewportPosition::Center => top + (bottom - top) / 2,
+            ViewportPosition::Bottom => bottom,
+        };
+
+        self.cursor.x = self.cursor.desired_x.min(self.max_cursor_x());
+    }
+
+    // Moves the cursor to the next or previous occurrence of the pattern, wrapping around the ends
+    // of the buffer. Returns false if the pattern wasn't found.
+    pub fn search(&mut self, search: &Search) -> bool {
+        let (y, x) = (self.cursor.y, self.cursor.x);
+
+        let found = match search.direction {
+            SearchDirection::Forward => find_forward(&self.content, y, x, search),
+            SearchDirection::Backward => find_backward(&self.content, y, x, search),
+        };
+
+        match found {
+            Some((y, x)) => {
+                self.cursor.y = y;
+                self.cursor.x = x;
+                self.cursor.desired_x = x;
+
+                self.viewport.adjust(self.cursor.y, self.content.len());
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Searches for the next or previous occurrence of the word under the cursor, like '*' and '#'
+    // in Vim. Returns the search so it can be repeated, or None if the cursor isn't on a word.
+    pub fn search_word_under_cursor(&mut self, direction: SearchDirection) -> Option<Search> {
+        let line: Vec<char> = self.content[self.cursor.y].chars().collect();
+        let class = char_class(*line.get(self.cursor.x)?, false);
+
+        if class == CharClass::Whitespace {
+            return None;
+        }
+
+        let mut start = self.cursor.x;
+        let mut end = self.cursor.x + 1;
+
+        while start > 0 && char_class(line[start - 1], false) == class {
+            start -= 1;
+        }
+
+        while end < line.len() && char_class(line[end], false) == class {
+            end += 1;
+        }
+
+        let search = Search {
+            pattern: line[start..end].iter().collect(),
+            direction,
+            whole_word: true,
+        };
+
+        // Searches from the start of the word so a backward search skips the word itself.
+        self.cursor.x = start;
+        self.search(&search);
+
+        Some(search)
+    }
 }
 
 impl Navigation for CommandLine {
@@ -72,16 +477,40 @@ impl Navigation for CommandLine {
     fn move_cursor_to_bot(&mut self) {
         unreachable!()
     }
+
+    fn move_word_forward(&mut self) {
+        unreachable!()
+    }
+
+    fn move_word_backward(&mut self) {
+        unreachable!()
+    }
+}
+
+impl CommandLine {
+    pub fn move_to_line_start(&mut self) {
+        self.cursor.x = self.prefix.len();
+        self.cursor.desired_x = self.cursor.x;
+    }
+
+    pub fn move_to_line_end(&mut self) {
+        self.cursor.x = self.prefix.len() + self.input.len();
+        self.cursor.desired_x = self.cursor.x;
+    }
 }
 
 impl Navigation for Minibuffer {
     fn move_cursor(&mut self, x: i32, y: i32) {
-        let new_y = (self.cursor.y as i32 + y).clamp(0, self.content.len() as i32 - 1) as usize;
-        self.cursor.y = new_y;
+        // Moving past either end of the candidates wraps around to the other end
+        let len = self.content.len() as i32;
 
-        let matched_len: i32 = self.matched_input.len() as i32;
-        let input_len: i32 = self.input.len() as i32;
-        let new_x = (self.cursor.x as i32 + x).clamp(0, matched_len + input_len) as usize;
+        if len > 0 {
+            self.cursor.y = (self.cursor.y as i32 + y).rem_euclid(len) as usize;
+            self.viewport.adjust(self.cursor.y, self.content.len());
+        }
+
+        let input_len = self.input.chars().count() as i32;
+        let new_x = (self.cursor.x as i32 + x).clamp(0, input_len) as usize;
 
         self.cursor.x = new_x;
         self.cursor.desired_x = new_x;
@@ -94,4 +523,587 @@ impl Navigation for Minibuffer {
     fn move_cursor_to_bot(&mut self) {
         unreachable!()
     }
+
+    fn move_word_forward(&mut self) {
+        unreachable!()
+    }
+
+    fn move_word_backward(&mut self) {
+        unreachable!()
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Navigation Helpers                   │
+// ╰──────────────────────────────────────╯
+
+fn char_class(c: char, big: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Whitespace
+    } else if big || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+// Returns the start and the exclusive end of the text the motion covers from the cursor. The
+// word boundaries are the same as for the word motions, except that like in Vim, the forward
+// word motion stops at the end of the line instead of joining the next line. Returns None if there
+// is no text object around the cursor.
+pub fn motion_range(
+    content: &[String],
+    cursor: Cursor,
+    motion: Motion,
+) -> Option<(Cursor, Cursor)> {
+    let (y, x) = (cursor.y, cursor.x);
+    let line_len = content[y].chars().count();
+
+    let ((start_y, start_x), (end_y, end_x)) = match motion {
+        Motion::WordForward => match next_word_start(content, y, x, false) {
+            Some((end_y, end_x)) if end_y == y => ((y, x), (y, end_x)),
+            _ => ((y, x), (y, line_len)),
+        },
+        Motion::WordEnd => match next_word_end(content, y, x, false) {
+            Some((end_y, end_x)) => ((y, x), (end_y, end_x + 1)),
+            None => ((y, x), (y, line_len)),
+        },
+        Motion::WordBackward => (prev_word_start(content, y, x, false), (y, x)),
+        Motion::LineEnd => ((y, x), (y, line_len)),
+        Motion::Chars(count) => ((y, x), (y, (x + count).min(line_len))),
+        Motion::Inside(object) | Motion::Around(object) => {
+            let around = matches!(motion, Motion::Around(_));
+
+            match object {
+                TextObject::Word => word_object(&content[y], y, x, around)?,
+                TextObject::Quote(quote) => quote_object(&content[y], y, x, quote, around)?,
+                TextObject::Pair(open, close) => {
+                    let (start, end) = enclosing_pair(content, y, x, open, close)?;
+
+                    if around {
+                        (start, (end.0, end.1 + 1))
+                    } else {
+                        ((start.0, start.1 + 1), end)
+                    }
+                }
+            }
+        }
+    };
+
+    Some((
+        Cursor {
+            x: start_x,
+            y: start_y,
+            desired_x: start_x,
+        },
+        Cursor {
+            x: end_x,
+            y: end_y,
+            desired_x: end_x,
+        },
+    ))
+}
+
+// Returns the range of the word or the whitespace under the cursor. The around variant also
+// covers the whitespace after the word, or before it if there is none after it.
+fn word_object(
+    line: &str,
+    y: usize,
+    x: usize,
+    around: bool,
+) -> Option<((usize, usize), (usize, usize))> {
+    let line: Vec<char> = line.chars().collect();
+    let class = char_class(*line.get(x)?, false);
+
+    let mut start = x;
+    let mut end = x + 1;
+
+    while start > 0 && char_class(line[start - 1], false) == class {
+        start -= 1;
+    }
+
+    while end < line.len() && char_class(line[end], false) == class {
+        end += 1;
+    }
+
+    if around {
+        let trailing = line[end..].iter().take_while(|c| c.is_whitespace()).count();
+
+        if class == CharClass::Whitespace {
+            // On whitespace, the word after it is covered as well.
+            let next_class = line.get(end).map(|c| char_class(*c, false));
+
+            while end < line.len() && Some(char_class(line[end], false)) == next_class {
+                end += 1;
+            }
+        } else if trailing > 0 {
+            end += trailing;
+        } else {
+            while start > 0 && line[start - 1].is_whitespace() {
+                start -= 1;
+            }
+        }
+    }
+
+    Some(((y, start), (y, end)))
+}
+
+// Returns the range of the quoted text on the line the cursor is in, or the first one after the
+// cursor. Quotes are paired from the start of the line, and quotes escaped with a backslash are
+// skipped.
+fn quote_object(
+    line: &str,
+    y: usize,
+    x: usize,
+    quote: char,
+    around: bool,
+) -> Option<((usize, usize), (usize, usize))> {
+    let line: Vec<char> = line.chars().collect();
+
+    let quotes: Vec<usize> = (0..line.len())
+        .filter(|i| line[*i] == quote && (*i == 0 || line[i - 1] != '\\'))
+        .collect();
+
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|(_, close)| x <= *close)?;
+
+    if around {
+        Some(((y, open), (y, close + 1)))
+    } else {
+        Some(((y, open + 1), (y, close)))
+    }
+}
+
+// Finds the positions of the opening and closing characters of the innermost pair around the
+// position. The pair can span several lines, and nested pairs are skipped. Returns None if the
+// position isn't inside a balanced pair.
+fn enclosing_pair(
+    content: &[String],
+    y: usize,
+    x: usize,
+    open: char,
+    close: char,
+) -> Option<((usize, usize), (usize, usize))> {
+    let mut depth = 0;
+    let mut start = None;
+
+    // Searches backwards for the opening character. A closing character under the cursor belongs
+    // to the pair being searched for, so it isn't counted.
+    'search: for line_y in (0..=y).rev() {
+        let line: Vec<char> = content[line_y].chars().collect();
+        let end = if line_y == y {
+            (x + 1).min(line.len())
+        } else {
+            line.len()
+        };
+
+        for line_x in (0..end).rev() {
+            if line[line_x] == open {
+                if depth == 0 {
+                    start = Some((line_y, line_x));
+                    break 'search;
+                }
+
+                depth -= 1;
+            } else if line[line_x] == close && (line_y, line_x) != (y, x) {
+                depth += 1;
+            }
+        }
+    }
+
+    let start = start?;
+    depth = 0;
+
+    // Searches forwards from the opening character for the one that closes it.
+    for (line_y, line) in content.iter().enumerate().skip(start.0) {
+        let skip = if line_y == start.0 { start.1 + 1 } else { 0 };
+
+        for (line_x, c) in line.chars().enumerate().skip(skip) {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                if depth == 0 {
+                    return Some((start, (line_y, line_x)));
+                }
+
+                depth -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+// Finds the start of the word after the given position. When `big` is set it looks for WORDs
+// instead. Empty lines count as words, and None is
+// returned if there is no word after the position.
+fn next_word_start(content: &[String], y: usize, x: usize, big: bool) -> Option<(usize, usize)> {
+    let (mut y, mut x) = (y, x);
+    let mut line: Vec<char> = content[y].chars().collect();
+
+    // Skips the rest of the word the cursor is on.
+    if let Some(&c) = line.get(x) {
+        let class = char_class(c, big);
+
+        if class != CharClass::Whitespace {
+            while x < line.len() && char_class(line[x], big) == class {
+                x += 1;
+            }
+        }
+    }
+
+    // Skips the whitespace up to the next word, wrapping to the following lines if needed.
+    loop {
+        while x < line.len() && line[x].is_whitespace() {
+            x += 1;
+        }
+
+        if x < line.len() {
+            return Some((y, x));
+        }
+
+        if y + 1 >= content.len() {
+            return None;
+        }
+
+        y += 1;
+        x = 0;
+        line = content[y].chars().collect();
+
+        if line.is_empty() {
+            return Some((y, x));
+        }
+    }
+}
+
+// Finds the last character of the word after the given position. Unlike the word starts, empty
+// lines are skipped, and None is returned if there is no word after the position.
+fn next_word_end(content: &[String], y: usize, x: usize, big: bool) -> Option<(usize, usize)> {
+    let mut y = y;
+    let mut line: Vec<char> = content[y].chars().collect();
+    let mut x = x + 1;
+
+    // Skips the whitespace up to the next word, wrapping to the following lines if needed.
+    loop {
+        while x < line.len() && line[x].is_whitespace() {
+            x += 1;
+        }
+
+        if x < line.len() {
+            break;
+        }
+
+        if y + 1 >= content.len() {
+            return None;
+        }
+
+        y += 1;
+        x = 0;
+        line = content[y].chars().collect();
+    }
+
+    // Moves to the last character of the word.
+    let class = char_class(line[x], big);
+
+    while x + 1 < line.len() && char_class(line[x + 1], big) == class {
+        x += 1;
+    }
+
+    Some((y, x))
+}
+
+// Finds the start of the word the position is in, or the start of the previous word if the
+// position already is at the start of one. Empty lines count as words and the search stops at the
+// start of the buffer.
+fn prev_word_start(content: &[String], y: usize, x: usize, big: bool) -> (usize, usize) {
+    let mut y = y;
+    let mut line: Vec<char> = content[y].chars().collect();
+    let mut x = x.min(line.len());
+
+    // Skips the whitespace before the cursor, wrapping to the previous lines if needed.
+    loop {
+        while x > 0 && line[x - 1].is_whitespace() {
+            x -= 1;
+        }
+
+        if x > 0 || y == 0 {
+            break;
+        }
+
+        y -= 1;
+        line = content[y].chars().collect();
+        x = line.len();
+
+        if line.is_empty() {
+            break;
+        }
+    }
+
+    // Moves to the start of the word.
+    if x > 0 {
+        let class = char_class(line[x - 1], big);
+
+        while x > 0 && char_class(line[x - 1], big) == class {
+            x -= 1;
+        }
+    }
+
+    (y, x)
+}
+
+// Finds the bracket matching the first bracket at or after the given position on its line. The
+// search spans multiple lines and keeps track of nested brackets. None is returned if the brackets
+// are unbalanced.
+fn matching_bracket(content: &[String], y: usize, x: usize) -> Option<(usize, usize)> {
+    let line: Vec<char> = content[y].chars().collect();
+    let (x, bracket) = line
+        .iter()
+        .enumerate()
+        .skip(x)
+        .find(|(_, c)| "()[]{}".contains(**c))
+        .map(|(x, c)| (x, *c))?;
+
+    let (open, close, forward) = match bracket {
+        '(' => ('(', ')', true),
+        '[' => ('[', ']', true),
+        '{' => ('{', '}', true),
+        ')' => ('(', ')', false),
+        ']' => ('[', ']', false),
+        _ => ('{', '}', false),
+    };
+
+    let mut depth = 0;
+
+    if forward {
+        for (line_y, line) in content.iter().enumerate().skip(y) {
+            let skip = if line_y == y { x } else { 0 };
+
+            for (line_x, c) in line.chars().enumerate().skip(skip) {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Some((line_y, line_x));
+                    }
+                }
+            }
+        }
+    } else {
+        for (line_y, line) in content.iter().enumerate().take(y + 1).rev() {
+            let chars: Vec<char> = line.chars().collect();
+            let take = if line_y == y { x + 1 } else { chars.len() };
+
+            for (line_x, c) in chars.iter().enumerate().take(take).rev() {
+                if *c == close {
+                    depth += 1;
+                } else if *c == open {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Some((line_y, line_x));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// Returns the amount of columns the text takes up on screen, with tabs expanded to the next tab
+// stop.
+pub fn display_width(text: &str, tab_width: usize) -> usize {
+    text.graphemes(true).fold(0, |column, grapheme| {
+        column + grapheme_width(grapheme, column, tab_width)
+    })
+}
+
+// Returns the amount of columns the grapheme takes up when it starts at the given column. Wide
+// characters like CJK and emoji take up two, and graphemes without a width of their own, like a
+// combining mark at the start of a line, take up one since they're drawn as a replacement
+// character.
+pub fn grapheme_width(grapheme: &str, column: usize, tab_width: usize) -> usize {
+    if grapheme == "\t" {
+        tab_width - column % tab_width
+    } else {
+        grapheme.width().max(1)
+    }
+}
+
+// Returns the columns the rows of a wrapped line start at, so the first row always starts at 0. A
+// wide character that doesn't fit at the end of a row moves to the next one, while tabs are split
+// over the rows. The cell after the line, where the cursor goes at its end, counts as part of the
+// line.
+pub fn wrap_columns(line: &str, width: usize, tab_width: usize) -> Vec<usize> {
+    let mut rows = vec![0];
+
+    if width == 0 {
+        return rows;
+    }
+
+    let mut column = 0;
+
+    for grapheme in line.graphemes(true).chain(std::iter::once(" ")) {
+        let grapheme_width = grapheme_width(grapheme, column, tab_width);
+        let end = column + grapheme_width;
+        let mut row_end = rows[rows.len() - 1] + width;
+
+        if grapheme != "\t" && column < row_end && end > row_end && grapheme_width <= width {
+            rows.push(column);
+        } else {
+            while end > row_end {
+                rows.push(row_end);
+                row_end += width;
+            }
+        }
+
+        column = end;
+    }
+
+    rows
+}
+
+// Returns the byte index of the grapheme that covers the column, or the length of the line if the
+// column is past its end.
+pub fn column_index(line: &str, column: usize, tab_width: usize) -> usize {
+    let mut start = 0;
+
+    for (index, grapheme) in line.grapheme_indices(true) {
+        start += grapheme_width(grapheme, start, tab_width);
+
+        if start > column {
+            return index;
+        }
+    }
+
+    line.len()
+}
+
+// Returns the byte index of the grapheme after the one at the index, or the length of the line at
+// its end.
+pub fn next_grapheme(line: &str, index: usize) -> usize {
+    let index = grapheme_start(line, index);
+
+    line[index..]
+        .graphemes(true)
+        .next()
+        .map_or(line.len(), |grapheme| index + grapheme.len())
+}
+
+// Returns the byte index of the grapheme before the one at the index, or 0 at the start of the
+// line.
+pub fn prev_grapheme(line: &str, index: usize) -> usize {
+    let index = grapheme_start(line, index);
+
+    line[..index]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(start, _)| start)
+}
+
+// Returns the byte index of the start of the grapheme the index is in, so a cursor never ends up
+// between the characters of a grapheme. Indexes past the end of the line are clamped to its
+// length.
+pub fn grapheme_start(line: &str, index: usize) -> usize {
+    if index >= line.len() {
+        return line.len();
+    }
+
+    line.grapheme_indices(true)
+        .map(|(start, _)| start)
+        .take_while(|start| *start <= index)
+        .last()
+        .unwrap_or(0)
+}
+
+// Converts a character index into a byte index of the line. Indexes past the end of the line are
+// clamped to its length.
+pub fn byte_index(line: &str, x: usize) -> usize {
+    line.char_indices()
+        .nth(x)
+        .map(|(index, _)| index)
+        .unwrap_or(line.len())
+}
+
+// Returns the byte ranges of every occurrence of the search pattern on the line. Whole word searches
+// only include the occurrences that aren't part of a larger word.
+pub fn search_matches(line: &str, search: &Search) -> Vec<(usize, usize)> {
+    if search.pattern.is_empty() {
+        return Vec::new();
+    }
+
+    line.match_indices(&search.pattern)
+        .map(|(start, pattern)| (start, start + pattern.len()))
+        .filter(|(start, end)| !search.whole_word || is_whole_word(line, *start, *end))
+        .collect()
+}
+
+// Checks that the characters around the range belong to other classes than the ones at its edges.
+fn is_whole_word(line: &str, start: usize, end: usize) -> bool {
+    let differs = |outside: Option<char>, inside: Option<char>| match (outside, inside) {
+        (Some(outside), Some(inside)) => char_class(outside, false) != char_class(inside, false),
+        _ => true,
+    };
+
+    let before = line[..start].chars().next_back();
+    let after = line[end..].chars().next();
+
+    differs(before, line[start..].chars().next()) && differs(after, line[..end].chars().next_back())
+}
+
+// Finds the first occurrence of the search after the given position. The search wraps around the
+// end of the buffer and ends back at the position itself.
+fn find_forward(content: &[String], y: usize, x: usize, search: &Search) -> Option<(usize, usize)> {
+    for offset in 0..=content.len() {
+        let line_y = (y + offset) % content.len();
+        let line = &content[line_y];
+        let start = if offset == 0 {
+            byte_index(line, x + 1)
+        } else {
+            0
+        };
+
+        if let Some((index, _)) = search_matches(line, search)
+            .into_iter()
+            .find(|(index, _)| *index >= start)
+        {
+            return Some((line_y, line[..index].chars().count()));
+        }
+    }
+
+    None
+}
+
+// Finds the last occurrence of the search that starts before the given position. The search wraps
+// around the start of the buffer and ends back at the position itself.
+fn find_backward(
+    content: &[String],
+    y: usize,
+    x: usize,
+    search: &Search,
+) -> Option<(usize, usize)> {
+    for offset in 0..=content.len() {
+        let line_y = (y + content.len() - offset % content.len()) % content.len();
+        let line = &content[line_y];
+        let end = if offset == 0 {
+            byte_index(line, x)
+        } else {
+            line.len()
+        };
+
+        // Matches only have to start before the position, so they can span past it.
+        if let Some((index, _)) = search_matches(line, search)
+            .into_iter()
+            .take_while(|(index, _)| *index < end)
+            .last()
+        {
+            return Some((line_y, line[..index].chars().count()));
+        }
+    }
+
+    None
 }
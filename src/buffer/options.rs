@@ -0,0 +1,60 @@
+// ╭──────────────────────────────────────╮
+// │ Options Enums                        │
+// ╰──────────────────────────────────────╯
+
+// The line ending a file is written with, named like the 'fileformat' option in Vim.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum FileFormat {
+    Unix,
+    Dos,
+}
+
+impl FileFormat {
+    pub fn line_ending(&self) -> &'static str {
+        match self {
+            FileFormat::Unix => "\n",
+            FileFormat::Dos => "\r\n",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Unix => "unix",
+            FileFormat::Dos => "dos",
+        }
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Options Structs                      │
+// ╰──────────────────────────────────────╯
+
+// The options that can differ between buffers, like the indentation style of the file.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct BufferOptions {
+    pub tab_width: usize,
+    pub expand_tab: bool,
+    pub shift_width: usize,
+    pub text_width: usize,
+    pub auto_pairs: bool,
+    pub trim_whitespace: bool,
+    pub file_format: FileFormat,
+    pub end_of_line: bool,
+    pub fix_end_of_line: bool,
+}
+
+impl Default for BufferOptions {
+    fn default() -> Self {
+        BufferOptions {
+            tab_width: 4,
+            expand_tab: true,
+            shift_width: 4,
+            text_width: 80,
+            auto_pairs: true,
+            trim_whitespace: false,
+            file_format: FileFormat::Unix,
+            end_of_line: true,
+            fix_end_of_line: false,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// ╭──────────────────────────────────────╮
+// │ Register Enums                       │
+// ╰──────────────────────────────────────╯
+
+// Line-wise text is pasted as whole lines, character-wise text is pasted into the current line, and
+// block-wise text is pasted into the same column of the following lines.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RegisterKind {
+    #[default]
+    Charwise,
+    Linewise,
+    Blockwise,
+}
+
+// ╭──────────────────────────────────────╮
+// │ Register Structs                     │
+// ╰──────────────────────────────────────╯
+
+// Holds yanked or deleted text. Each element is a line.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct Register {
+    pub text: Vec<String>,
+    pub kind: RegisterKind,
+}
+
+impl Register {
+    pub fn new(text: Vec<String>, kind: RegisterKind) -> Self {
+        Register { text, kind }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    // Returns the text repeated the given amount of times, for pasting with a count. Repeated
+    // character-wise text continues on the last line of the previous copy.
+    pub fn repeated(&self, count: usize) -> Self {
+        let mut text = self.text.clone();
+
+        if self.is_empty() {
+            return Register::new(text, self.kind);
+        }
+
+        for _ in 1..count {
+            match self.kind {
+                RegisterKind::Linewise => text.extend_from_slice(&self.text),
+                RegisterKind::Blockwise => {
+                    for (line, block_line) in text.iter_mut().zip(&self.text) {
+                        line.push_str(block_line);
+                    }
+                }
+                RegisterKind::Charwise => {
+                    let last = text.len() - 1;
+
+                    text[last].push_str(&self.text[0]);
+                    text.extend_from_slice(&self.text[1..]);
+                }
+            }
+        }
+
+        Register::new(text, self.kind)
+    }
+}
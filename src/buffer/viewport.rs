@@ -1,13 +1,25 @@
-// The visible part of the buffer content
+// The visible part of the buffer content. The scrolloff is the amount of lines that are kept
+// visible above and below the cursor, and left is the first visible column of long lines. When
+// lines wrap, top_row is the first visible row of the top line, which is only past 0 when the
+// cursor is on a line too long to fit on the screen.
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub struct Viewport {
     pub top: usize,
+    pub top_row: usize,
+    pub left: usize,
     pub height: usize,
+    pub scrolloff: usize,
 }
 
 impl Viewport {
-    pub fn new(height: usize) -> Self {
-        Viewport { top: 0, height }
+    pub fn new(height: usize, scrolloff: usize) -> Self {
+        Viewport {
+            top: 0,
+            top_row: 0,
+            left: 0,
+            height,
+            scrolloff,
+        }
     }
 
     pub fn bottom(&self) -> usize {
@@ -15,14 +27,93 @@ impl Viewport {
     }
 
     pub fn adjust(&mut self, cursor_y: usize, content_len: usize) {
-        if cursor_y < self.top {
-            self.top = cursor_y;
-        } else if cursor_y >= self.bottom() {
-            self.top = cursor_y.saturating_sub(self.height) + 1;
+        // The margin is capped at half the viewport, otherwise the viewport would scroll back and
+        // forth on every move.
+        let scrolloff = self.scrolloff.min(self.height.saturating_sub(1) / 2);
+
+        if cursor_y < self.top + scrolloff {
+            self.top = cursor_y.saturating_sub(scrolloff);
+        } else if cursor_y + scrolloff >= self.bottom() {
+            self.top = (cursor_y + scrolloff + 1).saturating_sub(self.height);
         }
 
         if self.bottom() > content_len {
             self.top = content_len.saturating_sub(self.height);
         }
     }
+
+    // Scrolls the viewport down until the cursor's row fits on the screen when lines wrap, since
+    // the lines above the cursor can take up more rows than the viewport is high. The rows function
+    // returns the amount of rows a line takes up, and the cursor row is the row of its line the
+    // cursor is on.
+    pub fn adjust_wrapped(
+        &mut self,
+        cursor_y: usize,
+        cursor_row: usize,
+        rows: impl Fn(usize) -> usize,
+    ) {
+        let scrolloff = self.scrolloff.min(self.height.saturating_sub(1) / 2);
+
+        // Only the cursor line can be partly scrolled off the top
+        if self.top != cursor_y {
+            self.top_row = 0;
+        } else {
+            self.top_row = self.top_row.min(cursor_row.saturating_sub(scrolloff));
+        }
+
+        while self.top < cursor_y {
+            let above: usize = (self.top..cursor_y).map(&rows).sum::<usize>() + cursor_row;
+
+            if above + scrolloff < self.height {
+                return;
+            }
+
+            self.top += 1;
+        }
+
+        if cursor_row - self.top_row + scrolloff >= self.height {
+            self.top_row = (cursor_row + scrolloff + 1).saturating_sub(self.height);
+        }
+    }
+
+    // Scrolls the viewport sideways so the cursor's column stays visible.
+    pub fn adjust_horizontal(&mut self, cursor_x: usize, width: usize) {
+        if cursor_x < self.left {
+            self.left = cursor_x;
+        } else if width > 0 && cursor_x >= self.left + width {
+            self.left = cursor_x + 1 - width;
+        }
+    }
+
+    // Scrolls the viewport down without going past the last page of the content.
+    pub fn scroll_down(&mut self, lines: usize, content_len: usize) {
+        self.top = (self.top + lines).min(content_len.saturating_sub(self.height));
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.top = self.top.saturating_sub(lines);
+    }
+
+    // Places the cursor line in the middle of the viewport.
+    pub fn center_on(&mut self, cursor_y: usize, content_len: usize) {
+        self.top = cursor_y.saturating_sub(self.height / 2);
+        self.clamp(content_len);
+    }
+
+    // Places the cursor line at the top of the viewport.
+    pub fn top_on(&mut self, cursor_y: usize, content_len: usize) {
+        self.top = cursor_y;
+        self.clamp(content_len);
+    }
+
+    // Places the cursor line at the bottom of the viewport.
+    pub fn bottom_on(&mut self, cursor_y: usize, content_len: usize) {
+        self.top = (cursor_y + 1).saturating_sub(self.height);
+        self.clamp(content_len);
+    }
+
+    // Makes sure the viewport doesn't scroll past the last page of the content.
+    fn clamp(&mut self, content_len: usize) {
+        self.top = self.top.min(content_len.saturating_sub(self.height));
+    }
 }
@@ -0,0 +1,65 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::editor::Options;
+
+// ╭──────────────────────────────────────╮
+// │ Backup Functions                     │
+// ╰──────────────────────────────────────╯
+
+// Copies the file to its backup before it's overwritten. The backup is called 'name~', or
+// 'name.~N~' with numbered backups where N counts up from the newest backup, and is placed next to
+// the file unless there's a backup directory. Returns the path of the backup.
+pub fn write_backup(path: &Path, options: &Options) -> io::Result<PathBuf> {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "No file name")),
+    };
+
+    let dir = match &options.backup_dir {
+        Some(dir) => dir.clone(),
+        None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+
+    let backup = if options.numbered_backups {
+        dir.join(format!("{}.~{}~", name, next_backup_number(&dir, &name)?))
+    } else {
+        dir.join(format!("{}~", name))
+    };
+
+    fs::copy(path, &backup)?;
+
+    Ok(backup)
+}
+
+// ╭──────────────────────────────────────╮
+// │ Backup Helpers                       │
+// ╰──────────────────────────────────────╯
+
+// Finds the number after the newest numbered backup of the file in the directory.
+fn next_backup_number(dir: &Path, name: &str) -> io::Result<usize> {
+    let read_dir = if dir.as_os_str().is_empty() {
+        fs::read_dir(".")?
+    } else {
+        fs::read_dir(dir)?
+    };
+
+    let prefix = format!("{}.~", name);
+    let mut newest = 0;
+
+    for entry in read_dir {
+        let entry_name = entry?.file_name().to_string_lossy().into_owned();
+
+        let number = entry_name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix('~'))
+            .and_then(|number| number.parse::<usize>().ok());
+
+        if let Some(number) = number {
+            newest = newest.max(number);
+        }
+    }
+
+    Ok(newest + 1)
+}
@@ -1,11 +1,28 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
 use ratatui::prelude::*;
 use ratatui::Terminal;
 
+use std::collections::{HashMap, HashSet};
 use std::io::Stdout;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use crate::buffer::{Buffer, Manipulation, Minibuffer, MinibufferKind, Mode, Navigation};
-use crate::keybinding::{Action, CommandParser, KeybindingManager, ModeParams};
-use crate::renderer::Renderer;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::buffer::{
+    Buffer, BufferKind, BufferState, Manipulation, Minibuffer, MinibufferKind, Mode, Navigation,
+    Register,
+};
+use crate::editor::{
+    read_recovery, recovery_dir, recovery_files, run_shell, write_backup, Options, RecentFiles,
+    Recovery, ShellOutput, TaskResult,
+};
+use crate::keybinding::{
+    Action, CharSearch, CommandParser, KeybindingManager, ModeParams, Motion, PendingAction,
+    Search, SearchDirection,
+};
+use crate::renderer::{Renderer, Theme};
+use crate::utils::FileArg;
 use crate::OxideError;
 
 // ╭──────────────────────────────────────╮
@@ -18,23 +35,98 @@ type Result<T> = std::result::Result<T, crate::OxideError>;
 // │ Editor Enums                         │
 // ╰──────────────────────────────────────╯
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MessageKind {
+    Info,
+    Error,
+}
+
+// What happens once every buffer in a save prompt has been answered
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PromptAction {
+    Quit,
+    KillBuffer,
+}
+
 // ╭──────────────────────────────────────╮
 // │ Editor Struct                        │
 // ╰──────────────────────────────────────╯
 
+// A message shown in the command line until the next keypress
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Message {
+    pub text: String,
+    pub kind: MessageKind,
+}
+
+impl Message {
+    pub fn info(text: String) -> Self {
+        Message {
+            text,
+            kind: MessageKind::Info,
+        }
+    }
+
+    pub fn error(text: String) -> Self {
+        Message {
+            text,
+            kind: MessageKind::Error,
+        }
+    }
+}
+
+// Asks whether to save each of the modified buffers, one at a time, before quitting or killing a
+// buffer. The answer is read from the next keypress.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SavePrompt {
+    pub buffers: Vec<usize>,
+    pub action: PromptAction,
+}
+
 pub struct BufferManager {
     pub buffers: Vec<Buffer>,
     pub active_buffer: usize,
+    // The indices of the buffers that have been active, the most recent first
+    pub recent_buffers: Vec<usize>,
 }
 
 impl BufferManager {
-    fn new(height: usize) -> Self {
+    fn new(height: usize, scrolloff: usize) -> Self {
         BufferManager {
-            buffers: vec![Buffer::scratch(height)],
+            buffers: vec![Buffer::scratch(height, scrolloff)],
             active_buffer: 0,
+            recent_buffers: vec![0],
         }
     }
 
+    // Makes the buffer at the index the active one, and moves it to the front of the recent
+    // buffers.
+    pub fn activate(&mut self, index: usize) {
+        self.active_buffer = index;
+        self.recent_buffers.retain(|recent| *recent != index);
+        self.recent_buffers.insert(0, index);
+    }
+
+    // Lists the buffers from the most to the least recently active. Buffers that have never been
+    // active follow in order, and the active buffer comes last since it's rarely the one wanted.
+    pub fn buffers_by_recency(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = self
+            .recent_buffers
+            .iter()
+            .copied()
+            .filter(|index| *index != self.active_buffer)
+            .collect();
+
+        for index in 0..self.buffers.len() {
+            if index != self.active_buffer && !order.contains(&index) {
+                order.push(index);
+            }
+        }
+
+        order.push(self.active_buffer);
+        order
+    }
+
     // Borrows the current buffer
     pub fn get_active_buffer(&mut self) -> Result<&Buffer> {
         if let Some(buffer) = self.buffers.get(self.active_buffer) {
@@ -53,35 +145,215 @@ impl BufferManager {
         }
     }
 
+    // Finds the buffer of the file, comparing the paths as they are on disk. Files that don't exist
+    // yet are compared by their absolute paths.
+    pub fn find_buffer(&self, path: &Path) -> Option<usize> {
+        let path = resolve_path(path);
+
+        self.buffers.iter().position(|buffer| {
+            buffer
+                .path
+                .as_ref()
+                .is_some_and(|buffer_path| resolve_path(buffer_path) == path)
+        })
+    }
+
     pub fn add_buffer(&mut self, buffer: Buffer) {
         self.buffers.push(buffer);
+        self.refresh_buffer_list();
+    }
+
+    // The buffers shown in the buffer list, which leaves out the list itself.
+    pub fn listed_buffers(&self) -> Vec<usize> {
+        (0..self.buffers.len())
+            .filter(|index| self.buffers[*index].kind != BufferKind::BufferList)
+            .collect()
+    }
+
+    // Rewrites the buffer list, if it's open, with a line for every other buffer. The first column
+    // holds the deletion mark, which is cleared, followed by the modified flag, the title, the
+    // line count and the path.
+    pub fn refresh_buffer_list(&mut self) {
+        let Some(list_index) = self
+            .buffers
+            .iter()
+            .position(|buffer| buffer.kind == BufferKind::BufferList)
+        else {
+            return;
+        };
+
+        let listed = self.listed_buffers();
+        let title_width = listed
+            .iter()
+            .map(|index| self.buffers[*index].title.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let content: Vec<String> = listed
+            .iter()
+            .map(|index| {
+                let buffer = &self.buffers[*index];
+                let path = buffer
+                    .path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default();
+
+                format!(
+                    "  {} {:<title_width$} {:>6} {}",
+                    if buffer.is_modified() { '+' } else { ' ' },
+                    buffer.title,
+                    buffer.content.len(),
+                    path
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect();
+
+        let list = &mut self.buffers[list_index];
+        list.content = if content.is_empty() {
+            vec![String::new()]
+        } else {
+            content
+        };
+        list.cursor.y = list.cursor.y.min(list.content.len() - 1);
+        list.cursor.x = 0;
+        list.viewport.adjust(list.cursor.y, list.content.len());
+    }
+
+    // Removes the buffer at the index, keeping the same buffer active if it wasn't the one removed.
+    pub fn remove_buffer(&mut self, index: usize, height: usize, scrolloff: usize) {
+        if index == self.active_buffer {
+            self.remove_active_buffer(height, scrolloff);
+            return;
+        }
+
+        self.buffers.remove(index);
+        self.forget_buffer(index);
+
+        if index < self.active_buffer {
+            self.active_buffer -= 1;
+        }
+
+        self.refresh_buffer_list();
+    }
+
+    // Removes the active buffer and switches to the one before it. If it was the last buffer, a
+    // new scratch buffer takes its place.
+    pub fn remove_active_buffer(&mut self, height: usize, scrolloff: usize) {
+        self.buffers.remove(self.active_buffer);
+        self.forget_buffer(self.active_buffer);
+
+        if self.buffers.is_empty() {
+            self.buffers.push(Buffer::scratch(height, scrolloff));
+        }
+
+        self.activate(
+            self.active_buffer
+                .saturating_sub(1)
+                .min(self.buffers.len() - 1),
+        );
+
+        self.refresh_buffer_list();
+    }
+
+    // Drops a removed buffer from the recent buffers, and shifts the indices after it.
+    fn forget_buffer(&mut self, index: usize) {
+        self.recent_buffers.retain(|recent| *recent != index);
+
+        for recent in self.recent_buffers.iter_mut() {
+            if *recent > index {
+                *recent -= 1;
+            }
+        }
     }
 }
 
 pub struct Editor {
     pub buffer_manager: BufferManager,
     pub renderer: Renderer,
+    pub theme: Theme,
     pub is_running: bool,
     pub minibuffer: Minibuffer,
+    pub last_char_search: Option<CharSearch>,
+    pub search: Option<Search>,
+    pub search_highlight: bool,
+    pub options: Options,
+    pub recent_files: RecentFiles,
+    pub message: Option<Message>,
+    pub save_prompt: Option<SavePrompt>,
+    pub last_input: Instant,
+    pub auto_save_pending: bool,
+    // The files backed up this session. Auto-saves only back up a file the first time it's written,
+    // so its backup isn't replaced by the content of the last auto-save
+    pub backed_up: HashSet<PathBuf>,
+    pub task_sender: UnboundedSender<TaskResult>,
+    pub task_receiver: UnboundedReceiver<TaskResult>,
+    // The command line files that are still loading, with the position to open them at
+    pub pending_files: Vec<FileArg>,
+    pub register: Register,
+    pub macros: HashMap<char, Vec<KeyEvent>>,
+    pub recording: Option<(char, Vec<KeyEvent>)>,
+    pub last_macro: Option<char>,
+    pub playing_macros: Vec<char>,
 }
 
 impl Editor {
     pub fn new(terminal: Terminal<CrosstermBackend<Stdout>>) -> Self {
         let renderer = Renderer::new(terminal);
         let height = renderer.get_terminal_size().height as usize;
-        let buffer_manager = BufferManager::new(height);
+        let options = Options::default();
+        let buffer_manager = BufferManager::new(height, options.scrolloff);
         let minibuffer = Minibuffer::default();
+        let (task_sender, task_receiver) = mpsc::unbounded_channel();
+
+        // A theme that can't be read falls back to the default styles
+        let (theme, warnings) = Theme::load();
+
+        for warning in &warnings {
+            log::warn!("{}", warning);
+        }
+
+        let message = (!warnings.is_empty()).then(|| Message::error(warnings.join(", ")));
 
         Editor {
             buffer_manager,
             renderer,
+            theme,
             is_running: true,
             minibuffer,
+            last_char_search: None,
+            search: None,
+            search_highlight: false,
+            options,
+            recent_files: RecentFiles::load(),
+            message,
+            save_prompt: None,
+            last_input: Instant::now(),
+            auto_save_pending: false,
+            backed_up: HashSet::new(),
+            task_sender,
+            task_receiver,
+            pending_files: Vec::new(),
+            register: Register::default(),
+            macros: HashMap::new(),
+            recording: None,
+            last_macro: None,
+            playing_macros: Vec::new(),
         }
     }
 
     // Calls the rendering function to not borrow past the editor's lifetime
-    pub fn render(&mut self) -> Result<()> {
+    pub fn render(&mut self, keybinding_manager: &KeybindingManager) -> Result<()> {
+        let buffer_width = self.renderer.get_buffer_width(&self.options);
+        let buffer = &mut self.buffer_manager.buffers[self.buffer_manager.active_buffer];
+
+        // Only the active buffer is edited, so it's the only one whose modified flag can be stale
+        buffer.update_modified();
+
+        buffer.scroll_to_cursor(buffer_width, self.options.wrap);
+
         let buffer = &self.buffer_manager.buffers[self.buffer_manager.active_buffer];
 
         let minibuffer: Option<&Minibuffer> = if buffer.mode == Mode::Minibuffer {
@@ -90,7 +362,558 @@ impl Editor {
             None
         };
 
-        self.renderer.render(buffer, minibuffer)?;
+        // The search matches are only highlighted until they're cleared
+        let search: Option<&Search> = self.search.as_ref().filter(|_| self.search_highlight);
+
+        self.renderer.render(
+            buffer,
+            &self.options,
+            minibuffer,
+            self.message.as_ref(),
+            search,
+            &keybinding_manager.pending_keys(),
+            &self.theme,
+        )?;
+
+        Ok(())
+    }
+
+    // Sends the keypress to the keybinding manager and executes the resulting action. Keypresses
+    // are recorded while a macro is recorded, except for the ones replayed from other macros.
+    pub fn handle_key(
+        &mut self,
+        key_event: KeyEvent,
+        keybinding_manager: &mut KeybindingManager,
+        tokio_runtime: &tokio::runtime::Runtime,
+    ) -> Result<()> {
+        self.last_input = Instant::now();
+        self.auto_save_pending = true;
+
+        if self.playing_macros.is_empty() {
+            if let Some((_, keys)) = &mut self.recording {
+                keys.push(key_event);
+            }
+        }
+
+        // A save prompt takes the keypress as its answer
+        if self.save_prompt.is_some() {
+            return self.answer_save_prompt(key_event, keybinding_manager, tokio_runtime);
+        }
+
+        let buffer = self.buffer_manager.get_active_buffer_mut()?;
+        let buffer_mode = buffer.mode;
+
+        // Text typed in Insert mode doesn't save an undo state, so the buffer is marked as changed
+        // for its modified flag to be checked again
+        if buffer_mode == Mode::Insert {
+            buffer.changed = true;
+        }
+
+        if let Some(action) = keybinding_manager.handle_input(&buffer_mode, key_event) {
+            self.parse_action(action, keybinding_manager, tokio_runtime)?;
+        }
+
+        Ok(())
+    }
+
+    // Writes the modified buffers once the editor has been idle for the 'autosave' delay, or right
+    // away when the terminal loses focus. It only runs once per idle period, so a failed write isn't
+    // retried until the next keypress. The message never replaces the command line or another
+    // message. Returns true if a buffer was written or failed to, so the screen is redrawn.
+    //
+    // One idle timer for the editor is enough, instead of one per buffer. Keypresses only change the
+    // active buffer, so the other modified buffers were changed before the last keypress and have
+    // been idle for at least as long.
+    pub fn auto_save(&mut self, tokio_runtime: &tokio::runtime::Runtime, focus_lost: bool) -> bool {
+        let delay = Duration::from_secs(self.options.auto_save as u64);
+
+        if self.options.auto_save == 0
+            || !self.auto_save_pending
+            || (!focus_lost && self.last_input.elapsed() < delay)
+        {
+            return false;
+        }
+
+        self.auto_save_pending = false;
+
+        let mut written = 0;
+        let mut failed: Vec<String> = Vec::new();
+
+        // Scratch buffers, locked buffers, buffers without a file and read-only files are never
+        // auto-saved. Neither is a buffer in Insert mode, as trimming the whitespace on write would
+        // change the line that's being typed
+        for index in 0..self.buffer_manager.buffers.len() {
+            let buffer = &self.buffer_manager.buffers[index];
+
+            if !buffer.state.mutable
+                || buffer.read_only
+                || buffer.path.is_none()
+                || buffer.mode == Mode::Insert
+                || !buffer.is_modified()
+            {
+                continue;
+            }
+
+            let title = buffer.title.clone();
+
+            match self.write_buffer(index, None, false, true, tokio_runtime) {
+                Ok(_) => written += 1,
+                Err(_) => failed.push(title),
+            }
+        }
+
+        let quiet = self.message.is_some()
+            || matches!(
+                self.buffer_manager.buffers[self.buffer_manager.active_buffer].mode,
+                Mode::Command | Mode::Minibuffer
+            );
+
+        if !quiet {
+            if !failed.is_empty() {
+                self.message = Some(Message::error(format!(
+                    "Auto-save failed: {}",
+                    failed.join(", ")
+                )));
+            } else if written > 0 {
+                self.message = Some(Message::info("Autosaved".to_string()));
+            }
+        }
+
+        written > 0 || !failed.is_empty()
+    }
+
+    // Handles the results of the background tasks that finished since the last call. A loaded file
+    // takes the place of its placeholder buffer, unless the placeholder was killed in the meantime,
+    // and the output of a shell command is read into its buffer or shown in a buffer of its own.
+    // Returns true if any task finished, so the screen is redrawn.
+    pub fn handle_tasks(&mut self, keybinding_manager: &mut KeybindingManager) -> bool {
+        let mut finished = false;
+
+        while let Ok(task) = self.task_receiver.try_recv() {
+            finished = true;
+
+            match task {
+                TaskResult::FileLoaded { path, result } => {
+                    let Some(index) =
+                        self.buffer_manager.buffers.iter().position(|buffer| {
+                            buffer.loading && buffer.path.as_ref() == Some(&path)
+                        })
+                    else {
+                        continue;
+                    };
+
+                    let file = self
+                        .pending_files
+                        .iter()
+                        .position(|file| file.path == path)
+                        .map(|position| self.pending_files.remove(position));
+
+                    match result {
+                        Ok(mut buffer) => {
+                            let placeholder = &self.buffer_manager.buffers[index];
+
+                            buffer.options = self.options.buffer_options(&buffer.options);
+                            buffer.viewport.height = placeholder.viewport.height;
+
+                            if let Some(file) = file {
+                                move_to_file_position(&mut buffer, &file);
+                            }
+
+                            if index == self.buffer_manager.active_buffer {
+                                self.message = buffer.line_ending_note().map(Message::info);
+                            }
+
+                            self.buffer_manager.buffers[index] = *buffer;
+                        }
+                        Err(e) => {
+                            let height = self.renderer.get_terminal_size().height as usize;
+
+                            self.message =
+                                Some(Message::error(format!("{}: {}", path.display(), e)));
+                            self.buffer_manager.remove_buffer(
+                                index,
+                                height,
+                                self.options.scrolloff,
+                            );
+                        }
+                    }
+                }
+                TaskResult::ShellFinished {
+                    command,
+                    result,
+                    read_into,
+                } => match result {
+                    Ok(output) => {
+                        self.message = output.error_message().map(Message::error);
+
+                        match read_into {
+                            Some(index) => self.read_shell_output(index, output.stdout),
+                            None => self.show_shell_output(&command, output, keybinding_manager),
+                        }
+                    }
+                    Err(e) => self.message = Some(Message::error(e.to_string())),
+                },
+            }
+        }
+
+        finished
+    }
+
+    // Runs the shell command in the background, so the editor keeps rendering while a long command
+    // runs. The output is handled in 'handle_tasks' once the command finishes.
+    fn spawn_shell(
+        &mut self,
+        command: String,
+        read_into: Option<usize>,
+        tokio_runtime: &tokio::runtime::Runtime,
+    ) {
+        let sender = self.task_sender.clone();
+
+        self.message = Some(Message::info(format!("Running {}…", command)));
+
+        tokio_runtime.spawn(async move {
+            let result = run_shell(&command).await;
+            let _ = sender.send(TaskResult::ShellFinished {
+                command,
+                result,
+                read_into,
+            });
+        });
+    }
+
+    // Shows the output of the command in a read-only buffer, which is reused by the next command.
+    fn show_shell_output(
+        &mut self,
+        command: &str,
+        output: ShellOutput,
+        keybinding_manager: &mut KeybindingManager,
+    ) {
+        let mut content = output.stdout;
+        content.extend(output.stderr);
+
+        if content.is_empty() {
+            if self.message.is_none() {
+                self.message = Some(Message::info(format!("Command finished: {}", command)));
+            }
+
+            return;
+        }
+
+        let height = self.renderer.get_terminal_size().height as usize;
+        let buffer = Buffer::new(
+            "*Shell Output*".to_string(),
+            content,
+            None,
+            BufferKind::Normal,
+            height,
+            self.options.scrolloff,
+            BufferState::new(true, false),
+        );
+
+        let index = match self
+            .buffer_manager
+            .buffers
+            .iter()
+            .position(|buffer| buffer.title == "*Shell Output*" && buffer.path.is_none())
+        {
+            Some(index) => {
+                self.buffer_manager.buffers[index] = buffer;
+                index
+            }
+            None => {
+                self.buffer_manager.add_buffer(buffer);
+                self.buffer_manager.buffers.len() - 1
+            }
+        };
+
+        if let Err(e) = self.set_active_buffer(index, keybinding_manager) {
+            self.message = Some(Message::error(e.to_string()));
+        }
+    }
+
+    // Reads the output of the command into the buffer it was run from, if that buffer is still
+    // there.
+    fn read_shell_output(&mut self, index: usize, lines: Vec<String>) {
+        let Some(buffer) = self
+            .buffer_manager
+            .buffers
+            .get_mut(index)
+            .filter(|buffer| !buffer.loading)
+        else {
+            self.message = Some(Message::error(
+                "The buffer of the command was closed".to_string(),
+            ));
+            return;
+        };
+
+        if let Err(e) = buffer.insert_lines_below(lines) {
+            self.message = Some(Message::error(e.to_string()));
+        }
+    }
+
+    // Shows the save prompt for the first buffer it asks about.
+    fn ask_save_prompt(&mut self, prompt: SavePrompt) {
+        if let Some(buffer) = prompt
+            .buffers
+            .first()
+            .and_then(|index| self.buffer_manager.buffers.get(*index))
+        {
+            self.message = Some(Message::info(format!(
+                "Save changes to \"{}\"? (y/n/c)",
+                buffer.title
+            )));
+            self.save_prompt = Some(prompt);
+        }
+    }
+
+    // Saves the buffer on 'y', skips it on 'n' and cancels on 'c' or Escape. Once every buffer is
+    // answered, the action that opened the prompt is run.
+    fn answer_save_prompt(
+        &mut self,
+        key_event: KeyEvent,
+        keybinding_manager: &mut KeybindingManager,
+        tokio_runtime: &tokio::runtime::Runtime,
+    ) -> Result<()> {
+        let Some(mut prompt) = self.save_prompt.take() else {
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Char('y') => {
+                match self.write_buffer(prompt.buffers[0], None, false, false, tokio_runtime) {
+                    Ok(Some(e)) => {
+                        self.message = Some(Message::error(format!("Backup failed: {}", e)))
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.message = Some(Message::error(e.to_string()));
+                        return Ok(());
+                    }
+                }
+
+                prompt.buffers.remove(0);
+            }
+            KeyCode::Char('n') => {
+                prompt.buffers.remove(0);
+            }
+            KeyCode::Char('c') | KeyCode::Esc => return Ok(()),
+            // Any other key asks again
+            _ => {}
+        }
+
+        if !prompt.buffers.is_empty() {
+            self.ask_save_prompt(prompt);
+            return Ok(());
+        }
+
+        match prompt.action {
+            PromptAction::Quit => self.is_running = false,
+            PromptAction::KillBuffer => self.parse_action(
+                Action::KillBuffer { force: true },
+                keybinding_manager,
+                tokio_runtime,
+            )?,
+        }
+
+        Ok(())
+    }
+
+    // Opens the file in a new buffer and returns its index. A file that's already open isn't read
+    // again, and the index of its buffer is returned instead. Existing files are read in the
+    // background, behind a placeholder buffer that's replaced once the file is loaded. Files that
+    // don't exist yet are opened as empty buffers, and created when they're written.
+    fn open_file(&mut self, path: PathBuf, tokio_runtime: &tokio::runtime::Runtime) -> usize {
+        if let Some(index) = self.buffer_manager.find_buffer(&path) {
+            return index;
+        }
+
+        let height = self.renderer.get_terminal_size().height as usize;
+        let scrolloff = self.options.scrolloff;
+
+        if path.exists() {
+            let sender = self.task_sender.clone();
+
+            self.recent_files.add(&path);
+            self.buffer_manager
+                .add_buffer(Buffer::loading(path.clone(), height, scrolloff));
+
+            tokio_runtime.spawn(async move {
+                let result = Buffer::from_file(path.clone(), height, scrolloff)
+                    .await
+                    .map(Box::new);
+                let _ = sender.send(TaskResult::FileLoaded { path, result });
+            });
+        } else {
+            let title = match path.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => "[NO NAME]".to_string(),
+            };
+
+            let mut buffer = Buffer::new(
+                title,
+                Vec::new(),
+                Some(path),
+                BufferKind::Normal,
+                height,
+                scrolloff,
+                BufferState::default(),
+            );
+            buffer.options = self.options.buffer_options(&buffer.options);
+            self.buffer_manager.add_buffer(buffer);
+        }
+
+        self.buffer_manager.buffers.len() - 1
+    }
+
+    // Puts the recovered changes in the buffer of their file, or in a new buffer if it isn't open,
+    // and returns the index of the buffer. The buffer is modified either way, so the changes are
+    // only kept once they're written.
+    fn recover_buffer(&mut self, recovery: Recovery) -> usize {
+        let open = recovery
+            .path
+            .as_ref()
+            .and_then(|path| self.buffer_manager.find_buffer(path))
+            .filter(|index| !self.buffer_manager.buffers[*index].loading);
+
+        if let Some(index) = open {
+            self.buffer_manager.buffers[index].recover(recovery.content);
+            return index;
+        }
+
+        let height = self.renderer.get_terminal_size().height as usize;
+        let mut buffer = Buffer::recovered(
+            recovery.title,
+            recovery.content,
+            recovery.path,
+            height,
+            self.options.scrolloff,
+        );
+        buffer.options = self.options.buffer_options(&buffer.options);
+        self.buffer_manager.add_buffer(buffer);
+
+        self.buffer_manager.buffers.len() - 1
+    }
+
+    // Opens the files given on the command line, at the position given with them. The first file
+    // becomes the active buffer. The files that are still loading are moved to their position once
+    // they're loaded, and the ones that couldn't be read are reported then.
+    pub fn open_args(
+        &mut self,
+        files: Vec<FileArg>,
+        keybinding_manager: &mut KeybindingManager,
+        tokio_runtime: &tokio::runtime::Runtime,
+    ) -> Result<()> {
+        let mut first: Option<usize> = None;
+
+        for file in files {
+            let index = self.open_file(file.path.clone(), tokio_runtime);
+            let buffer = &mut self.buffer_manager.buffers[index];
+
+            if buffer.loading {
+                self.pending_files.push(file);
+            } else {
+                move_to_file_position(buffer, &file);
+            }
+
+            first.get_or_insert(index);
+        }
+
+        if let Some(index) = first {
+            self.set_active_buffer(index, keybinding_manager)?;
+            self.message = self.buffer_manager.buffers[index]
+                .line_ending_note()
+                .map(Message::info);
+        }
+
+        Ok(())
+    }
+
+    // Writes the buffer at the index, after backing up the file it overwrites if backups are on.
+    // Auto-saves skip the backup of a file that was already backed up this session. A failed backup
+    // doesn't stop the write, and is returned so it can be reported.
+    fn write_buffer(
+        &mut self,
+        index: usize,
+        path: Option<PathBuf>,
+        force: bool,
+        auto_save: bool,
+        tokio_runtime: &tokio::runtime::Runtime,
+    ) -> std::result::Result<Option<std::io::Error>, crate::buffer::Error> {
+        let buffer = &mut self.buffer_manager.buffers[index];
+
+        let backup_error = match path.as_ref().or(buffer.path.as_ref()) {
+            Some(target)
+                if self.options.backup
+                    && target.is_file()
+                    && !(auto_save && self.backed_up.contains(target)) =>
+            {
+                let result = write_backup(target, &self.options);
+
+                if result.is_ok() {
+                    self.backed_up.insert(target.clone());
+                }
+
+                result.err()
+            }
+            _ => None,
+        };
+
+        tokio_runtime.block_on(buffer.write_buffer(path, force))?;
+
+        if let Some(path) = &self.buffer_manager.buffers[index].path {
+            self.recent_files.add(path);
+        }
+
+        Ok(backup_error)
+    }
+
+    // Fills the minibuffer with the candidates for its input. An error is shown in the minibuffer
+    // instead of closing it, until the input fills it again.
+    fn fill_minibuffer(&mut self) {
+        self.minibuffer.error = self.minibuffer.fill().err().map(|error| error.to_string());
+    }
+
+    // Switches to the buffer at the index. The keybindings follow the kind of the new buffer, and
+    // its viewport is fitted to the current terminal height, which may have changed since it was
+    // last shown.
+    fn set_active_buffer(
+        &mut self,
+        index: usize,
+        keybinding_manager: &mut KeybindingManager,
+    ) -> Result<()> {
+        let height = self.renderer.get_terminal_size().height as usize;
+
+        self.buffer_manager.activate(index);
+
+        // The buffer list is brought up to date with the flags and line counts of the buffers
+        if self.buffer_manager.get_active_buffer()?.kind == BufferKind::BufferList {
+            self.buffer_manager.refresh_buffer_list();
+        }
+
+        let buffer = self.buffer_manager.get_active_buffer_mut()?;
+        buffer.viewport.height = height - 2;
+        buffer
+            .viewport
+            .adjust(buffer.cursor.y, buffer.content.len());
+
+        keybinding_manager.set_buffer_kind(buffer.kind);
+
+        Ok(())
+    }
+
+    // Pastes the register the given amount of times. In Visual mode the selection is replaced by
+    // the register, and the replaced text ends up in the register instead.
+    fn paste(&mut self, count: usize, before: bool) -> Result<()> {
+        let register = self.register.repeated(count);
+        let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+        if buffer.mode == Mode::Visual {
+            if let Some(replaced) = buffer.paste_over_selection(&register) {
+                self.register = replaced;
+            }
+        } else {
+            buffer.paste(&register, before);
+        }
 
         Ok(())
     }
@@ -99,91 +922,858 @@ impl Editor {
     pub fn parse_action(
         &mut self,
         action: Action,
-        keybinding_manager: &KeybindingManager,
+        keybinding_manager: &mut KeybindingManager,
         tokio_runtime: &tokio::runtime::Runtime,
     ) -> Result<()> {
         if self.buffer_manager.get_active_buffer()?.mode != Mode::Minibuffer {
             match action {
                 Action::SwitchMode(mode) => {
-                    self.buffer_manager.get_active_buffer_mut()?.switch_mode(mode);
+                    self.buffer_manager
+                        .get_active_buffer_mut()?
+                        .switch_mode(mode);
+                }
+                Action::InsertChar(c) => {
+                    self.buffer_manager.get_active_buffer_mut()?.add_char(c)?
                 }
-                Action::InsertChar(c) => self.buffer_manager.get_active_buffer_mut()?.add_char(c)?,
                 Action::InsertTab => self.buffer_manager.get_active_buffer_mut()?.add_tab()?,
-                Action::NewLine(direction) => self.buffer_manager.get_active_buffer_mut()?.new_line(direction),
-                Action::DeleteLine => self.buffer_manager.get_active_buffer_mut()?.delete_line(),
-                Action::MoveCursor(x, y) => self.buffer_manager.get_active_buffer_mut()?.move_cursor(x, y),
-                Action::TopOfBuffer => self.buffer_manager.get_active_buffer_mut()?.move_cursor_to_top(),
-                Action::EndOfBuffer => self.buffer_manager.get_active_buffer_mut()?.move_cursor_to_bot(),
-                Action::Quit => self.is_running = false,
-                Action::DeleteChar => self.buffer_manager.get_active_buffer_mut()?.remove_char()?,
-                Action::WriteBuffer => {
-                    tokio_runtime.block_on(self.buffer_manager.get_active_buffer_mut()?.write_buffer())?
+                Action::NewLine(direction) => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .new_line(direction),
+                Action::DeleteLine => {
+                    if let Some(register) =
+                        self.buffer_manager.get_active_buffer_mut()?.delete_line(1)
+                    {
+                        self.register = register;
+                    }
+                }
+                Action::MoveCursor(x, y) => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    buffer.move_cursor(x, y);
+                    // Moving the cursor in Insert mode starts a new undo step
+                    buffer.break_undo_sequence();
+                }
+                // Moves by screen rows when the lines wrap, and by lines otherwise
+                Action::MoveDisplayRow { down } => {
+                    let width = if self.options.wrap {
+                        self.renderer.get_buffer_width(&self.options)
+                    } else {
+                        0
+                    };
+
+                    self.buffer_manager
+                        .get_active_buffer_mut()?
+                        .move_display_row(down, width);
+                }
+                Action::WordForward => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_word_forward(),
+                Action::WordBackward => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_word_backward(),
+                Action::EndOfWord => self.buffer_manager.get_active_buffer_mut()?.move_word_end(),
+                Action::BigWordForward => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_big_word_forward(),
+                Action::BigWordBackward => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_big_word_backward(),
+                Action::StartOfLine => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    buffer.move_to_line_start();
+                    buffer.break_undo_sequence();
+                }
+                Action::EndOfLine => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    buffer.move_to_line_end();
+                    buffer.break_undo_sequence();
+                }
+                Action::FirstNonBlank => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_to_first_non_blank(),
+                Action::GotoLine(line) => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_to_line(line),
+                Action::FindChar(search) => {
+                    self.buffer_manager
+                        .get_active_buffer_mut()?
+                        .find_char(search, false);
+                    self.last_char_search = Some(search);
+                }
+                Action::RepeatFindChar { reverse } => {
+                    // Repeating without a previous search does nothing
+                    if let Some(search) = self.last_char_search {
+                        let search = if reverse { search.reversed() } else { search };
+
+                        self.buffer_manager
+                            .get_active_buffer_mut()?
+                            .find_char(search, true);
+                    }
+                }
+                Action::MatchingBracket => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_to_matching_bracket(),
+                Action::GotoPercentage(percent) => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_to_percentage(percent),
+                Action::ScrollHalfPage { down } => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .scroll_half_page(down),
+                Action::ScrollPage { down } => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .scroll_page(down),
+                Action::ScrollLine { down } => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .scroll_line(down),
+                Action::AlignViewport(position) => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .align_viewport(position),
+                Action::MoveToViewport(position) => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_to_viewport(position),
+                Action::Repeat(count, action) => match *action {
+                    // Jumping to the top or end of the buffer with a count goes to that line instead
+                    Action::TopOfBuffer | Action::EndOfBuffer => self
+                        .buffer_manager
+                        .get_active_buffer_mut()?
+                        .move_to_line(count),
+                    // '%' with a count jumps to that percentage of the buffer instead of the matching bracket
+                    Action::MatchingBracket => self.parse_action(
+                        Action::GotoPercentage(count),
+                        keybinding_manager,
+                        tokio_runtime,
+                    )?,
+                    Action::DeleteLine => {
+                        if let Some(register) = self
+                            .buffer_manager
+                            .get_active_buffer_mut()?
+                            .delete_line(count)
+                        {
+                            self.register = register;
+                        }
+                    }
+                    // Toggling repeatedly would undo itself, so the count is ignored
+                    Action::ToggleComment => self
+                        .buffer_manager
+                        .get_active_buffer_mut()?
+                        .toggle_comment(),
+                    Action::YankLine => {
+                        self.register = self
+                            .buffer_manager
+                            .get_active_buffer_mut()?
+                            .yank_lines(count)
+                    }
+                    Action::Paste { before } => self.paste(count, before)?,
+                    // 'x' with a count deletes that many characters, up to the end of the line
+                    Action::DeleteChar
+                        if self.buffer_manager.get_active_buffer()?.mode == Mode::Normal =>
+                    {
+                        self.parse_action(
+                            Action::Delete(Motion::Chars(count)),
+                            keybinding_manager,
+                            tokio_runtime,
+                        )?
+                    }
+                    action => {
+                        // Repeated changes are undone in one step
+                        let undo_len = self.buffer_manager.get_active_buffer()?.history.len();
+
+                        for _ in 0..count {
+                            self.parse_action(action.clone(), keybinding_manager, tokio_runtime)?;
+                        }
+
+                        self.buffer_manager
+                            .get_active_buffer_mut()?
+                            .history
+                            .squash(undo_len);
+                    }
+                },
+                Action::TopOfBuffer => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_cursor_to_top(),
+                Action::EndOfBuffer => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_cursor_to_bot(),
+                Action::KillBuffer { force } => {
+                    let buffer = self.buffer_manager.get_active_buffer()?;
+
+                    if !buffer.state.killable {
+                        self.message = Some(Message::error(format!(
+                            "Buffer \"{}\" can't be killed",
+                            buffer.title
+                        )));
+                    } else if !force && buffer.is_modified() {
+                        self.ask_save_prompt(SavePrompt {
+                            buffers: vec![self.buffer_manager.active_buffer],
+                            action: PromptAction::KillBuffer,
+                        });
+                    } else {
+                        let height = self.renderer.get_terminal_size().height as usize;
+                        self.buffer_manager
+                            .remove_active_buffer(height, self.options.scrolloff);
+                        self.set_active_buffer(
+                            self.buffer_manager.active_buffer,
+                            keybinding_manager,
+                        )?;
+                    }
+                }
+                Action::CycleBuffer { next } => {
+                    let len = self.buffer_manager.buffers.len();
+                    let active = self.buffer_manager.active_buffer;
+                    let index = if next {
+                        (active + 1) % len
+                    } else {
+                        (active + len - 1) % len
+                    };
+
+                    self.set_active_buffer(index, keybinding_manager)?;
+                }
+                Action::QuitAll { force } => {
+                    let modified: Vec<usize> = (0..self.buffer_manager.buffers.len())
+                        .filter(|index| self.buffer_manager.buffers[*index].is_modified())
+                        .collect();
+
+                    if !force && !modified.is_empty() {
+                        self.ask_save_prompt(SavePrompt {
+                            buffers: modified,
+                            action: PromptAction::Quit,
+                        });
+                    } else {
+                        self.is_running = false;
+                    }
+                }
+                Action::Quit { force } => {
+                    if !force && self.buffer_manager.get_active_buffer()?.is_modified() {
+                        self.ask_save_prompt(SavePrompt {
+                            buffers: vec![self.buffer_manager.active_buffer],
+                            action: PromptAction::Quit,
+                        });
+                    } else {
+                        self.is_running = false;
+                    }
+                }
+                Action::DeleteChar => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    // Deleted text is kept in the register so it can be pasted, except for
+                    // backspacing in Insert and Command mode
+                    let deleted = match buffer.mode {
+                        Mode::Normal if buffer.state.mutable => buffer.yank_char(),
+                        Mode::Visual if buffer.state.mutable => buffer.yank_selection(),
+                        _ => None,
+                    };
+
+                    if let Some(register) = deleted {
+                        self.register = register;
+                    }
+
+                    buffer.remove_char()?;
+                }
+                Action::DeleteForward => {
+                    if let Some(register) = self
+                        .buffer_manager
+                        .get_active_buffer_mut()?
+                        .delete_forward()
+                    {
+                        self.register = register;
+                    }
+                }
+                Action::DeleteWordBefore => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .delete_word_before()?,
+                Action::DeleteToLineStart => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .delete_to_line_start()?,
+                Action::Yank => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    if let Some((start, _)) = buffer.selection() {
+                        if let Some(register) = buffer.yank_selection() {
+                            self.register = register;
+                        }
+
+                        // The cursor goes back to the start of the selection, like in Vim
+                        buffer.cursor.x = start.x;
+                        buffer.cursor.y = start.y;
+                        buffer.cursor.desired_x = start.x;
+                        buffer
+                            .viewport
+                            .adjust(buffer.cursor.y, buffer.content.len());
+                    }
+
+                    buffer.switch_mode(ModeParams::Normal);
+                }
+                Action::Delete(motion) => match self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .delete_motion(motion)
+                {
+                    Ok(Some(register)) => self.register = register,
+                    Ok(None) => {}
+                    Err(e) => self.message = Some(Message::error(e.to_string())),
+                },
+                Action::Change(motion) => match self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .change_motion(motion)
+                {
+                    Ok(Some(register)) => self.register = register,
+                    Ok(None) => {}
+                    Err(e) => self.message = Some(Message::error(e.to_string())),
+                },
+                Action::ChangeLine => {
+                    if let Some(register) =
+                        self.buffer_manager.get_active_buffer_mut()?.change_line()
+                    {
+                        self.register = register;
+                    }
+                }
+                Action::ReplaceChar(c) => {
+                    self.buffer_manager.get_active_buffer_mut()?.replace_char(c)
+                }
+                Action::JoinLines { spaces } => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .join_line(spaces),
+                Action::MoveLines { down } => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .move_lines(down),
+                Action::Indent => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+                    buffer.indent_line(buffer.options.shift_width)
+                }
+                Action::Dedent => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+                    buffer.dedent_line(buffer.options.shift_width)
+                }
+                Action::ChangeCase(case) => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .change_case(case),
+                Action::ToggleComment => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .toggle_comment(),
+                Action::Reflow => self.buffer_manager.get_active_buffer_mut()?.reflow(),
+                Action::VisualInsert { append } => self
+                    .buffer_manager
+                    .get_active_buffer_mut()?
+                    .start_visual_insert(append),
+                Action::ToggleMacroRecording => match self.recording.take() {
+                    Some((name, mut keys)) => {
+                        // The 'q' that stopped the recording isn't part of the macro
+                        keys.pop();
+                        self.macros.insert(name, keys);
+                    }
+                    None => keybinding_manager.await_char(PendingAction::RecordMacro),
+                },
+                Action::RecordMacro(name) => {
+                    if name.is_ascii_lowercase() {
+                        self.recording = Some((name, Vec::new()));
+                    } else {
+                        self.message = Some(Message::error("Macros are named a-z".to_string()));
+                    }
+                }
+                Action::PlayMacro(name) => {
+                    // '@@' plays the last played macro again
+                    let name = match (name, self.last_macro) {
+                        ('@', Some(last_macro)) => last_macro,
+                        _ => name,
+                    };
+
+                    // A macro playing itself would never stop, so it's stopped right away
+                    if self.playing_macros.contains(&name) {
+                        self.message =
+                            Some(Message::error(format!("Macro @{} plays itself", name)));
+                    } else if let Some(keys) = self.macros.get(&name).cloned() {
+                        self.last_macro = Some(name);
+                        self.playing_macros.push(name);
+
+                        let result = keys.into_iter().try_for_each(|key_event| {
+                            self.handle_key(key_event, keybinding_manager, tokio_runtime)
+                        });
+
+                        self.playing_macros.pop();
+                        result?;
+                    } else {
+                        self.message =
+                            Some(Message::error(format!("Macro @{} is not recorded", name)));
+                    }
+                }
+                Action::YankLine => {
+                    self.register = self.buffer_manager.get_active_buffer_mut()?.yank_lines(1)
+                }
+                Action::Paste { before } => self.paste(1, before)?,
+                Action::WriteBuffer { path, force } => {
+                    let index = self.buffer_manager.active_buffer;
+
+                    // The error is returned as well, so the commands after the write aren't run
+                    match self.write_buffer(index, path, force, false, tokio_runtime) {
+                        Ok(Some(e)) => {
+                            self.message = Some(Message::error(format!("Backup failed: {}", e)))
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            self.message = Some(Message::error(e.to_string()));
+                            return Err(e.into());
+                        }
+                    }
+                }
+                Action::WriteAll => {
+                    let mut written = 0;
+                    let mut failed: Vec<String> = Vec::new();
+                    let mut backups_failed: Vec<String> = Vec::new();
+
+                    // Buffers without changes or a file to write to are skipped
+                    for index in 0..self.buffer_manager.buffers.len() {
+                        let buffer = &self.buffer_manager.buffers[index];
+
+                        if !buffer.state.mutable || buffer.path.is_none() || !buffer.is_modified() {
+                            continue;
+                        }
+
+                        let title = buffer.title.clone();
+
+                        match self.write_buffer(index, None, false, false, tokio_runtime) {
+                            Ok(backup_error) => {
+                                written += 1;
+
+                                if backup_error.is_some() {
+                                    backups_failed.push(title);
+                                }
+                            }
+                            Err(_) => failed.push(title),
+                        }
+                    }
+
+                    let summary = format!(
+                        "{} buffer{} written",
+                        written,
+                        if written == 1 { "" } else { "s" }
+                    );
+
+                    let summary = if failed.is_empty() {
+                        summary
+                    } else {
+                        format!(
+                            "{}, {} failed: {}",
+                            summary,
+                            failed.len(),
+                            failed.join(", ")
+                        )
+                    };
+
+                    self.message = Some(if backups_failed.is_empty() && failed.is_empty() {
+                        Message::info(summary)
+                    } else if backups_failed.is_empty() {
+                        Message::error(summary)
+                    } else {
+                        Message::error(format!(
+                            "{}, backup failed: {}",
+                            summary,
+                            backups_failed.join(", ")
+                        ))
+                    });
                 }
                 Action::ExecuteCommand => {
-                    let input: &str = self.buffer_manager.get_active_buffer_mut()?.get_command();
-                    let commands = CommandParser::parse(input);
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    // The prefix tells what the input is meant for
+                    let commands = match buffer.command_line.prefix.as_str() {
+                        "/" => vec![Action::Search(Search {
+                            pattern: buffer.get_command().to_string(),
+                            direction: SearchDirection::Forward,
+                            whole_word: false,
+                        })],
+                        "?" => vec![Action::Search(Search {
+                            pattern: buffer.get_command().to_string(),
+                            direction: SearchDirection::Backward,
+                            whole_word: false,
+                        })],
+                        _ => CommandParser::parse(buffer.get_command()),
+                    };
+
+                    // The mode is switched first, since the commands can change the active buffer
+                    buffer.switch_mode(ModeParams::Normal);
 
                     for command in commands {
                         self.parse_action(command, keybinding_manager, tokio_runtime)?;
                     }
+                }
+                Action::Search(search) => {
+                    // An empty pattern repeats the last pattern in the new direction
+                    let search = match (search.pattern.is_empty(), &self.search) {
+                        (true, Some(last_search)) => Search {
+                            direction: search.direction,
+                            ..last_search.clone()
+                        },
+                        _ => search,
+                    };
+
+                    if !self.buffer_manager.get_active_buffer_mut()?.search(&search) {
+                        self.message = Some(Message::error(format!(
+                            "Pattern not found: {}",
+                            search.pattern
+                        )));
+                    }
 
-                    self.buffer_manager.get_active_buffer_mut()?
-                        .switch_mode(ModeParams::Normal);
+                    self.search = Some(search);
+                    self.search_highlight = true;
+                }
+                Action::RepeatSearch { reverse } => {
+                    if let Some(search) = &self.search {
+                        self.search_highlight = true;
+
+                        let search = if reverse {
+                            search.reversed()
+                        } else {
+                            search.clone()
+                        };
+
+                        if !self.buffer_manager.get_active_buffer_mut()?.search(&search) {
+                            self.message = Some(Message::error(format!(
+                                "Pattern not found: {}",
+                                search.pattern
+                            )));
+                        }
+                    }
+                }
+                Action::ClearSearchHighlight => self.search_highlight = false,
+                Action::Substitute(substitution) => {
+                    let (replacements, lines) = self
+                        .buffer_manager
+                        .get_active_buffer_mut()?
+                        .substitute(&substitution);
+
+                    self.message = Some(if replacements == 0 {
+                        Message::error(format!("Pattern not found: {}", substitution.pattern))
+                    } else {
+                        Message::info(format!(
+                            "{} substitution{} on {} line{}",
+                            replacements,
+                            if replacements == 1 { "" } else { "s" },
+                            lines,
+                            if lines == 1 { "" } else { "s" },
+                        ))
+                    });
+                }
+                Action::Error(message) => self.message = Some(Message::error(message)),
+                Action::SetOption(name, value) => {
+                    let buffer =
+                        &mut self.buffer_manager.buffers[self.buffer_manager.active_buffer];
+
+                    if let Err(message) = self.options.set(&mut buffer.options, &name, &value) {
+                        self.message = Some(Message::error(message));
+                    }
+
+                    for buffer in &mut self.buffer_manager.buffers {
+                        buffer.viewport.scrolloff = self.options.scrolloff;
+                    }
+                }
+                Action::ShowOptions => {
+                    let buffer = self.buffer_manager.get_active_buffer()?;
+                    self.message = Some(Message::info(self.options.describe(&buffer.options)));
+                }
+                Action::Undo => {
+                    if let Err(e) = self.buffer_manager.get_active_buffer_mut()?.undo() {
+                        self.message = Some(Message::error(e.to_string()));
+                    }
+                }
+                Action::Redo => {
+                    if let Err(e) = self.buffer_manager.get_active_buffer_mut()?.redo() {
+                        self.message = Some(Message::error(e.to_string()));
+                    }
+                }
+                Action::SetMark(name) => {
+                    if let Err(e) = self.buffer_manager.get_active_buffer_mut()?.set_mark(name) {
+                        self.message = Some(Message::error(e.to_string()));
+                    }
+                }
+                Action::GotoMark(name) => {
+                    if let Err(e) = self.buffer_manager.get_active_buffer_mut()?.goto_mark(name) {
+                        self.message = Some(Message::error(e.to_string()));
+                    }
+                }
+                Action::SearchWordUnderCursor(direction) => {
+                    match self
+                        .buffer_manager
+                        .get_active_buffer_mut()?
+                        .search_word_under_cursor(direction)
+                    {
+                        Some(search) => {
+                            self.search = Some(search);
+                            self.search_highlight = true;
+                        }
+                        None => {
+                            self.message = Some(Message::error("No word under cursor".to_string()))
+                        }
+                    }
                 }
                 Action::OpenFile(path) => {
-                    tokio_runtime.block_on(self.buffer_manager.get_active_buffer_mut()?.load_file(&path))?;
+                    let index = self.open_file(path, tokio_runtime);
+
+                    self.message = self.buffer_manager.buffers[index]
+                        .line_ending_note()
+                        .map(Message::info);
+                    self.set_active_buffer(index, keybinding_manager)?;
+                }
+                // The placeholder of a file that's still loading is replaced once it's read anyway
+                Action::ReloadBuffer { .. } if self.buffer_manager.get_active_buffer()?.loading => {
+                }
+                Action::ReloadBuffer { force } => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    // Buffers of files that were never written have nothing to revert to either
+                    match buffer.path.clone().filter(|path| path.exists()) {
+                        None => {
+                            self.message =
+                                Some(Message::error("No file to revert from".to_string()))
+                        }
+                        Some(_) if !force && buffer.is_modified() => {
+                            self.message = Some(Message::error(
+                                "No write since last change (add ! to override)".to_string(),
+                            ))
+                        }
+                        Some(path) => match tokio_runtime.block_on(buffer.load_file(&path)) {
+                            Ok(_) => {
+                                self.message =
+                                    Some(Message::info(format!("\"{}\" reloaded", buffer.title)));
+
+                                if let Some(note) = buffer.line_ending_note() {
+                                    self.message = Some(Message::info(note));
+                                }
+                            }
+                            Err(e) => self.message = Some(Message::error(e.to_string())),
+                        },
+                    }
+                }
+                // The recovered changes go to the buffer of the file they came from, as a change
+                // that's written to the file like any other. The recovery file is removed once
+                // its changes are back in a buffer.
+                Action::Recover => {
+                    let files = recovery_dir()
+                        .map(|dir| recovery_files(&dir))
+                        .unwrap_or_default();
+                    let mut recovered = 0;
+                    let mut failed: Vec<String> = Vec::new();
+
+                    for file in files {
+                        match read_recovery(&file) {
+                            Ok(recovery) => {
+                                let index = self.recover_buffer(recovery);
+
+                                let _ = std::fs::remove_file(&file);
+                                recovered += 1;
+                                self.set_active_buffer(index, keybinding_manager)?;
+                            }
+                            Err(e) => failed.push(format!("{}: {}", file.display(), e)),
+                        }
+                    }
+
+                    self.message = Some(if !failed.is_empty() {
+                        Message::error(failed.join("; "))
+                    } else if recovered == 0 {
+                        Message::info("No recovery files".to_string())
+                    } else {
+                        Message::info(format!(
+                            "Recovered {} buffer{}, write them to keep the changes",
+                            recovered,
+                            if recovered == 1 { "" } else { "s" }
+                        ))
+                    });
+                }
+                Action::ShowBufferList => {
+                    let index = match self
+                        .buffer_manager
+                        .buffers
+                        .iter()
+                        .position(|buffer| buffer.kind == BufferKind::BufferList)
+                    {
+                        Some(index) => index,
+                        None => {
+                            let height = self.renderer.get_terminal_size().height as usize;
+                            self.buffer_manager
+                                .add_buffer(Buffer::buffer_list(height, self.options.scrolloff));
+                            self.buffer_manager.buffers.len() - 1
+                        }
+                    };
+
+                    self.set_active_buffer(index, keybinding_manager)?;
+                }
+                Action::BufferListOpen => {
+                    let line = self.buffer_manager.get_active_buffer()?.cursor.y;
+
+                    if let Some(index) = self.buffer_manager.listed_buffers().get(line) {
+                        self.set_active_buffer(*index, keybinding_manager)?;
+                    }
+                }
+                // The mark is kept in the first column of the line, and the cursor moves on to the
+                // next line so several buffers can be marked in a row
+                Action::BufferListMark { delete } => {
+                    let list = self.buffer_manager.get_active_buffer_mut()?;
+                    let line = &mut list.content[list.cursor.y];
+
+                    if !line.is_empty() {
+                        line.replace_range(..1, if delete { "D" } else { " " });
+                        list.cursor.y = (list.cursor.y + 1).min(list.content.len() - 1);
+                        list.viewport.adjust(list.cursor.y, list.content.len());
+                    }
+                }
+                Action::BufferListExecute => {
+                    let listed = self.buffer_manager.listed_buffers();
+                    let list = self.buffer_manager.get_active_buffer()?;
+                    let marked: Vec<usize> = list
+                        .content
+                        .iter()
+                        .zip(listed)
+                        .filter(|(line, _)| line.starts_with('D'))
+                        .map(|(_, index)| index)
+                        .collect();
+
+                    let height = self.renderer.get_terminal_size().height as usize;
+                    let mut kept: Vec<String> = Vec::new();
+
+                    // Buffers are removed from the back, so the indices before them stay valid
+                    for index in marked.into_iter().rev() {
+                        let buffer = &self.buffer_manager.buffers[index];
+
+                        if !buffer.state.killable || buffer.is_modified() {
+                            kept.push(buffer.title.clone());
+                        } else {
+                            self.buffer_manager.remove_buffer(
+                                index,
+                                height,
+                                self.options.scrolloff,
+                            );
+                        }
+                    }
+
+                    if !kept.is_empty() {
+                        kept.reverse();
+                        self.message = Some(Message::error(format!(
+                            "Buffers with unsaved changes or that can't be killed were kept: {}",
+                            kept.join(", ")
+                        )));
+                    }
+
+                    self.buffer_manager.refresh_buffer_list();
+                }
+                Action::ReadFile(path) => {
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+
+                    if let Err(e) = tokio_runtime.block_on(buffer.read_file(&path)) {
+                        self.message = Some(Message::error(e.to_string()));
+                    }
+                }
+                Action::ShellCommand(command) => self.spawn_shell(command, None, tokio_runtime),
+                Action::ReadShellCommand(command) => {
+                    let index = self.buffer_manager.active_buffer;
+                    self.spawn_shell(command, Some(index), tokio_runtime)
                 }
                 Action::Minibuffer(kind) => {
-                    self.buffer_manager.get_active_buffer_mut()?
+                    self.buffer_manager
+                        .get_active_buffer_mut()?
                         .switch_mode(ModeParams::Minibuffer);
 
                     match kind {
                         MinibufferKind::Buffer(_) => {
-                            let mut buffers: Vec<String> = Vec::new();
-
-                            for buffer in &self.buffer_manager.buffers {
-                                buffers.push(buffer.title.clone());
-                            }
+                            let buffers = self
+                                .buffer_manager
+                                .buffers_by_recency()
+                                .into_iter()
+                                .map(|index| {
+                                    (index, self.buffer_manager.buffers[index].display_title())
+                                })
+                                .collect();
 
                             self.minibuffer.kind = MinibufferKind::Buffer(buffers);
                         }
+                        MinibufferKind::Recent(_) => {
+                            let files = self
+                                .recent_files
+                                .existing()
+                                .iter()
+                                .map(|file| file.to_string_lossy().into_owned())
+                                .collect();
+
+                            self.minibuffer.kind = MinibufferKind::Recent(files);
+                        }
                         _ => self.minibuffer.kind = kind,
                     }
 
-                    self.minibuffer.fill()?;
+                    let height = self.renderer.get_terminal_size().height as usize;
+                    self.minibuffer.resize(height);
+                    self.fill_minibuffer();
                 }
                 _ => {}
             }
         } else {
             match action {
+                // Escaping returns to the mode the minibuffer was opened from
                 Action::Escape => {
                     self.minibuffer = Minibuffer::default();
-                    self.buffer_manager.get_active_buffer_mut()?
-                        .switch_mode(ModeParams::Normal);
+
+                    let buffer = self.buffer_manager.get_active_buffer_mut()?;
+                    buffer.leave_minibuffer();
+
+                    keybinding_manager.reset();
+                    keybinding_manager.set_buffer_kind(buffer.kind);
+                    return Ok(());
                 }
                 Action::InsertChar(c) => self.minibuffer.add_char(c)?,
                 Action::MoveCursor(x, y) => self.minibuffer.move_cursor(x, y),
                 Action::DeleteChar => self.minibuffer.remove_char()?,
-                Action::Append => self.minibuffer.append(),
-                Action::ExecuteCommand => match self.minibuffer.execute()? {
-                    Some(action) => {
+                Action::CycleCandidate { next } => self.minibuffer.complete_or_cycle(next),
+                Action::AcceptCompletion => self.minibuffer.append(),
+                Action::ExecuteCommand => match self.minibuffer.execute() {
+                    // The minibuffer stays open on an error, so the input can be corrected
+                    Err(error) => {
+                        self.minibuffer.error = Some(error.to_string());
+                        return Ok(());
+                    }
+                    Ok(Some(action)) => {
+                        // The mode is switched first, since the action can change the active buffer
+                        self.buffer_manager
+                            .get_active_buffer_mut()?
+                            .switch_mode(ModeParams::Normal);
+
                         match action {
+                            // The file gets a buffer of its own, so the active buffer and its
+                            // changes are kept. A file that's already open is switched to instead
                             Action::OpenFile(path) => {
-                                if self.buffer_manager.get_active_buffer()?.path.is_some() {
-                                    tokio_runtime
-                                        .block_on(self.buffer_manager.get_active_buffer_mut()?.load_file(&path))?;
-                                } else {
-                                    let height = self.renderer.get_terminal_size().height as usize;
-                                    let buffer =
-                                        tokio_runtime.block_on(Buffer::from_file(path, height))?;
+                                let index = self.open_file(path, tokio_runtime);
+                                self.set_active_buffer(index, keybinding_manager)?;
 
-                                    self.buffer_manager.add_buffer(buffer);
-                                    self.buffer_manager.active_buffer = self.buffer_manager.buffers.len() - 1;
-                                }
+                                self.message = self
+                                    .buffer_manager
+                                    .get_active_buffer()?
+                                    .line_ending_note()
+                                    .map(Message::info);
                             }
                             Action::OpenBuffer(num) => {
                                 if num < self.buffer_manager.buffers.len() {
-                                    self.buffer_manager.active_buffer = num;
+                                    self.set_active_buffer(num, keybinding_manager)?;
                                 } else {
                                     return Err(OxideError::IndexError);
                                 }
@@ -192,17 +1782,50 @@ impl Editor {
                         }
 
                         self.minibuffer = Minibuffer::default();
-                        self.buffer_manager.get_active_buffer_mut()?
-                            .switch_mode(ModeParams::Normal);
                     }
-                    None => {}
+                    Ok(None) => {}
                 },
                 _ => {}
             }
 
-            self.minibuffer.fill()?;
+            self.fill_minibuffer();
         }
 
         Ok(())
     }
 }
+
+// ╭──────────────────────────────────────╮
+// │ Editor Helpers                       │
+// ╰──────────────────────────────────────╯
+
+// Resolves the path to the file it points to, following symlinks. A file that doesn't exist can't
+// be resolved, so its directory is resolved instead, or it's only made absolute if that doesn't
+// exist either.
+fn resolve_path(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    match (dir.canonicalize(), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+// Moves the cursor to the line and column the file was opened at. Both start at 1.
+fn move_to_file_position(buffer: &mut Buffer, file: &FileArg) {
+    if let Some(line) = file.line {
+        buffer.move_to_line(line);
+    }
+
+    if let Some(column) = file.column {
+        buffer.cursor.x = column.saturating_sub(1).min(buffer.max_cursor_x());
+        buffer.cursor.desired_x = buffer.cursor.x;
+    }
+}
@@ -2,6 +2,18 @@
 // │ Editor Module                        │
 // ╰──────────────────────────────────────╯
 
+pub mod backup;
 pub mod editor;
+pub mod options;
+pub mod recent;
+pub mod recovery;
+pub mod shell;
+pub mod task;
 
+pub use backup::*;
 pub use editor::*;
+pub use options::*;
+pub use recent::*;
+pub use recovery::*;
+pub use shell::*;
+pub use task::*;
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+
+use crate::buffer::{BufferOptions, FileFormat};
+use crate::keybinding::OptionValue;
+
+// ╭──────────────────────────────────────╮
+// │ Options Structs                      │
+// ╰──────────────────────────────────────╯
+
+// The options of the editor. The buffer options are the values new buffers start with, while the
+// open buffers keep their own copy.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Options {
+    pub number: bool,
+    pub relative_number: bool,
+    pub scrolloff: usize,
+    pub wrap: bool,
+    pub list: bool,
+    pub backup: bool,
+    pub backup_dir: Option<PathBuf>,
+    pub numbered_backups: bool,
+    pub auto_save: usize,
+    pub buffer: BufferOptions,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            number: true,
+            relative_number: false,
+            scrolloff: 0,
+            wrap: false,
+            list: false,
+            backup: false,
+            backup_dir: None,
+            numbered_backups: false,
+            auto_save: 0,
+            buffer: BufferOptions::default(),
+        }
+    }
+}
+
+impl Options {
+    // Sets the option with the given name, like ':set' in Vim. Buffer options are set on both the
+    // given buffer and the defaults for new buffers, except for 'endofline' which only describes
+    // the file of the buffer. Returns an error message if the option doesn't exist or the value
+    // isn't valid for it.
+    pub fn set(
+        &mut self,
+        buffer: &mut BufferOptions,
+        name: &str,
+        value: &OptionValue,
+    ) -> Result<(), String> {
+        match name {
+            "number" | "nu" => self.number = flag(name, value)?,
+            "relativenumber" | "rnu" => self.relative_number = flag(name, value)?,
+            "scrolloff" | "so" => self.scrolloff = number(name, value, 0)?,
+            "wrap" => self.wrap = flag(name, value)?,
+            "list" => self.list = flag(name, value)?,
+            "backup" | "bk" => self.backup = flag(name, value)?,
+            "backupdir" | "bdir" => match value {
+                OptionValue::Value(dir) if dir.is_empty() => self.backup_dir = None,
+                OptionValue::Value(dir) => self.backup_dir = Some(PathBuf::from(dir)),
+                _ => return Err(format!("Option {} needs a value", name)),
+            },
+            "numberedbackup" | "nbk" => self.numbered_backups = flag(name, value)?,
+            "autosave" | "as" => self.auto_save = number(name, value, 0)?,
+            "tabwidth" | "ts" => {
+                buffer.tab_width = number(name, value, 1)?;
+                self.buffer.tab_width = buffer.tab_width;
+            }
+            "expandtab" | "et" => {
+                buffer.expand_tab = flag(name, value)?;
+                self.buffer.expand_tab = buffer.expand_tab;
+            }
+            "shiftwidth" | "sw" => {
+                buffer.shift_width = number(name, value, 1)?;
+                self.buffer.shift_width = buffer.shift_width;
+            }
+            "textwidth" | "tw" => {
+                buffer.text_width = number(name, value, 1)?;
+                self.buffer.text_width = buffer.text_width;
+            }
+            "autopairs" | "ap" => {
+                buffer.auto_pairs = flag(name, value)?;
+                self.buffer.auto_pairs = buffer.auto_pairs;
+            }
+            "trim" => {
+                buffer.trim_whitespace = flag(name, value)?;
+                self.buffer.trim_whitespace = buffer.trim_whitespace;
+            }
+            "fileformat" | "ff" => {
+                buffer.file_format = file_format(name, value)?;
+                self.buffer.file_format = buffer.file_format;
+            }
+            "endofline" | "eol" => buffer.end_of_line = flag(name, value)?,
+            "fixendofline" | "fixeol" => {
+                buffer.fix_end_of_line = flag(name, value)?;
+                self.buffer.fix_end_of_line = buffer.fix_end_of_line;
+            }
+            // A 'no' in front of an option turns it off, which is only checked after the option
+            // names so options like 'number' still work
+            _ => match (name.strip_prefix("no"), value) {
+                (Some(option), OptionValue::On) => {
+                    return self
+                        .set(buffer, option, &OptionValue::Off)
+                        .map_err(|error| {
+                            // The error names the whole option that was typed
+                            if error == unknown_option(option) {
+                                unknown_option(name)
+                            } else {
+                                error
+                            }
+                        });
+                }
+                _ => return Err(unknown_option(name)),
+            },
+        }
+
+        Ok(())
+    }
+
+    // Returns the options for a newly opened buffer, which keeps the indentation style and line
+    // endings detected in its file.
+    pub fn buffer_options(&self, detected: &BufferOptions) -> BufferOptions {
+        BufferOptions {
+            expand_tab: detected.expand_tab,
+            file_format: detected.file_format,
+            end_of_line: detected.end_of_line,
+            ..self.buffer.clone()
+        }
+    }
+
+    // Lists the current values the way they're set, like 'nowrap' or 'tabwidth=4'.
+    pub fn describe(&self, buffer: &BufferOptions) -> String {
+        let show_flag = |name: &str, on: bool| {
+            if on {
+                name.to_string()
+            } else {
+                format!("no{}", name)
+            }
+        };
+
+        [
+            show_flag("number", self.number),
+            show_flag("relativenumber", self.relative_number),
+            format!("scrolloff={}", self.scrolloff),
+            show_flag("wrap", self.wrap),
+            show_flag("list", self.list),
+            show_flag("backup", self.backup),
+            format!(
+                "backupdir={}",
+                self.backup_dir
+                    .as_ref()
+                    .map(|dir| dir.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            ),
+            show_flag("numberedbackup", self.numbered_backups),
+            format!("autosave={}", self.auto_save),
+            format!("tabwidth={}", buffer.tab_width),
+            show_flag("expandtab", buffer.expand_tab),
+            format!("shiftwidth={}", buffer.shift_width),
+            format!("textwidth={}", buffer.text_width),
+            show_flag("autopairs", buffer.auto_pairs),
+            show_flag("trim", buffer.trim_whitespace),
+            format!("fileformat={}", buffer.file_format.name()),
+            show_flag("endofline", buffer.end_of_line),
+            show_flag("fixendofline", buffer.fix_end_of_line),
+        ]
+        .join(" ")
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Options Helpers                      │
+// ╰──────────────────────────────────────╯
+
+fn unknown_option(name: &str) -> String {
+    format!("Unknown option: {}", name)
+}
+
+// Reads the value of an option that's either on or off.
+fn flag(name: &str, value: &OptionValue) -> Result<bool, String> {
+    match value {
+        OptionValue::On => Ok(true),
+        OptionValue::Off => Ok(false),
+        OptionValue::Value(_) => Err(format!("Option {} takes no value", name)),
+    }
+}
+
+// Reads the value of a numeric option, which can't be less than the minimum.
+fn number(name: &str, value: &OptionValue, min: usize) -> Result<usize, String> {
+    match value {
+        OptionValue::Value(value) => match value.parse::<usize>() {
+            Ok(number) if number >= min => Ok(number),
+            _ => Err(format!("Invalid value for {}: {}", name, value)),
+        },
+        _ => Err(format!("Option {} needs a value", name)),
+    }
+}
+
+// Reads the value of the 'fileformat' option.
+fn file_format(name: &str, value: &OptionValue) -> Result<FileFormat, String> {
+    match value {
+        OptionValue::Value(value) if value == "unix" => Ok(FileFormat::Unix),
+        OptionValue::Value(value) if value == "dos" => Ok(FileFormat::Dos),
+        OptionValue::Value(value) => Err(format!("Invalid value for {}: {}", name, value)),
+        _ => Err(format!("Option {} needs a value", name)),
+    }
+}
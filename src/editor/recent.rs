@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::xdg_dir;
+
+// ╭──────────────────────────────────────╮
+// │ Recent Files Constants               │
+// ╰──────────────────────────────────────╯
+
+pub const MAX_RECENT_FILES: usize = 100;
+
+// ╭──────────────────────────────────────╮
+// │ Recent Files Structs                 │
+// ╰──────────────────────────────────────╯
+
+// The files that were opened or written, newest first. The list is kept in the data directory so
+// it's there the next time the editor starts.
+#[derive(Debug, Default, Eq, PartialEq, Hash, Clone)]
+pub struct RecentFiles {
+    pub files: Vec<PathBuf>,
+    pub store: Option<PathBuf>,
+}
+
+impl RecentFiles {
+    // Loads the list from '$XDG_DATA_HOME/oxide/recent', or '~/.local/share/oxide/recent'.
+    pub fn load() -> Self {
+        xdg_dir("XDG_DATA_HOME", ".local/share")
+            .map(|dir| RecentFiles::load_from(dir.join("oxide").join("recent")))
+            .unwrap_or_default()
+    }
+
+    // Loads the list from the file it's kept in. A list that can't be read starts out empty.
+    pub fn load_from(store: PathBuf) -> Self {
+        let files = fs::read_to_string(&store)
+            .map(|content| content.lines().map(PathBuf::from).collect())
+            .unwrap_or_default();
+
+        RecentFiles {
+            files,
+            store: Some(store),
+        }
+    }
+
+    // Moves the file to the front of the list, and drops the oldest files past the limit.
+    pub fn add(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        self.files.retain(|file| *file != path);
+        self.files.insert(0, path);
+        self.files.truncate(MAX_RECENT_FILES);
+
+        self.save();
+    }
+
+    // Returns the files that still exist. The ones that don't are removed from the list.
+    pub fn existing(&mut self) -> Vec<PathBuf> {
+        let len = self.files.len();
+        self.files.retain(|file| file.is_file());
+
+        if self.files.len() != len {
+            self.save();
+        }
+
+        self.files.clone()
+    }
+
+    // Writes the list to its file. Failing to do so only means the list is lost on exit, so the
+    // error is ignored.
+    fn save(&self) {
+        if let Some(store) = &self.store {
+            let content: Vec<String> = self
+                .files
+                .iter()
+                .map(|file| file.to_string_lossy().into_owned())
+                .collect();
+
+            if let Some(dir) = store.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+
+            let _ = fs::write(store, content.join("\n"));
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::buffer::Buffer;
+use crate::utils::xdg_dir;
+
+// ╭──────────────────────────────────────╮
+// │ Recovery Structs                     │
+// ╰──────────────────────────────────────╯
+
+// The changes rescued from a buffer, with the title and the file of the buffer they came from.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Recovery {
+    pub title: String,
+    pub path: Option<PathBuf>,
+    pub content: Vec<String>,
+}
+
+// ╭──────────────────────────────────────╮
+// │ Recovery Functions                   │
+// ╰──────────────────────────────────────╯
+
+// Writes the content of the modified buffers to the recovery directory, so the changes aren't lost
+// when the editor crashes. The files are called '<name>.<timestamp>', and start with the title and
+// the path of the buffer on the first two lines, so they can be recovered to where they belong.
+// Returns the written files.
+pub fn save_recovery(dir: &Path, buffers: &[Buffer]) -> io::Result<Vec<PathBuf>> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let mut saved = Vec::new();
+
+    for buffer in buffers.iter().filter(|buffer| buffer.is_modified()) {
+        fs::create_dir_all(dir)?;
+
+        // Buffers of files with the same name get a number to tell them apart
+        let mut file = dir.join(format!("{}.{}", buffer.title, timestamp));
+        let mut number = 1;
+
+        while file.exists() {
+            file = dir.join(format!("{}.{}.{}", buffer.title, timestamp, number));
+            number += 1;
+        }
+
+        let path = buffer
+            .path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+
+        fs::write(
+            &file,
+            format!("{}\n{}\n{}", buffer.title, path, buffer.content.join("\n")),
+        )?;
+        saved.push(file);
+    }
+
+    Ok(saved)
+}
+
+// Reads the rescued changes back from a recovery file.
+pub fn read_recovery(file: &Path) -> io::Result<Recovery> {
+    let text = fs::read_to_string(file)?;
+    let mut lines = text.split('\n');
+
+    let (Some(title), Some(path)) = (lines.next(), lines.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not a recovery file",
+        ));
+    };
+
+    Ok(Recovery {
+        title: title.to_string(),
+        path: (!path.is_empty()).then(|| PathBuf::from(path)),
+        content: lines.map(|line| line.to_string()).collect(),
+    })
+}
+
+// Lists the files in the recovery directory, oldest first.
+pub fn recovery_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<(SystemTime, PathBuf)> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && !is_hidden(path))
+            .map(|path| (modified_time(&path), path))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    files.sort();
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+// Lists the recovery files that haven't been reported yet, and marks them as reported, so the
+// files that are left alone aren't reported again every time the editor starts. The time of the
+// last report is kept as the modification time of a hidden file in the directory.
+pub fn unreported_recovery_files(dir: &Path) -> Vec<PathBuf> {
+    let marker = dir.join(".reported");
+    let reported = fs::metadata(&marker)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    let files: Vec<PathBuf> = recovery_files(dir)
+        .into_iter()
+        .filter(|file| reported.is_none_or(|reported| modified_time(file) > reported))
+        .collect();
+
+    if !files.is_empty() {
+        let _ = fs::write(&marker, "");
+    }
+
+    files
+}
+
+// Returns '$XDG_CACHE_HOME/oxide/recovery', or '~/.cache/oxide/recovery'.
+pub fn recovery_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache").map(|dir| dir.join("oxide").join("recovery"))
+}
+
+// ╭──────────────────────────────────────╮
+// │ Recovery Helpers                     │
+// ╰──────────────────────────────────────╯
+
+fn modified_time(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(UNIX_EPOCH)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
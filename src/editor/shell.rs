@@ -0,0 +1,71 @@
+use std::process::Stdio;
+
+use tokio::process::Command;
+
+// ╭──────────────────────────────────────╮
+// │ Shell Structs                        │
+// ╰──────────────────────────────────────╯
+
+// The output of a finished shell command, split into lines.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ShellOutput {
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+    pub status: Option<i32>,
+    pub success: bool,
+}
+
+impl ShellOutput {
+    // Describes a failed command or anything it wrote to stderr, so it can be shown as a message.
+    pub fn error_message(&self) -> Option<String> {
+        let stderr = self
+            .stderr
+            .iter()
+            .rev()
+            .find(|line| !line.trim().is_empty());
+
+        match (self.success, self.status, stderr) {
+            (true, _, None) => None,
+            (true, _, Some(line)) => Some(line.clone()),
+            (false, Some(status), Some(line)) => {
+                Some(format!("Command exited with status {}: {}", status, line))
+            }
+            (false, Some(status), None) => Some(format!("Command exited with status {}", status)),
+            (false, None, Some(line)) => Some(format!("Command was terminated: {}", line)),
+            (false, None, None) => Some("Command was terminated".to_string()),
+        }
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Shell Functions                      │
+// ╰──────────────────────────────────────╯
+
+// Runs the command with 'sh -c', so quoting and escaping are left to the shell. The command gets
+// no input, since the terminal belongs to the editor while it runs.
+pub async fn run_shell(command: &str) -> std::io::Result<ShellOutput> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+
+    Ok(ShellOutput {
+        stdout: split_lines(&output.stdout),
+        stderr: split_lines(&output.stderr),
+        status: output.status.code(),
+        success: output.status.success(),
+    })
+}
+
+// ╭──────────────────────────────────────╮
+// │ Shell Helpers                        │
+// ╰──────────────────────────────────────╯
+
+fn split_lines(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+
+use crate::buffer::{Buffer, Error};
+use crate::editor::ShellOutput;
+
+// ╭──────────────────────────────────────╮
+// │ Task Enums                           │
+// ╰──────────────────────────────────────╯
+
+// The result of a background task, which is sent back to the editor once the task is done.
+#[derive(Debug)]
+pub enum TaskResult {
+    FileLoaded {
+        path: PathBuf,
+        result: Result<Box<Buffer>, Error>,
+    },
+    // The output of a shell command, which is read into the buffer at the index for ':r !', and
+    // shown in the shell output buffer otherwise.
+    ShellFinished {
+        command: String,
+        result: std::io::Result<ShellOutput>,
+        read_into: Option<usize>,
+    },
+}
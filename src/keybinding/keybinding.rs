@@ -3,7 +3,8 @@ use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::buffer::{BufferKind, MinibufferKind, Mode};
+use crate::buffer::{BufferKind, MinibufferKind, Mode, VisualKind};
+use crate::utils::expand_home;
 
 // ╭──────────────────────────────────────╮
 // │ Keybinding Enums                     │
@@ -19,24 +20,92 @@ pub enum Action {
     InsertTab,
     NewLine(NewLineDirection),
     DeleteChar,
+    DeleteForward,
+    DeleteWordBefore,
+    DeleteToLineStart,
     DeleteLine,
     MoveCursor(i32, i32),
+    MoveDisplayRow { down: bool },
+    WordForward,
+    WordBackward,
+    EndOfWord,
+    BigWordForward,
+    BigWordBackward,
+    StartOfLine,
+    EndOfLine,
+    FirstNonBlank,
+    GotoLine(usize),
+    FindChar(CharSearch),
+    RepeatFindChar { reverse: bool },
+    MatchingBracket,
+    GotoPercentage(usize),
+    Undo,
+    Redo,
+    Yank,
+    YankLine,
+    Paste { before: bool },
+    Delete(Motion),
+    Change(Motion),
+    ChangeLine,
+    ReplaceChar(char),
+    JoinLines { spaces: bool },
+    MoveLines { down: bool },
+    Indent,
+    Dedent,
+    ChangeCase(CaseChange),
+    ToggleComment,
+    Reflow,
+    Substitute(Substitution),
+    Error(String),
+    SetOption(String, OptionValue),
+    ShowOptions,
+    VisualInsert { append: bool },
+    ToggleMacroRecording,
+    RecordMacro(char),
+    PlayMacro(char),
+    ScrollHalfPage { down: bool },
+    ScrollPage { down: bool },
+    ScrollLine { down: bool },
+    AlignViewport(ViewportPosition),
+    MoveToViewport(ViewportPosition),
+    AwaitChar(PendingAction),
     TopOfBuffer,
     EndOfBuffer,
-    Quit,
-    WriteBuffer,
+    Quit { force: bool },
+    QuitAll { force: bool },
+    KillBuffer { force: bool },
+    CycleBuffer { next: bool },
+    WriteBuffer { path: Option<PathBuf>, force: bool },
+    WriteAll,
     ExecuteCommand,
+    Search(Search),
+    RepeatSearch { reverse: bool },
+    SearchWordUnderCursor(SearchDirection),
+    ClearSearchHighlight,
+    SetMark(char),
+    GotoMark(char),
     OpenFile(PathBuf),
+    ReloadBuffer { force: bool },
+    ReadFile(PathBuf),
+    Recover,
+    ShellCommand(String),
+    ReadShellCommand(String),
     Minibuffer(MinibufferKind),
     OpenBuffer(usize),
-    Append,
+    ShowBufferList,
+    BufferListOpen,
+    BufferListMark { delete: bool },
+    BufferListExecute,
+    CycleCandidate { next: bool },
+    AcceptCompletion,
+    Repeat(usize, Box<Action>),
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum ModeParams {
     Normal,
     Insert { insert_direction: InsertDirection },
-    Visual,
+    Visual { kind: VisualKind },
     Command { prefix: String, input: String },
     Minibuffer,
 }
@@ -56,10 +125,161 @@ pub enum InsertDirection {
     End,
 }
 
+// Defines which way a search goes
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+impl SearchDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            SearchDirection::Forward => SearchDirection::Backward,
+            SearchDirection::Backward => SearchDirection::Forward,
+        }
+    }
+}
+
+// Defines where the cursor line can be placed in the viewport
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ViewportPosition {
+    Center,
+    Top,
+    Bottom,
+}
+
+// Defines the motions an operator like 'd' can act on
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Motion {
+    WordForward,
+    WordEnd,
+    WordBackward,
+    LineEnd,
+    Chars(usize),
+    Inside(TextObject),
+    Around(TextObject),
+}
+
+// Defines the text objects an operator can act on, like the word or the parentheses around the
+// cursor
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TextObject {
+    Word,
+    Quote(char),
+    Pair(char, char),
+}
+
+// Defines how the case of characters can be changed
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CaseChange {
+    Toggle,
+    Lower,
+    Upper,
+}
+
+// Defines the lines a command applies to. Line numbers start at 1 and the end is inclusive
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LineRange {
+    Current,
+    All,
+    Lines(usize, usize),
+}
+
+// Defines the value given to an option with ':set'. 'name' turns an option on, 'noname' turns it
+// off and 'name=value' sets it to the value
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum OptionValue {
+    On,
+    Off,
+    Value(String),
+}
+
+// Defines the actions that take the next keypress as an argument instead of looking it up as a
+// keybinding
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PendingAction {
+    FindChar {
+        direction: SearchDirection,
+        till: bool,
+    },
+    SetMark,
+    GotoMark,
+    ReplaceChar,
+    RecordMacro,
+    PlayMacro,
+}
+
+impl PendingAction {
+    // Turns the pending action into an executable action using the pressed character
+    pub fn with_char(self, character: char) -> Action {
+        match self {
+            PendingAction::FindChar { direction, till } => Action::FindChar(CharSearch {
+                character,
+                direction,
+                till,
+            }),
+            PendingAction::SetMark => Action::SetMark(character),
+            PendingAction::GotoMark => Action::GotoMark(character),
+            PendingAction::ReplaceChar => Action::ReplaceChar(character),
+            PendingAction::RecordMacro => Action::RecordMacro(character),
+            PendingAction::PlayMacro => Action::PlayMacro(character),
+        }
+    }
+}
+
 // ╭──────────────────────────────────────╮
 // │ Keybinding Structs                   │
 // ╰──────────────────────────────────────╯
 
+// A replacement of a pattern on a range of lines. Global substitutions replace every occurrence on
+// a line instead of only the first one
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Substitution {
+    pub pattern: String,
+    pub replacement: String,
+    pub global: bool,
+    pub range: LineRange,
+}
+
+// A search for a character on the current line. Till searches stop right before the character
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct CharSearch {
+    pub character: char,
+    pub direction: SearchDirection,
+    pub till: bool,
+}
+
+impl CharSearch {
+    // Returns the same search going the opposite way
+    pub fn reversed(self) -> Self {
+        CharSearch {
+            direction: self.direction.reversed(),
+            ..self
+        }
+    }
+}
+
+// A search for a pattern in the whole buffer. Whole word searches skip the occurrences that are
+// part of a larger word
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Search {
+    pub pattern: String,
+    pub direction: SearchDirection,
+    pub whole_word: bool,
+}
+
+impl Search {
+    // Returns the same search going the opposite way
+    pub fn reversed(&self) -> Self {
+        Search {
+            pattern: self.pattern.clone(),
+            direction: self.direction.reversed(),
+            whole_word: self.whole_word,
+        }
+    }
+}
+
 // Stores the users currently pressed keys
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct KeySequence {
@@ -78,6 +298,10 @@ pub struct KeybindingManager {
     mode_bindings: HashMap<Mode, HashMap<Option<BufferKind>, HashMap<KeySequence, Action>>>,
     current_buffer_kind: BufferKind,
     current_sequence: KeySequence,
+    pending_action: Option<PendingAction>,
+    // The keys of the action waiting for its argument
+    pending_sequence: KeySequence,
+    count: Option<usize>,
 }
 
 // Handles parsing the command line commands
@@ -89,6 +313,9 @@ impl KeybindingManager {
             mode_bindings: HashMap::new(),
             current_buffer_kind: BufferKind::Normal,
             current_sequence: KeySequence { keys: Vec::new() },
+            pending_action: None,
+            pending_sequence: KeySequence { keys: Vec::new() },
+            count: None,
         };
 
         manager.setup_default_bindings();
@@ -126,6 +353,251 @@ impl KeybindingManager {
             Action::MoveCursor(1, 0),
         );
 
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('e'), KeyModifiers::NONE),
+            ],
+            Action::MoveDisplayRow { down: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('i'), KeyModifiers::NONE),
+            ],
+            Action::MoveDisplayRow { down: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('w'), KeyModifiers::NONE)],
+            Action::WordForward,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('b'), KeyModifiers::NONE)],
+            Action::WordBackward,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('E'), KeyModifiers::SHIFT)],
+            Action::EndOfWord,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('W'), KeyModifiers::SHIFT)],
+            Action::BigWordForward,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('B'), KeyModifiers::SHIFT)],
+            Action::BigWordBackward,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('0'), KeyModifiers::NONE)],
+            Action::StartOfLine,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('$'), KeyModifiers::NONE)],
+            Action::EndOfLine,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('^'), KeyModifiers::NONE)],
+            Action::FirstNonBlank,
+        );
+
+        // 'f' and 'F' open new lines in this layout, so the character search uses 'k' and 'K'
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('k'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Forward,
+                till: false,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('K'), KeyModifiers::SHIFT)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Backward,
+                till: false,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('t'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Forward,
+                till: true,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('T'), KeyModifiers::SHIFT)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Backward,
+                till: true,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char(';'), KeyModifiers::NONE)],
+            Action::RepeatFindChar { reverse: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char(','), KeyModifiers::NONE)],
+            Action::RepeatFindChar { reverse: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('%'), KeyModifiers::NONE)],
+            Action::MatchingBracket,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('d'), KeyModifiers::CONTROL)],
+            Action::ScrollHalfPage { down: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('u'), KeyModifiers::CONTROL)],
+            Action::ScrollHalfPage { down: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('f'), KeyModifiers::CONTROL)],
+            Action::ScrollPage { down: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('b'), KeyModifiers::CONTROL)],
+            Action::ScrollPage { down: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('u'), KeyModifiers::NONE)],
+            Action::Undo,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('r'), KeyModifiers::CONTROL)],
+            Action::Redo,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('e'), KeyModifiers::CONTROL)],
+            Action::ScrollLine { down: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('y'), KeyModifiers::CONTROL)],
+            Action::ScrollLine { down: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+            ],
+            Action::AlignViewport(ViewportPosition::Center),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+                (KeyCode::Char('t'), KeyModifiers::NONE),
+            ],
+            Action::AlignViewport(ViewportPosition::Top),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+            ],
+            Action::AlignViewport(ViewportPosition::Bottom),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('H'), KeyModifiers::SHIFT)],
+            Action::MoveToViewport(ViewportPosition::Top),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('M'), KeyModifiers::SHIFT)],
+            Action::MoveToViewport(ViewportPosition::Center),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('L'), KeyModifiers::SHIFT)],
+            Action::MoveToViewport(ViewportPosition::Bottom),
+        );
+
         self.add_binding(
             Mode::Normal,
             Some(BufferKind::Normal),
@@ -145,173 +617,1040 @@ impl KeybindingManager {
         );
 
         self.add_binding(
-            Mode::Normal,
-            Some(BufferKind::Normal),
-            vec![(KeyCode::Char('a'), KeyModifiers::NONE)],
-            Action::SwitchMode(ModeParams::Insert {
-                insert_direction: InsertDirection::After,
-            }),
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('a'), KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Insert {
+                insert_direction: InsertDirection::After,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('A'), KeyModifiers::SHIFT)],
+            Action::SwitchMode(ModeParams::Insert {
+                insert_direction: InsertDirection::End,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('x'), KeyModifiers::NONE)],
+            Action::DeleteChar,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Delete, KeyModifiers::NONE)],
+            Action::DeleteChar,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('d'), KeyModifiers::NONE),
+                (KeyCode::Char('d'), KeyModifiers::NONE),
+            ],
+            Action::DeleteLine,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('d'), KeyModifiers::NONE),
+                (KeyCode::Char('w'), KeyModifiers::NONE),
+            ],
+            Action::Delete(Motion::WordForward),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('d'), KeyModifiers::NONE),
+                (KeyCode::Char('e'), KeyModifiers::NONE),
+            ],
+            Action::Delete(Motion::WordEnd),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('d'), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+            ],
+            Action::Delete(Motion::WordBackward),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+                (KeyCode::Char('w'), KeyModifiers::NONE),
+            ],
+            Action::Change(Motion::WordForward),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+                (KeyCode::Char('e'), KeyModifiers::NONE),
+            ],
+            Action::Change(Motion::WordEnd),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+            ],
+            Action::Change(Motion::WordBackward),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+                (KeyCode::Char('$'), KeyModifiers::NONE),
+            ],
+            Action::Change(Motion::LineEnd),
+        );
+
+        // Text objects, like 'diw' or 'ca('
+        let text_objects = [
+            (KeyCode::Char('w'), KeyModifiers::NONE, TextObject::Word),
+            (
+                KeyCode::Char('"'),
+                KeyModifiers::NONE,
+                TextObject::Quote('"'),
+            ),
+            (
+                KeyCode::Char('\''),
+                KeyModifiers::NONE,
+                TextObject::Quote('\''),
+            ),
+            (
+                KeyCode::Char('`'),
+                KeyModifiers::NONE,
+                TextObject::Quote('`'),
+            ),
+            (
+                KeyCode::Char('('),
+                KeyModifiers::NONE,
+                TextObject::Pair('(', ')'),
+            ),
+            (
+                KeyCode::Char(')'),
+                KeyModifiers::NONE,
+                TextObject::Pair('(', ')'),
+            ),
+            (
+                KeyCode::Char('b'),
+                KeyModifiers::NONE,
+                TextObject::Pair('(', ')'),
+            ),
+            (
+                KeyCode::Char('['),
+                KeyModifiers::NONE,
+                TextObject::Pair('[', ']'),
+            ),
+            (
+                KeyCode::Char(']'),
+                KeyModifiers::NONE,
+                TextObject::Pair('[', ']'),
+            ),
+            (
+                KeyCode::Char('{'),
+                KeyModifiers::NONE,
+                TextObject::Pair('{', '}'),
+            ),
+            (
+                KeyCode::Char('}'),
+                KeyModifiers::NONE,
+                TextObject::Pair('{', '}'),
+            ),
+            (
+                KeyCode::Char('B'),
+                KeyModifiers::SHIFT,
+                TextObject::Pair('{', '}'),
+            ),
+            (
+                KeyCode::Char('<'),
+                KeyModifiers::NONE,
+                TextObject::Pair('<', '>'),
+            ),
+            (
+                KeyCode::Char('>'),
+                KeyModifiers::NONE,
+                TextObject::Pair('<', '>'),
+            ),
+        ];
+
+        for (key, modifiers, object) in text_objects {
+            for (operator, action) in [
+                ('d', Action::Delete as fn(Motion) -> Action),
+                ('c', Action::Change),
+            ] {
+                self.add_binding(
+                    Mode::Normal,
+                    Some(BufferKind::Normal),
+                    vec![
+                        (KeyCode::Char(operator), KeyModifiers::NONE),
+                        (KeyCode::Char('i'), KeyModifiers::NONE),
+                        (key, modifiers),
+                    ],
+                    action(Motion::Inside(object)),
+                );
+
+                self.add_binding(
+                    Mode::Normal,
+                    Some(BufferKind::Normal),
+                    vec![
+                        (KeyCode::Char(operator), KeyModifiers::NONE),
+                        (KeyCode::Char('a'), KeyModifiers::NONE),
+                        (key, modifiers),
+                    ],
+                    action(Motion::Around(object)),
+                );
+            }
+        }
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+            ],
+            Action::ChangeLine,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('r'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::ReplaceChar),
+        );
+
+        // 'q' either starts recording, which takes the register as an argument, or stops it, so
+        // the editor decides what it does.
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('q'), KeyModifiers::NONE)],
+            Action::ToggleMacroRecording,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('@'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::PlayMacro),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('~'), KeyModifiers::NONE)],
+            Action::ChangeCase(CaseChange::Toggle),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('>'), KeyModifiers::NONE),
+                (KeyCode::Char('>'), KeyModifiers::NONE),
+            ],
+            Action::Indent,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('<'), KeyModifiers::NONE),
+                (KeyCode::Char('<'), KeyModifiers::NONE),
+            ],
+            Action::Dedent,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Up, KeyModifiers::ALT)],
+            Action::MoveLines { down: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Down, KeyModifiers::ALT)],
+            Action::MoveLines { down: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('J'), KeyModifiers::SHIFT)],
+            Action::JoinLines { spaces: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('J'), KeyModifiers::SHIFT),
+            ],
+            Action::JoinLines { spaces: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('D'), KeyModifiers::SHIFT)],
+            Action::Delete(Motion::LineEnd),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('C'), KeyModifiers::SHIFT)],
+            Action::Change(Motion::LineEnd),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('y'), KeyModifiers::NONE),
+                (KeyCode::Char('y'), KeyModifiers::NONE),
+            ],
+            Action::YankLine,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('p'), KeyModifiers::NONE)],
+            Action::Paste { before: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('P'), KeyModifiers::SHIFT)],
+            Action::Paste { before: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+            ],
+            Action::TopOfBuffer,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('G'), KeyModifiers::SHIFT)],
+            Action::EndOfBuffer,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('f'), KeyModifiers::NONE)],
+            Action::NewLine(NewLineDirection::Under),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('F'), KeyModifiers::SHIFT)],
+            Action::NewLine(NewLineDirection::Over),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char(':'), KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Command {
+                prefix: ":".to_string(),
+                input: String::new(),
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('/'), KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Command {
+                prefix: "/".to_string(),
+                input: String::new(),
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('?'), KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Command {
+                prefix: "?".to_string(),
+                input: String::new(),
+            }),
+        );
+
+        // 'n' moves left in this layout, so searches are repeated with 'h' and 'l'
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('h'), KeyModifiers::NONE)],
+            Action::RepeatSearch { reverse: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('l'), KeyModifiers::NONE)],
+            Action::RepeatSearch { reverse: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('*'), KeyModifiers::NONE)],
+            Action::SearchWordUnderCursor(SearchDirection::Forward),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('#'), KeyModifiers::NONE)],
+            Action::SearchWordUnderCursor(SearchDirection::Backward),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('m'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::SetMark),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('`'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::GotoMark),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('v'), KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Visual {
+                kind: VisualKind::Char,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('V'), KeyModifiers::SHIFT)],
+            Action::SwitchMode(ModeParams::Visual {
+                kind: VisualKind::Line,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Char('v'), KeyModifiers::CONTROL)],
+            Action::SwitchMode(ModeParams::Visual {
+                kind: VisualKind::Block,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('v'), KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Visual {
+                kind: VisualKind::Char,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('V'), KeyModifiers::SHIFT)],
+            Action::SwitchMode(ModeParams::Visual {
+                kind: VisualKind::Line,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('v'), KeyModifiers::CONTROL)],
+            Action::SwitchMode(ModeParams::Visual {
+                kind: VisualKind::Block,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('f'), KeyModifiers::NONE),
+                (KeyCode::Char('f'), KeyModifiers::NONE),
+            ],
+            Action::Minibuffer(MinibufferKind::File(PathBuf::new())),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('f'), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+            ],
+            Action::Minibuffer(MinibufferKind::Buffer(Vec::new())),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('f'), KeyModifiers::NONE),
+                (KeyCode::Char('r'), KeyModifiers::NONE),
+            ],
+            Action::Minibuffer(MinibufferKind::Recent(Vec::new())),
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+                (KeyCode::Char('k'), KeyModifiers::NONE),
+            ],
+            Action::KillBuffer { force: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+                (KeyCode::Char('n'), KeyModifiers::NONE),
+            ],
+            Action::CycleBuffer { next: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+                (KeyCode::Char('p'), KeyModifiers::NONE),
+            ],
+            Action::CycleBuffer { next: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
+                (KeyCode::Char('l'), KeyModifiers::NONE),
+            ],
+            Action::ShowBufferList,
+        );
+
+        // The buffer list opens the buffer under the cursor, and kills the buffers marked with 'd'
+        // on 'x' like the buffer menu in Emacs
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::BufferList),
+            vec![(KeyCode::Enter, KeyModifiers::NONE)],
+            Action::BufferListOpen,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::BufferList),
+            vec![(KeyCode::Char('d'), KeyModifiers::NONE)],
+            Action::BufferListMark { delete: true },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::BufferList),
+            vec![(KeyCode::Char('u'), KeyModifiers::NONE)],
+            Action::BufferListMark { delete: false },
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::BufferList),
+            vec![(KeyCode::Char('x'), KeyModifiers::NONE)],
+            Action::BufferListExecute,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('/'), KeyModifiers::NONE),
+            ],
+            Action::ToggleComment,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char(' '), KeyModifiers::NONE),
+                (KeyCode::Char('/'), KeyModifiers::NONE),
+            ],
+            Action::ToggleComment,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('c'), KeyModifiers::NONE),
+            ],
+            Action::ToggleComment,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('q'), KeyModifiers::NONE),
+            ],
+            Action::Reflow,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('q'), KeyModifiers::NONE),
+            ],
+            Action::Reflow,
+        );
+
+        self.add_binding(
+            Mode::Normal,
+            None,
+            vec![(KeyCode::Esc, KeyModifiers::NONE)],
+            Action::Escape,
+        );
+
+        // INSERT MODE
+        self.add_binding(
+            Mode::Insert,
+            None,
+            vec![(KeyCode::Esc, KeyModifiers::NONE)],
+            Action::SwitchMode(ModeParams::Normal),
+        );
+
+        self.add_binding(
+            Mode::Insert,
+            None,
+            vec![(KeyCode::Enter, KeyModifiers::NONE)],
+            Action::NewLine(NewLineDirection::Under),
+        );
+
+        // VISUAL MODE
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('n'), KeyModifiers::NONE)],
+            Action::MoveCursor(-1, 0),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('e'), KeyModifiers::NONE)],
+            Action::MoveCursor(0, 1),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('i'), KeyModifiers::NONE)],
+            Action::MoveCursor(0, -1),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('o'), KeyModifiers::NONE)],
+            Action::MoveCursor(1, 0),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('e'), KeyModifiers::NONE),
+            ],
+            Action::MoveDisplayRow { down: true },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('i'), KeyModifiers::NONE),
+            ],
+            Action::MoveDisplayRow { down: false },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('w'), KeyModifiers::NONE)],
+            Action::WordForward,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('b'), KeyModifiers::NONE)],
+            Action::WordBackward,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('E'), KeyModifiers::SHIFT)],
+            Action::EndOfWord,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('W'), KeyModifiers::SHIFT)],
+            Action::BigWordForward,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('B'), KeyModifiers::SHIFT)],
+            Action::BigWordBackward,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('0'), KeyModifiers::NONE)],
+            Action::StartOfLine,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('$'), KeyModifiers::NONE)],
+            Action::EndOfLine,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('^'), KeyModifiers::NONE)],
+            Action::FirstNonBlank,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('k'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Forward,
+                till: false,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('K'), KeyModifiers::SHIFT)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Backward,
+                till: false,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('t'), KeyModifiers::NONE)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Forward,
+                till: true,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('T'), KeyModifiers::SHIFT)],
+            Action::AwaitChar(PendingAction::FindChar {
+                direction: SearchDirection::Backward,
+                till: true,
+            }),
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char(';'), KeyModifiers::NONE)],
+            Action::RepeatFindChar { reverse: false },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char(','), KeyModifiers::NONE)],
+            Action::RepeatFindChar { reverse: true },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('%'), KeyModifiers::NONE)],
+            Action::MatchingBracket,
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('d'), KeyModifiers::CONTROL)],
+            Action::ScrollHalfPage { down: true },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('u'), KeyModifiers::CONTROL)],
+            Action::ScrollHalfPage { down: false },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('f'), KeyModifiers::CONTROL)],
+            Action::ScrollPage { down: true },
         );
 
         self.add_binding(
-            Mode::Normal,
-            Some(BufferKind::Normal),
-            vec![(KeyCode::Char('A'), KeyModifiers::SHIFT)],
-            Action::SwitchMode(ModeParams::Insert {
-                insert_direction: InsertDirection::End,
-            }),
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('b'), KeyModifiers::CONTROL)],
+            Action::ScrollPage { down: false },
         );
 
         self.add_binding(
-            Mode::Normal,
-            Some(BufferKind::Normal),
-            vec![(KeyCode::Char('x'), KeyModifiers::NONE)],
-            Action::DeleteChar,
+            Mode::Visual,
+            None,
+            vec![
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+            ],
+            Action::AlignViewport(ViewportPosition::Center),
         );
 
         self.add_binding(
-            Mode::Normal,
-            Some(BufferKind::Normal),
+            Mode::Visual,
+            None,
             vec![
-                (KeyCode::Char('d'), KeyModifiers::NONE),
-                (KeyCode::Char('d'), KeyModifiers::NONE),
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+                (KeyCode::Char('t'), KeyModifiers::NONE),
             ],
-            Action::DeleteLine,
+            Action::AlignViewport(ViewportPosition::Top),
         );
 
         self.add_binding(
-            Mode::Normal,
+            Mode::Visual,
             None,
             vec![
-                (KeyCode::Char('g'), KeyModifiers::NONE),
-                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('z'), KeyModifiers::NONE),
+                (KeyCode::Char('b'), KeyModifiers::NONE),
             ],
-            Action::TopOfBuffer,
+            Action::AlignViewport(ViewportPosition::Bottom),
         );
 
         self.add_binding(
-            Mode::Normal,
+            Mode::Visual,
             None,
-            vec![(KeyCode::Char('G'), KeyModifiers::SHIFT)],
-            Action::EndOfBuffer,
+            vec![(KeyCode::Char('H'), KeyModifiers::SHIFT)],
+            Action::MoveToViewport(ViewportPosition::Top),
         );
 
         self.add_binding(
-            Mode::Normal,
-            Some(BufferKind::Normal),
-            vec![(KeyCode::Char('f'), KeyModifiers::NONE)],
-            Action::NewLine(NewLineDirection::Under),
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('M'), KeyModifiers::SHIFT)],
+            Action::MoveToViewport(ViewportPosition::Center),
         );
 
         self.add_binding(
-            Mode::Normal,
-            Some(BufferKind::Normal),
-            vec![(KeyCode::Char('F'), KeyModifiers::SHIFT)],
-            Action::NewLine(NewLineDirection::Over),
+            Mode::Visual,
+            None,
+            vec![(KeyCode::Char('L'), KeyModifiers::SHIFT)],
+            Action::MoveToViewport(ViewportPosition::Bottom),
         );
 
         self.add_binding(
-            Mode::Normal,
+            Mode::Visual,
             None,
-            vec![(KeyCode::Char(':'), KeyModifiers::NONE)],
-            Action::SwitchMode(ModeParams::Command {
-                prefix: ":".to_string(),
-                input: String::new(),
-            }),
+            vec![(KeyCode::Char('d'), KeyModifiers::NONE)],
+            Action::DeleteChar,
         );
 
         self.add_binding(
-            Mode::Normal,
+            Mode::Visual,
             None,
-            vec![(KeyCode::Char('v'), KeyModifiers::NONE)],
-            Action::SwitchMode(ModeParams::Visual),
+            vec![(KeyCode::Char('x'), KeyModifiers::NONE)],
+            Action::DeleteChar,
         );
 
         self.add_binding(
-            Mode::Normal,
+            Mode::Visual,
             None,
-            vec![
-                (KeyCode::Char(' '), KeyModifiers::NONE),
-                (KeyCode::Char('f'), KeyModifiers::NONE),
-                (KeyCode::Char('f'), KeyModifiers::NONE),
-            ],
-            Action::Minibuffer(MinibufferKind::File(PathBuf::new())),
+            vec![(KeyCode::Delete, KeyModifiers::NONE)],
+            Action::DeleteChar,
         );
 
         self.add_binding(
-            Mode::Normal,
+            Mode::Visual,
             None,
-            vec![
-                (KeyCode::Char(' '), KeyModifiers::NONE),
-                (KeyCode::Char('f'), KeyModifiers::NONE),
-                (KeyCode::Char('b'), KeyModifiers::NONE),
-            ],
-            Action::Minibuffer(MinibufferKind::Buffer(Vec::new())),
+            vec![(KeyCode::Char('y'), KeyModifiers::NONE)],
+            Action::Yank,
         );
 
         self.add_binding(
-            Mode::Normal,
-            None,
-            vec![(KeyCode::Esc, KeyModifiers::NONE)],
-            Action::Escape,
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('p'), KeyModifiers::NONE)],
+            Action::Paste { before: false },
         );
 
-        // INSERT MODE
         self.add_binding(
-            Mode::Insert,
-            None,
-            vec![(KeyCode::Esc, KeyModifiers::NONE)],
-            Action::SwitchMode(ModeParams::Normal),
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('I'), KeyModifiers::SHIFT)],
+            Action::VisualInsert { append: false },
         );
 
         self.add_binding(
-            Mode::Insert,
-            None,
-            vec![(KeyCode::Enter, KeyModifiers::NONE)],
-            Action::NewLine(NewLineDirection::Under),
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('A'), KeyModifiers::SHIFT)],
+            Action::VisualInsert { append: true },
         );
 
-        // VISUAL MODE
         self.add_binding(
             Mode::Visual,
-            None,
-            vec![(KeyCode::Char('n'), KeyModifiers::NONE)],
-            Action::MoveCursor(-1, 0),
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('~'), KeyModifiers::NONE)],
+            Action::ChangeCase(CaseChange::Toggle),
         );
 
         self.add_binding(
             Mode::Visual,
-            None,
-            vec![(KeyCode::Char('e'), KeyModifiers::NONE)],
-            Action::MoveCursor(0, 1),
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('u'), KeyModifiers::NONE)],
+            Action::ChangeCase(CaseChange::Lower),
         );
 
         self.add_binding(
             Mode::Visual,
-            None,
-            vec![(KeyCode::Char('i'), KeyModifiers::NONE)],
-            Action::MoveCursor(0, -1),
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('U'), KeyModifiers::SHIFT)],
+            Action::ChangeCase(CaseChange::Upper),
         );
 
         self.add_binding(
             Mode::Visual,
-            None,
-            vec![(KeyCode::Char('o'), KeyModifiers::NONE)],
-            Action::MoveCursor(1, 0),
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('>'), KeyModifiers::NONE)],
+            Action::Indent,
         );
 
         self.add_binding(
             Mode::Visual,
-            None,
-            vec![(KeyCode::Char('d'), KeyModifiers::NONE)],
-            Action::DeleteChar,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('<'), KeyModifiers::NONE)],
+            Action::Dedent,
         );
 
         self.add_binding(
             Mode::Visual,
-            None,
-            vec![(KeyCode::Char('x'), KeyModifiers::NONE)],
-            Action::DeleteChar,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Up, KeyModifiers::ALT)],
+            Action::MoveLines { down: false },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Down, KeyModifiers::ALT)],
+            Action::MoveLines { down: true },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![(KeyCode::Char('J'), KeyModifiers::SHIFT)],
+            Action::JoinLines { spaces: true },
+        );
+
+        self.add_binding(
+            Mode::Visual,
+            Some(BufferKind::Normal),
+            vec![
+                (KeyCode::Char('g'), KeyModifiers::NONE),
+                (KeyCode::Char('J'), KeyModifiers::SHIFT),
+            ],
+            Action::JoinLines { spaces: false },
         );
 
         self.add_binding(
@@ -396,11 +1735,19 @@ impl KeybindingManager {
             Action::ExecuteCommand,
         );
 
+        // Ctrl-n and Ctrl-p move through the candidates like in other fuzzy finders
         self.add_binding(
             Mode::Minibuffer,
             None,
             vec![(KeyCode::Char('n'), KeyModifiers::CONTROL)],
-            Action::MoveCursor(-1, 0),
+            Action::MoveCursor(0, 1),
+        );
+
+        self.add_binding(
+            Mode::Minibuffer,
+            None,
+            vec![(KeyCode::Char('p'), KeyModifiers::CONTROL)],
+            Action::MoveCursor(0, -1),
         );
 
         self.add_binding(
@@ -435,8 +1782,85 @@ impl KeybindingManager {
             Mode::Minibuffer,
             None,
             vec![(KeyCode::Tab, KeyModifiers::NONE)],
-            Action::Append,
+            Action::CycleCandidate { next: true },
+        );
+
+        self.add_binding(
+            Mode::Minibuffer,
+            None,
+            vec![(KeyCode::BackTab, KeyModifiers::SHIFT)],
+            Action::CycleCandidate { next: false },
+        );
+
+        self.add_binding(
+            Mode::Minibuffer,
+            None,
+            vec![(KeyCode::Char('y'), KeyModifiers::CONTROL)],
+            Action::AcceptCompletion,
         );
+
+        // NAVIGATION KEYS
+        for mode in [Mode::Normal, Mode::Visual] {
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::Left, KeyModifiers::NONE)],
+                Action::MoveCursor(-1, 0),
+            );
+
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::Down, KeyModifiers::NONE)],
+                Action::MoveCursor(0, 1),
+            );
+
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::Up, KeyModifiers::NONE)],
+                Action::MoveCursor(0, -1),
+            );
+
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::Right, KeyModifiers::NONE)],
+                Action::MoveCursor(1, 0),
+            );
+        }
+
+        for mode in [Mode::Normal, Mode::Insert, Mode::Visual] {
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::PageUp, KeyModifiers::NONE)],
+                Action::ScrollPage { down: false },
+            );
+
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::PageDown, KeyModifiers::NONE)],
+                Action::ScrollPage { down: true },
+            );
+        }
+
+        for mode in [Mode::Normal, Mode::Insert, Mode::Visual, Mode::Command] {
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::Home, KeyModifiers::NONE)],
+                Action::StartOfLine,
+            );
+
+            self.add_binding(
+                mode,
+                None,
+                vec![(KeyCode::End, KeyModifiers::NONE)],
+                Action::EndOfLine,
+            );
+        }
     }
 
     // Adds keybindings to the keybinding manager
@@ -467,6 +1891,38 @@ impl KeybindingManager {
     // Checks the mode of the keybinding and the current buffer mode and redirects to the
     // appropriate parser
     pub fn handle_input(&mut self, current_mode: &Mode, key_event: KeyEvent) -> Option<Action> {
+        // If an action is waiting for an argument, the keypress is consumed as that argument.
+        // Enter is passed on as a newline, and any other key that isn't a character, like escape,
+        // cancels the action.
+        if let Some(pending_action) = self.pending_action.take() {
+            self.pending_sequence.keys.clear();
+
+            return match key_event.code {
+                KeyCode::Char(c) => Some(self.with_count(pending_action.with_char(c))),
+                KeyCode::Enter => Some(self.with_count(pending_action.with_char('\n'))),
+                _ => {
+                    self.count = None;
+                    None
+                }
+            };
+        }
+
+        // Digits typed before a keybinding in normal and visual mode are collected into a count.
+        // A leading zero is left to its own keybinding.
+        if matches!(current_mode, Mode::Normal | Mode::Visual)
+            && self.current_sequence.keys.is_empty()
+            && key_event.modifiers == KeyModifiers::NONE
+        {
+            if let KeyCode::Char(c @ '0'..='9') = key_event.code {
+                if c != '0' || self.count.is_some() {
+                    let count = self.count.unwrap_or(0).saturating_mul(10);
+                    self.count = Some(count.saturating_add(c as usize - '0' as usize));
+
+                    return None;
+                }
+            }
+        }
+
         let key_binding = Keybinding {
             key: key_event.code,
             modifiers: key_event.modifiers,
@@ -485,9 +1941,14 @@ impl KeybindingManager {
         // If the keybinding exists, it's sent back
         // If not it checks if the current key sequence exists in any existing
         // keybinding and stores the current key sequence
-        if action.is_some() {
+        if let Some(Action::AwaitChar(pending_action)) = action {
+            self.pending_sequence.keys = std::mem::take(&mut self.current_sequence.keys);
+            self.pending_action = Some(pending_action);
+
+            None
+        } else if let Some(action) = action {
             self.current_sequence.keys.clear();
-            action
+            Some(self.with_count(action))
         } else {
             if let Some(mode_bindings) = self.mode_bindings.get(current_mode) {
                 let mut sequence_matches = false;
@@ -508,9 +1969,10 @@ impl KeybindingManager {
                     }
                 }
 
-                // If not, it clears the current key sequence
+                // If not, it clears the current key sequence and count
                 if !sequence_matches {
                     self.current_sequence.keys.clear();
+                    self.count = None;
                 }
             }
 
@@ -554,10 +2016,35 @@ impl KeybindingManager {
                 key: KeyCode::Backspace,
                 ..
             } => Some(Action::DeleteChar),
+            Keybinding {
+                key: KeyCode::Delete,
+                ..
+            } => Some(Action::DeleteForward),
+            Keybinding {
+                key: KeyCode::Char('w'),
+                modifiers: KeyModifiers::CONTROL,
+            } => Some(Action::DeleteWordBefore),
+            Keybinding {
+                key: KeyCode::Char('u'),
+                modifiers: KeyModifiers::CONTROL,
+            } => Some(Action::DeleteToLineStart),
             Keybinding {
                 key: KeyCode::Enter,
                 ..
             } => Some(Action::NewLine(NewLineDirection::Under)),
+            Keybinding {
+                key: KeyCode::Left, ..
+            } => Some(Action::MoveCursor(-1, 0)),
+            Keybinding {
+                key: KeyCode::Down, ..
+            } => Some(Action::MoveCursor(0, 1)),
+            Keybinding {
+                key: KeyCode::Up, ..
+            } => Some(Action::MoveCursor(0, -1)),
+            Keybinding {
+                key: KeyCode::Right,
+                ..
+            } => Some(Action::MoveCursor(1, 0)),
             _ => {
                 if let Some(mode_bindings) = self.mode_bindings.get(current_mode) {
                     if let Some(action) = mode_bindings
@@ -672,18 +2159,274 @@ impl KeybindingManager {
         }
     }
 
+    // Makes the next keypress the argument of the given action
+    pub fn await_char(&mut self, pending_action: PendingAction) {
+        self.pending_action = Some(pending_action);
+    }
+
+    // Wraps the action in a repeat if a count was typed before it. Escaping throws the count away.
+    fn with_count(&mut self, action: Action) -> Action {
+        match self.count.take() {
+            Some(count) if action != Action::Escape => Action::Repeat(count, Box::new(action)),
+            _ => action,
+        }
+    }
+
     pub fn set_buffer_kind(&mut self, kind: BufferKind) {
         self.current_buffer_kind = kind;
     }
+
+    // Forgets the keys typed so far, along with the count and an action waiting for its argument.
+    pub fn reset(&mut self) {
+        self.current_sequence.keys.clear();
+        self.pending_action = None;
+        self.pending_sequence.keys.clear();
+        self.count = None;
+    }
+
+    // Returns the count and the keys typed so far for a keybinding that isn't complete yet, like
+    // '3d' or '␣f'. It's empty when nothing is pending.
+    pub fn pending_keys(&self) -> String {
+        let mut pending = self
+            .count
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+
+        for key in self
+            .pending_sequence
+            .keys
+            .iter()
+            .chain(&self.current_sequence.keys)
+        {
+            pending.push_str(&key_name(key));
+        }
+
+        pending
+    }
 }
 
 impl CommandParser {
     pub fn parse(input: &str) -> Vec<Action> {
-        match input {
-            "wq" => vec![Action::WriteBuffer, Action::Quit],
-            "w" => vec![Action::WriteBuffer],
-            "q" => vec![Action::Quit],
-            _ => Vec::new(),
+        // The command is separated from its argument by the first space
+        let (command, argument) = match input.split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (input, ""),
+        };
+
+        // Shell commands are passed on untouched, so the shell does the quoting and escaping
+        if let Some(shell_command) = input.strip_prefix('!') {
+            return match shell_command.trim_start() {
+                "" => vec![Action::Error("Argument required".to_string())],
+                shell_command => vec![Action::ShellCommand(shell_command.to_string())],
+            };
+        }
+
+        match (command, argument) {
+            ("wq", "") => vec![
+                Action::WriteBuffer {
+                    path: None,
+                    force: false,
+                },
+                Action::Quit { force: false },
+            ],
+            ("wq", path) => vec![
+                Action::WriteBuffer {
+                    path: Some(expand_path(path)),
+                    force: false,
+                },
+                Action::Quit { force: false },
+            ],
+            ("w" | "w!", "") => vec![Action::WriteBuffer {
+                path: None,
+                force: command == "w!",
+            }],
+            ("w" | "w!", path) => vec![Action::WriteBuffer {
+                path: Some(expand_path(path)),
+                force: command == "w!",
+            }],
+            ("q", "") => vec![Action::Quit { force: false }],
+            ("q!", "") => vec![Action::Quit { force: true }],
+            ("wa", "") => vec![Action::WriteAll],
+            ("qa", "") => vec![Action::QuitAll { force: false }],
+            ("qa!", "") => vec![Action::QuitAll { force: true }],
+            ("wqa", "") => vec![Action::WriteAll, Action::QuitAll { force: false }],
+            ("bd" | "bdelete", "") => vec![Action::KillBuffer { force: false }],
+            ("bd!" | "bdelete!", "") => vec![Action::KillBuffer { force: true }],
+            ("bn" | "bnext", "") => vec![Action::CycleBuffer { next: true }],
+            ("bp" | "bprevious", "") => vec![Action::CycleBuffer { next: false }],
+            ("noh" | "nohlsearch", "") => vec![Action::ClearSearchHighlight],
+            ("e" | "edit", "") => vec![Action::ReloadBuffer { force: false }],
+            ("e!" | "edit!", "") => vec![Action::ReloadBuffer { force: true }],
+            // Opening a file that's already open switches to its buffer, so the bang reads it again
+            ("e!" | "edit!", path) => vec![
+                Action::OpenFile(expand_path(path)),
+                Action::ReloadBuffer { force: true },
+            ],
+            ("e" | "edit", path) => vec![Action::OpenFile(expand_path(path))],
+            ("r" | "read", shell_command) if shell_command.starts_with('!') => {
+                match shell_command[1..].trim_start() {
+                    "" => vec![Action::Error("Argument required".to_string())],
+                    shell_command => vec![Action::ReadShellCommand(shell_command.to_string())],
+                }
+            }
+            ("r" | "read", "") => vec![Action::Error("Argument required".to_string())],
+            ("r" | "read", path) => vec![Action::ReadFile(expand_path(path))],
+            ("recover", "") => vec![Action::Recover],
+            ("set", "") => vec![Action::ShowOptions],
+            ("set", options) => options.split_whitespace().map(parse_option).collect(),
+            _ => {
+                if let Some(substitution) = parse_substitution(input) {
+                    return match substitution {
+                        Ok(substitution) => vec![Action::Substitute(substitution)],
+                        Err(message) => vec![Action::Error(message)],
+                    };
+                }
+
+                match input.parse::<usize>() {
+                    Ok(line) => vec![Action::GotoLine(line)],
+                    // An empty command line does nothing, anything else is a typo worth showing
+                    Err(_) if input.trim().is_empty() => Vec::new(),
+                    Err(_) => vec![Action::Error(format!("Not an editor command: {}", input))],
+                }
+            }
+        }
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Keybinding Helpers                   │
+// ╰──────────────────────────────────────╯
+
+// Names a key as briefly as possible, like 'd', '␣' or 'C-w'.
+fn key_name(key: &Keybinding) -> String {
+    let name = match key.key {
+        KeyCode::Char(' ') => "␣".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "⏎".to_string(),
+        KeyCode::Tab => "⇥".to_string(),
+        KeyCode::Backspace => "⌫".to_string(),
+        KeyCode::Esc => "⎋".to_string(),
+        code => format!("<{}>", code),
+    };
+
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("C-{}", name)
+    } else if key.modifiers.contains(KeyModifiers::ALT) {
+        format!("M-{}", name)
+    } else {
+        name
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Command Parsing Helpers              │
+// ╰──────────────────────────────────────╯
+
+// Expands a leading '~' to the home directory and resolves relative paths against the current
+// working directory.
+fn expand_path(path: &str) -> PathBuf {
+    let path = expand_home(path);
+
+    match std::env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path,
+    }
+}
+
+// Parses a single ':set' argument into the option name and value.
+fn parse_option(option: &str) -> Action {
+    match option.split_once('=') {
+        Some((name, value)) => {
+            Action::SetOption(name.to_string(), OptionValue::Value(value.to_string()))
+        }
+        None => Action::SetOption(option.to_string(), OptionValue::On),
+    }
+}
+
+// Parses a substitute command like '%s/old/new/g'. Returns None if the input isn't a substitute
+// command, and an error message if it's malformed.
+fn parse_substitution(input: &str) -> Option<std::result::Result<Substitution, String>> {
+    let (range, rest) = parse_line_range(input);
+    let rest = rest.strip_prefix('s')?.strip_prefix('/')?;
+
+    let parts = split_unescaped(rest, '/');
+
+    if parts.len() > 3 {
+        return Some(Err("Trailing characters after the flags".to_string()));
+    }
+
+    let pattern = parts[0].clone();
+    let replacement = parts.get(1).cloned().unwrap_or_default();
+    let flags = parts.get(2).cloned().unwrap_or_default();
+
+    if pattern.is_empty() {
+        return Some(Err("Empty pattern".to_string()));
+    }
+
+    if let Some(flag) = flags.chars().find(|flag| *flag != 'g') {
+        return Some(Err(format!("Invalid flag: {}", flag)));
+    }
+
+    Some(Ok(Substitution {
+        pattern,
+        replacement,
+        global: flags.contains('g'),
+        range,
+    }))
+}
+
+// Parses the range at the start of a command: '%' for the whole buffer, a line number, or two line
+// numbers separated by a comma. Returns the range and the rest of the command.
+fn parse_line_range(input: &str) -> (LineRange, &str) {
+    if let Some(rest) = input.strip_prefix('%') {
+        return (LineRange::All, rest);
+    }
+
+    let parse_number = |input: &str| -> Option<(usize, usize)> {
+        let digits = input.chars().take_while(|c| c.is_ascii_digit()).count();
+        input[..digits].parse().ok().map(|number| (number, digits))
+    };
+
+    let Some((start, start_len)) = parse_number(input) else {
+        return (LineRange::Current, input);
+    };
+
+    let rest = &input[start_len..];
+
+    match rest
+        .strip_prefix(',')
+        .and_then(|after_comma| parse_number(after_comma).map(|end| (end, after_comma)))
+    {
+        Some(((end, end_len), after_comma)) => (
+            LineRange::Lines(start.min(end), start.max(end)),
+            &after_comma[end_len..],
+        ),
+        None => (LineRange::Lines(start, start), rest),
+    }
+}
+
+// Splits the input on the separator, except where it's escaped with a backslash. Escaped
+// separators and backslashes lose their backslash.
+fn split_unescaped(input: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == separator || next == '\\' => {
+                    parts.last_mut().unwrap().push(next)
+                }
+                Some(next) => {
+                    parts.last_mut().unwrap().push(c);
+                    parts.last_mut().unwrap().push(next);
+                }
+                None => parts.last_mut().unwrap().push(c),
+            },
+            c if c == separator => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
         }
     }
+
+    parts
 }
@@ -1,8 +1,14 @@
-use ratatui::crossterm::event::{self, Event};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
 
-use oxide::editor::Editor;
+use ratatui::crossterm::cursor::SetCursorStyle;
+use ratatui::crossterm::event::{self, DisableFocusChange, EnableFocusChange, Event};
+use ratatui::crossterm::execute;
+
+use oxide::editor::{recovery_dir, save_recovery, unreported_recovery_files, Editor, Message};
 use oxide::keybinding::{KeybindingManager, ModeParams};
 use oxide::utils::logging::setup_logger;
+use oxide::utils::parse_args;
 
 // ╭──────────────────────────────────────╮
 // │ Types                                │
@@ -17,44 +23,128 @@ type Result<T> = std::result::Result<T, oxide::OxideError>;
 fn main() -> Result<()> {
     setup_logger()?;
 
-    // Initializes core components
+    // Initializes core components. The terminal is restored by a panic hook if the editor panics.
     let terminal = ratatui::init();
+    execute!(std::io::stdout(), EnableFocusChange)?;
     let mut editor = Editor::new(terminal);
     let tokio_runtime = tokio::runtime::Runtime::new()?;
     let mut keybinding_manager = KeybindingManager::new();
 
-    // Main loop
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run(&mut editor, &mut keybinding_manager, &tokio_runtime)
+    }));
+
+    // Restores the terminal to the correct mode, with the user's cursor shape
+    execute!(
+        std::io::stdout(),
+        DisableFocusChange,
+        SetCursorStyle::DefaultUserShape
+    )?;
+    ratatui::restore();
+
+    // The unsaved changes are rescued when the editor crashes
+    match result {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => {
+            rescue(&editor);
+            Err(e)
+        }
+        Err(panic) => {
+            rescue(&editor);
+            panic::resume_unwind(panic)
+        }
+    }
+}
+
+fn run(
+    editor: &mut Editor,
+    keybinding_manager: &mut KeybindingManager,
+    tokio_runtime: &tokio::runtime::Runtime,
+) -> Result<()> {
+    // Opens the files given on the command line
+    let files = parse_args(std::env::args().skip(1));
+    editor.open_args(files, keybinding_manager, tokio_runtime)?;
+
+    // Tells the user about the changes rescued from a crash. The files are only reported once,
+    // and not while another message is shown, so they're reported the next time instead.
+    if let (None, Some(dir)) = (&editor.message, recovery_dir()) {
+        let recovered = unreported_recovery_files(&dir).len();
+
+        if recovered > 0 {
+            editor.message = Some(Message::info(format!(
+                "Found {} recovery file{}, open them with :recover",
+                recovered,
+                if recovered == 1 { "" } else { "s" }
+            )));
+        }
+    }
+
+    // Main loop. The screen is only redrawn when something changed, which is after an event, a
+    // finished background task or an auto-save.
+    let mut redraw = true;
+
     while editor.is_running {
+        // Swaps in the files that were loaded in the background, and shows the output of the
+        // finished shell commands
+        redraw |= editor.handle_tasks(keybinding_manager);
+
         // Renders the buffer
-        editor.render()?;
+        if redraw {
+            editor.render(keybinding_manager)?;
+        }
+
+        // Waits a while for a keypress, so auto-save and background tasks run while idle
+        if !event::poll(Duration::from_millis(100))? {
+            redraw = editor.auto_save(tokio_runtime, false);
+            continue;
+        }
+
+        redraw = true;
 
         // Checks the user keypresses
         match event::read() {
             Ok(event) => match event {
                 Event::Key(key_event) => {
-                    let buffer_mode = &editor.buffer_manager.get_active_buffer()?.mode;
-                    let input_result = keybinding_manager.handle_input(buffer_mode, key_event);
-
-                    if let Some(action) = input_result {
-                        match editor.parse_action(action, &keybinding_manager, &tokio_runtime) {
-                            Ok(_) => {}
-                            Err(_) => {
-                                editor
-                                    .buffer_manager
-                                    .get_active_buffer_mut()?
-                                    .switch_mode(ModeParams::Normal);
-                            }
+                    // Messages are only shown until the next keypress
+                    editor.message = None;
+
+                    match editor.handle_key(key_event, keybinding_manager, tokio_runtime) {
+                        Ok(_) => {}
+                        Err(_) => {
+                            editor
+                                .buffer_manager
+                                .get_active_buffer_mut()?
+                                .switch_mode(ModeParams::Normal);
                         }
                     }
                 }
+                Event::FocusLost => {
+                    editor.auto_save(tokio_runtime, true);
+                }
                 _ => {}
             },
             Err(_) => {}
         }
     }
 
-    // Restores the terminal to the correct mode
-    ratatui::restore();
-
     Ok(())
 }
+
+// Writes the modified buffers to the recovery directory, and tells the user where they went.
+fn rescue(editor: &Editor) {
+    let Some(dir) = recovery_dir() else {
+        return;
+    };
+
+    match save_recovery(&dir, &editor.buffer_manager.buffers) {
+        Ok(files) if !files.is_empty() => {
+            eprintln!("Unsaved changes were saved to:");
+
+            for file in files {
+                eprintln!("  {}", file.display());
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to save the unsaved changes: {}", e),
+    }
+}
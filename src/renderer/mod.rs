@@ -4,6 +4,8 @@
 
 pub mod error;
 pub mod renderer;
+pub mod theme;
 
 pub use error::*;
 pub use renderer::*;
+pub use theme::*;
@@ -1,42 +1,30 @@
-use ratatui::layout::{Constraint, Layout};
+use ratatui::crossterm::cursor::SetCursorStyle;
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Layout, Position};
 use ratatui::prelude::*;
-use ratatui::style::{Color, Style};
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Clear, Paragraph};
 use ratatui::Terminal;
 
 use std::io::Stdout;
 
-use crate::buffer::{Buffer, Minibuffer, Mode};
-use crate::renderer::Error;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-// ╭──────────────────────────────────────╮
-// │ Renderer Consts                      │
-// ╰──────────────────────────────────────╯
-
-const CURSOR_STYLE: Style = Style::new()
-    .fg(Color::Black)
-    .bg(Color::Rgb(0xf2, 0xd5, 0xcf));
-const HIGHLIGHT_STYLE: Style = Style::new().bg(Color::Rgb(0x45, 0x47, 0x5a));
-const ELEMENT_STYLE: Style = Style::new().bg(Color::Rgb(0x11, 0x11, 0x1b));
-const PREFIX_STYLE: Style = Style::new().fg(Color::Black).bg(Color::Blue);
-const _ERROR_STYLE: Style = Style::new().fg(Color::Red);
+use crate::buffer::{
+    grapheme_width, search_matches, Buffer, Candidate, Cursor, Minibuffer, Mode, VisualKind,
+};
+use crate::editor::{Message, MessageKind, Options};
+use crate::keybinding::Search;
+use crate::renderer::{Error, Theme};
 
 // ╭──────────────────────────────────────╮
 // │ Renderer Macros                      │
 // ╰──────────────────────────────────────╯
 
 macro_rules! format_line {
-    ($line:expr) => {{
-        let mut spans: Vec<Span> = Vec::new();
-        let line_str = format!("{} ", $line);
-
-        spans.push(Span::raw(line_str));
-
-        Line::from(spans)
-    }};
-
-    ($line:expr, $x_pos:expr) => {{
+    ($line:expr, $x_pos:expr, $theme:expr) => {{
         let mut spans: Vec<Span> = Vec::new();
         let line_str = format!("{} ", $line);
 
@@ -44,7 +32,7 @@ macro_rules! format_line {
             let span = Span::from(c.to_string());
 
             if num == $x_pos {
-                spans.push(span.style(CURSOR_STYLE));
+                spans.push(span.style($theme.cursor));
             } else {
                 spans.push(span);
             }
@@ -53,114 +41,205 @@ macro_rules! format_line {
         Line::from(spans)
     }};
 
-    ($line:expr, $line_num:expr, $y_pos:expr) => {{
+    ($line:expr, $line_num:expr, $y_pos:expr, $theme:expr) => {{
         let formatted_line = Line::from(String::from($line));
 
         if $y_pos == $line_num {
-            formatted_line.style(CURSOR_STYLE)
+            formatted_line.style($theme.cursor)
         } else {
             formatted_line
         }
     }};
 
-    ($line:expr, $line_num:expr, $start:expr, $cursor:expr) => {{
+    // Formats a line of the buffer with the cursor, the visual selection and the search matches.
+    // The matches are character ranges, flagged if they're the match under the cursor. Only the
+    // columns within the width are formatted, unless there's no width because the lines wrap.
+    // With 'list' on, tabs, trailing spaces and non-breaking spaces are made visible.
+    (
+        $line:expr,
+        $line_num:expr,
+        $buffer:expr,
+        $matches:expr,
+        $width:expr,
+        $list:expr,
+        $theme:expr
+    ) => {{
+        let selection = match ($buffer.mode, $buffer.visual_start) {
+            (Mode::Visual, Some(start)) => Some((start, $buffer.cursor)),
+            _ => None,
+        };
+        let in_selection = selection
+            .map(|(start, cursor)| {
+                $line_num >= start.y.min(cursor.y) && $line_num <= start.y.max(cursor.y)
+            })
+            .unwrap_or(false);
+
+        let tab_width = $buffer.options.tab_width;
+        let left = $buffer.viewport.left;
+        let right = $width
+            .map(|width: usize| left + width)
+            .unwrap_or(usize::MAX);
+
+        // Characters with the same style are joined into one span. The line is drawn grapheme by
+        // grapheme, so combining marks and emoji sequences stay with the character they belong
+        // to, and wide characters take up two columns. Tabs are expanded up to the next tab stop,
+        // and the columns left of the viewport are skipped while the characters keep their
+        // numbers. The space after the line is where the cursor goes at its end.
         let mut spans: Vec<Span> = Vec::new();
-        let line_str = format!("{} ", $line);
+        let mut run = String::new();
+        let mut run_style = Style::new();
+        let mut column = 0;
+        let mut num = 0;
+        let cursor_index = ($buffer.cursor.y == $line_num).then(|| $buffer.cursor_index());
+
+        // The spaces from this byte to the end of the line are trailing. They're found from the
+        // end, so the work doesn't grow with the length of the line.
+        let trailing_start = if $list {
+            $line.trim_end_matches(' ').len()
+        } else {
+            $line.len()
+        };
+
+        let graphemes = $line
+            .grapheme_indices(true)
+            .chain(std::iter::once(($line.len(), " ")));
+
+        for (index, grapheme) in graphemes {
+            if column >= right {
+                break;
+            }
+
+            let start = column;
+            column += grapheme_width(grapheme, column, tab_width);
 
-        // Sets the top and bottom cursor
-        let (top, bottom) =
-            if $start.y < $cursor.y || ($start.y == $cursor.y && $start.x <= $cursor.x) {
-                ($start, $cursor)
+            // The characters are numbered by their first character
+            let grapheme_num = num;
+            num += grapheme.chars().count();
+
+            if column <= left {
+                continue;
+            }
+
+            let num = grapheme_num;
+            let is_cursor = cursor_index == Some(index);
+            let style = if selection
+                .map(|(start, cursor)| match $buffer.visual_kind {
+                    VisualKind::Char => is_selected(start, cursor, $line_num, num),
+                    VisualKind::Line => in_selection,
+                    VisualKind::Block => {
+                        in_selection && num >= start.x.min(cursor.x) && num <= start.x.max(cursor.x)
+                    }
+                })
+                .unwrap_or(false)
+            {
+                $theme.selection
             } else {
-                ($cursor, $start)
+                match $matches
+                    .iter()
+                    .find(|(start, end, _)| num >= *start && num < *end)
+                {
+                    Some((_, _, true)) => $theme.current_match,
+                    Some((_, _, false)) => $theme.search_match,
+                    None => Style::new(),
+                }
             };
 
-        for (num, c) in line_str.chars().enumerate() {
-            let span = Span::from(c.to_string());
+            // A character is drawn in its first cell, and takes up the rest of its cells itself
+            // unless it's a tab, or cut off by the left side of the viewport. The cells are marked
+            // in the whitespace style when they show whitespace.
+            let (first, rest, marked) = match grapheme {
+                "\t" if $list => ("»", "·", true),
+                "\t" => (" ", " ", false),
+                " " if $list && index >= trailing_start && index < $line.len() => ("·", "·", true),
+                "\u{a0}" if $list => ("␣", "␣", true),
+                grapheme if grapheme.width() == 0 => ("\u{fffd}", " ", false),
+                grapheme => (grapheme, " ", false),
+            };
+            let cell_style = if marked {
+                style.patch($theme.whitespace)
+            } else {
+                style
+            };
 
-            let is_selected: bool = if $line_num >= top.y && $line_num <= bottom.y {
-                if $line_num == top.y && $line_num == bottom.y {
-                    // Single line selection
-                    let (left, right) = if $start.x <= $cursor.x {
-                        ($start.x, $cursor.x)
-                    } else {
-                        ($cursor.x, $start.x)
-                    };
-                    num >= left && num <= right
-                } else if $line_num == top.y {
-                    // First line of multi-line selection
-                    num >= top.x
-                } else if $line_num == bottom.y {
-                    // Last line of multi-line selection
-                    num <= bottom.x
+            // The cursor only covers the first cell of a tab
+            for cell in start.max(left)..column {
+                let cell_str = if cell == start {
+                    first
+                } else if grapheme == "\t" || start < left {
+                    rest
+                } else {
+                    continue;
+                };
+                let cell_style = if cell == start && is_cursor {
+                    $theme.cursor
                 } else {
-                    // Middle lines of multi-line selection
-                    true
+                    cell_style
+                };
+
+                if cell_style != run_style && !run.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut run), run_style));
                 }
-            } else {
-                false
-            };
 
-            if $cursor.y == $line_num && $cursor.x == num {
-                spans.push(span.style(CURSOR_STYLE));
-            } else if is_selected {
-                spans.push(span.style(HIGHLIGHT_STYLE));
-            } else {
-                spans.push(span);
+                run_style = cell_style;
+                run.push_str(cell_str);
             }
         }
 
+        if !run.is_empty() {
+            spans.push(Span::styled(run, run_style));
+        }
+
         Line::from(spans)
     }};
 }
 
-macro_rules! format_statusline {
-    ($mode: expr, $title: expr, $lines: expr, $cursor: expr) => {{
-        let left_line = Line::from(format!(" {} ", $mode)).left_aligned();
-        let middle_line = Line::from($title).centered();
-
-        let line_delta = format!("[{}/{}] :{}", $cursor.y + 1, $lines + 1, $cursor.x);
-        let line_percentage = if $lines > 0 {
-            (($cursor.y as f32 / $lines as f32) * 100_f32).floor()
+macro_rules! format_minibuffer {
+    // The prefix shows the selected candidate and the amount of candidates, as in 'Find File
+    // [3/48]:', and is styled differently when nothing matches the input.
+    (
+        $prefix:expr,
+        $input:expr,
+        $matched:expr,
+        $x_pos:expr,
+        $selected:expr,
+        $total:expr,
+        $theme:expr
+    ) => {{
+        let prefix = format!(
+            "{} [{}/{}]:",
+            $prefix.trim_end_matches(':'),
+            ($selected + 1).min($total),
+            $total
+        );
+        let (prefix_style, input_style) = if $total == 0 {
+            ($theme.no_match, $theme.error)
         } else {
-            100.0
+            ($theme.prefix, Style::new())
         };
 
-        let right_line =
-            Line::from(format!(" {}  {}% ", line_delta, line_percentage)).right_aligned();
-
-        (left_line, middle_line, right_line)
-    }};
-}
-
-macro_rules! format_minibuffer {
-    ($prefix:expr, $input:expr, $matched:expr, $x_pos:expr) => {{
-        let mut input: Vec<Span> = vec![Span::from($prefix).style(PREFIX_STYLE)];
+        let mut input: Vec<Span> = vec![Span::from(prefix).style(prefix_style)];
         let mut spans: Vec<Span> = Vec::new();
         let mut matched: Vec<Span> = Vec::new();
         let line_str = format!("{} ", $input);
 
-        for (num, entry) in $matched.iter().enumerate() {
+        for entry in $matched.iter() {
             let span = if entry != "/" {
                 Span::from(format!("{}/", entry))
             } else {
                 Span::from(entry.to_string())
             };
 
-            if num == $x_pos {
-                matched.push(span.style(CURSOR_STYLE));
-            } else {
-                matched.push(span);
-            }
+            // The matched part of the path is made of directories, styled like the candidates
+            matched.push(span.style($theme.directory));
         }
 
         for (num, c) in line_str.chars().enumerate() {
             let span = Span::from(c.to_string());
 
-            if $x_pos >= $matched.len() && num == $x_pos - $matched.len() {
-                spans.push(span.style(CURSOR_STYLE));
+            if num == $x_pos {
+                spans.push(span.style($theme.cursor));
             } else {
-                spans.push(span);
+                spans.push(span.style(input_style));
             }
         }
 
@@ -185,8 +264,9 @@ type Result<'a, T> = std::result::Result<T, Error>;
 pub struct Renderer {
     terminal: Terminal<CrosstermBackend<Stdout>>,
     vertical: Layout,
-    horizontal: Layout,
     statusline: Layout,
+    // The mode the terminal cursor was last shaped for
+    cursor_mode: Option<Mode>,
 }
 
 impl Renderer {
@@ -197,12 +277,6 @@ impl Renderer {
             Constraint::Length(1),
         ]);
 
-        let horizontal = Layout::horizontal([
-            Constraint::Length(3),
-            Constraint::Length(1),
-            Constraint::Fill(1),
-        ]);
-
         let statusline = Layout::horizontal([
             Constraint::Fill(1),
             Constraint::Fill(1),
@@ -212,18 +286,34 @@ impl Renderer {
         Renderer {
             terminal,
             vertical,
-            horizontal,
             statusline,
+            cursor_mode: None,
         }
     }
 
-    pub fn render(&mut self, buffer: &Buffer, minibuffer_opt: Option<&Minibuffer>) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        buffer: &Buffer,
+        options: &Options,
+        minibuffer_opt: Option<&Minibuffer>,
+        message_opt: Option<&Message>,
+        search_opt: Option<&Search>,
+        pending_keys: &str,
+        theme: &Theme,
+    ) -> Result<()> {
         self.terminal.draw(|frame| {
             let mut lines: Vec<Line> = Vec::new();
             let mut nums: Vec<Line> = Vec::new();
             let [buffer_vert, statusline_area, command_line_area] =
                 self.vertical.areas(frame.area());
-            let [num_line, _, buffer_area] = self.horizontal.areas(buffer_vert);
+            let gutter_width = gutter_width(options);
+            let [num_line, _, buffer_area] = Layout::horizontal([
+                Constraint::Length(gutter_width as u16),
+                Constraint::Length(gutter_width.min(1) as u16),
+                Constraint::Fill(1),
+            ])
+            .areas(buffer_vert);
             let [left_status_area, middle_status_area, right_status_area] =
                 self.statusline.areas(statusline_area);
 
@@ -234,41 +324,125 @@ impl Renderer {
                 .skip(buffer.viewport.top)
                 .take(buffer.viewport.bottom() - buffer.viewport.top);
 
+            // Wrapped lines are formatted in full, since all of their columns are shown.
+            let width = (!options.wrap).then_some(buffer_area.width as usize);
+
+            // The cursor's cell, which is found while the rows are laid out when the lines wrap
+            let mut cursor_position = (!options.wrap).then(|| {
+                (
+                    buffer.cursor_column().saturating_sub(buffer.viewport.left),
+                    buffer.cursor.y.saturating_sub(buffer.viewport.top),
+                )
+            });
+
             for (num, line) in visible_buffer_content {
-                match buffer.mode {
-                    Mode::Visual => {
-                        if let Some(start) = buffer.visual_start {
-                            lines.push(format_line!(line, num, start, buffer.cursor));
-                        }
-                    }
-                    _ => {
-                        if buffer.cursor.y == num {
-                            lines.push(format_line!(line, buffer.cursor.x));
-                        } else {
-                            lines.push(format_line!(line));
-                        }
-                    }
+                // The matches are only computed for the visible lines.
+                let matches: Vec<(usize, usize, bool)> = match search_opt {
+                    Some(search) => search_matches(line, search)
+                        .into_iter()
+                        .map(|(start, end)| {
+                            let start_x = line[..start].chars().count();
+                            let end_x = start_x + line[start..end].chars().count();
+                            let is_current = buffer.cursor.y == num && buffer.cursor.x == start_x;
+
+                            (start_x, end_x, is_current)
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                let formatted =
+                    format_buffer_line(line, num, buffer, &matches, width, options.list, theme);
+
+                // Relative numbers count the lines from the cursor line, which shows its own
+                // number if the absolute numbers are on as well.
+                let distance = num.abs_diff(buffer.cursor.y);
+
+                let number = if num == buffer.cursor.y {
+                    let current = if options.number { num + 1 } else { 0 };
+
+                    Line::from(format!("{:<3}", current)).style(theme.line_number)
+                } else if options.relative_number {
+                    Line::from(format!("{:>3}", distance))
+                } else {
+                    Line::from(format!("{:>3}", num + 1))
+                };
+
+                if !options.wrap {
+                    lines.push(formatted);
+                    nums.push(number);
+                    continue;
                 }
 
-                if num == buffer.cursor.y {
-                    nums.push(
-                        Line::from(format!("{:<3}", num + 1)).fg(Color::Rgb(0xf2, 0xd5, 0xcf)),
-                    );
+                // Wrapped lines take up more rows, which are left empty in the number column. The
+                // rows of the top line above the viewport's top row are skipped.
+                let rows = split_rows(
+                    formatted,
+                    &buffer.line_rows(num, buffer_area.width as usize),
+                );
+                let skip = if num == buffer.viewport.top {
+                    buffer.viewport.top_row
                 } else {
-                    nums.push(Line::from(format!("{:>3}", num + 1)));
+                    0
+                };
+
+                if num == buffer.cursor.y {
+                    let (row, column) = buffer.cursor_row(buffer_area.width as usize);
+                    cursor_position = Some((column, lines.len() + row - skip));
                 }
+
+                nums.extend(
+                    std::iter::once(number)
+                        .chain((1..rows.len()).map(|_| Line::from("")))
+                        .skip(skip),
+                );
+                lines.extend(rows.into_iter().skip(skip));
             }
 
             frame.render_widget(Paragraph::new(lines), buffer_area);
+
+            // The terminal cursor is placed on the cursor cell
+            if let Some((x, y)) = cursor_position {
+                frame.set_cursor_position(Position::new(
+                    buffer_area.x + x as u16,
+                    buffer_area.y + y as u16,
+                ));
+            }
             frame.render_widget(Paragraph::new(nums), num_line);
 
             if let Some(minibuffer) = minibuffer_opt {
-                let [_, minibuffer_area] = Layout::vertical([
+                // Only the candidates in the minibuffer viewport are shown
+                let visible_content: Vec<(usize, &Candidate)> = minibuffer
+                    .content
+                    .iter()
+                    .enumerate()
+                    .skip(minibuffer.viewport.top)
+                    .take(minibuffer.viewport.height)
+                    .collect();
+
+                let preview_height = minibuffer.preview.as_ref().map_or(0, Vec::len) as u16;
+
+                let [_, preview_area, minibuffer_area] = Layout::vertical([
                     Constraint::Fill(1),
-                    Constraint::Length(minibuffer.content.len() as u16 + 1),
+                    Constraint::Length(preview_height),
+                    Constraint::Length(visible_content.len() as u16 + 1),
                 ])
                 .areas(frame.area());
 
+                // The preview of the selected file sits above the candidates
+                if let Some(preview) = &minibuffer.preview {
+                    let preview_lines: Vec<Line> = preview
+                        .iter()
+                        .map(|line| Line::from(line.as_str()))
+                        .collect();
+
+                    frame.render_widget(Clear, preview_area);
+                    frame.render_widget(
+                        Paragraph::new(preview_lines).style(theme.preview),
+                        preview_area,
+                    );
+                }
+
                 let [mb_content_area, mb_input_area] =
                     Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
                         .areas(minibuffer_area);
@@ -277,38 +451,71 @@ impl Renderer {
                     Layout::horizontal([Constraint::Length(1), Constraint::Fill(1)])
                         .areas(mb_content_area);
 
-                let minibuffer_input = format_minibuffer!(
+                let mut minibuffer_input = format_minibuffer!(
                     minibuffer.prefix.clone(),
                     minibuffer.input,
                     minibuffer.matched_input,
-                    minibuffer.cursor.x
+                    minibuffer.cursor.x,
+                    minibuffer.cursor.y,
+                    minibuffer.content.len(),
+                    theme
                 );
                 let mut minibuffer_content: Vec<Line> = Vec::new();
 
-                for (num, entry) in minibuffer.content.iter().enumerate() {
-                    minibuffer_content.push(format_line!(entry, num, minibuffer.cursor.y));
+                // The input is at the end of the line, followed by a space for the cursor
+                let input_start = minibuffer_input.width() - minibuffer.input.width() - 1;
+                let input_x = minibuffer.input[..minibuffer.input_index()].width();
+
+                frame.set_cursor_position(Position::new(
+                    mb_input_area.x + (input_start + input_x) as u16,
+                    mb_input_area.y,
+                ));
+
+                if let Some(error) = &minibuffer.error {
+                    minibuffer_input
+                        .push_span(Span::from(format!(" {}", error)).style(theme.error));
+                }
+
+                // Directories end in '/' and have a color of their own
+                for (num, entry) in visible_content {
+                    let line = if entry.is_dir {
+                        format_line!(format!("{}/", entry.name), num, minibuffer.cursor.y, theme)
+                    } else {
+                        format_line!(entry.name.as_str(), num, minibuffer.cursor.y, theme)
+                    };
+
+                    if entry.is_dir && num != minibuffer.cursor.y {
+                        minibuffer_content.push(line.style(theme.directory));
+                    } else {
+                        minibuffer_content.push(line);
+                    }
                 }
 
                 frame.render_widget(Clear, mb_padding);
                 frame.render_widget(Clear, mb_content);
-                frame.render_widget(Block::new().style(ELEMENT_STYLE), mb_padding);
+                frame.render_widget(Block::new().style(theme.element), mb_padding);
                 frame.render_widget(
-                    Paragraph::new(minibuffer_content).style(ELEMENT_STYLE),
+                    Paragraph::new(minibuffer_content).style(theme.element),
                     mb_content,
                 );
                 frame.render_widget(Paragraph::new(minibuffer_input), mb_input_area);
             } else {
-                let (left_status, middle_status, right_status) = format_statusline!(
-                    buffer.mode,
-                    buffer.title.clone(),
-                    buffer.content.len() - 1,
-                    buffer.cursor
-                );
+                let [left_status, middle_status, right_status] =
+                    statusline_segments(buffer, pending_keys, statusline_area.width as usize);
 
-                frame.render_widget(Block::new().style(ELEMENT_STYLE), statusline_area);
-                frame.render_widget(Paragraph::new(left_status), left_status_area);
-                frame.render_widget(Paragraph::new(middle_status), middle_status_area);
-                frame.render_widget(Paragraph::new(right_status), right_status_area);
+                frame.render_widget(Block::new().style(theme.element), statusline_area);
+                frame.render_widget(
+                    Paragraph::new(Line::from(left_status).left_aligned()),
+                    left_status_area,
+                );
+                frame.render_widget(
+                    Paragraph::new(Line::from(middle_status).centered()),
+                    middle_status_area,
+                );
+                frame.render_widget(
+                    Paragraph::new(Line::from(right_status).right_aligned()),
+                    right_status_area,
+                );
 
                 if buffer.mode == Mode::Command {
                     let cmd_input = format_line!(
@@ -316,17 +523,51 @@ impl Renderer {
                             "{}{}",
                             buffer.command_line.prefix, buffer.command_line.input,
                         ),
-                        buffer.command_line.cursor.x
+                        buffer.command_line.cursor.x,
+                        theme
                     );
 
                     frame.render_widget(Paragraph::new(cmd_input), command_line_area);
+                    frame.set_cursor_position(Position::new(
+                        command_line_area.x + buffer.command_line.cursor.x as u16,
+                        command_line_area.y,
+                    ));
+                } else if let Some(message) = message_opt {
+                    let style = match message.kind {
+                        MessageKind::Info => Style::new(),
+                        MessageKind::Error => theme.error,
+                    };
+
+                    frame.render_widget(
+                        Paragraph::new(message.text.as_str()).style(style),
+                        command_line_area,
+                    );
                 }
             }
         })?;
 
+        // The cursor is a bar while typing text and a block otherwise
+        if self.cursor_mode != Some(buffer.mode) {
+            let cursor_style = match buffer.mode {
+                Mode::Insert | Mode::Command | Mode::Minibuffer => SetCursorStyle::SteadyBar,
+                Mode::Normal | Mode::Visual => SetCursorStyle::SteadyBlock,
+            };
+
+            execute!(self.terminal.backend_mut(), cursor_style)?;
+            self.cursor_mode = Some(buffer.mode);
+        }
+
         Ok(())
     }
 
+    // Returns the width available to the buffer content, which is the terminal width without the
+    // line numbers and their padding
+    pub fn get_buffer_width(&self, options: &Options) -> usize {
+        let gutter_width = gutter_width(options);
+
+        (self.get_terminal_size().width as usize).saturating_sub(gutter_width + gutter_width.min(1))
+    }
+
     // Returns the terminal size
     pub fn get_terminal_size(&self) -> ratatui::layout::Size {
         match self.terminal.size() {
@@ -335,3 +576,157 @@ impl Renderer {
         }
     }
 }
+
+// Formats a line of the buffer as it's rendered. Only the columns within the width are formatted,
+// so long lines take as long as short ones, unless there's no width because the lines wrap.
+pub fn format_buffer_line(
+    line: &str,
+    num: usize,
+    buffer: &Buffer,
+    matches: &[(usize, usize, bool)],
+    width: Option<usize>,
+    list: bool,
+    theme: &Theme,
+) -> Line<'static> {
+    format_line!(line, num, buffer, matches, width, list, theme)
+}
+
+// Builds the mode, file and position segments of the statusline. Each segment gets a third of the
+// width, so the least important parts are dropped first when the terminal is narrow: the file type,
+// the percentage and the pending keys on the right, and the start of the path in the middle
+pub fn statusline_segments(buffer: &Buffer, pending_keys: &str, width: usize) -> [String; 3] {
+    let segment_width = width / 3;
+
+    let left = format!(" {} ", buffer.mode_name());
+
+    let mut flags = String::new();
+
+    if buffer.is_modified() {
+        flags.push_str(" [+]");
+    }
+
+    if buffer.read_only || !buffer.state.mutable {
+        flags.push_str(" [RO]");
+    }
+
+    let path_width = segment_width.saturating_sub(flags.chars().count());
+    let middle = format!(
+        "{}{}",
+        truncate_start(&buffer.display_path(), path_width),
+        flags
+    );
+
+    let lines = buffer.content.len() - 1;
+    let percentage = (buffer.cursor.y * 100).checked_div(lines).unwrap_or(100);
+
+    // The column is where the cursor is on screen, counting from 1 like the line does
+    let column = buffer.cursor_column() + 1;
+
+    let mut parts = [
+        pending_keys.to_string(),
+        buffer.file_type().unwrap_or_default(),
+        format!("[{}/{}] :{}", buffer.cursor.y + 1, lines + 1, column),
+        format!(" {}%", percentage),
+    ];
+
+    for part in [1, 3, 0] {
+        if join_segment(&parts).chars().count() <= segment_width {
+            break;
+        }
+
+        parts[part].clear();
+    }
+
+    [left, middle, join_segment(&parts)]
+}
+
+// Joins the parts of a statusline segment that aren't empty
+fn join_segment(parts: &[String]) -> String {
+    let parts: Vec<&str> = parts
+        .iter()
+        .filter(|part| !part.is_empty())
+        .map(String::as_str)
+        .collect();
+
+    format!(" {} ", parts.join("  "))
+}
+
+// Cuts the start off the text so it fits the width, marking the cut with '…'
+fn truncate_start(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+
+    if len <= width {
+        text.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        let kept: String = text.chars().skip(len - (width - 1)).collect();
+        format!("…{}", kept)
+    }
+}
+
+// Splits a formatted line into the rows it wraps into, which start at the given columns.
+fn split_rows<'a>(line: Line<'a>, rows: &[usize]) -> Vec<Line<'a>> {
+    let mut split = vec![Line::default()];
+    let mut next_rows = rows.iter().skip(1).peekable();
+    let mut column = 0;
+
+    for span in line.spans {
+        let mut text = String::new();
+
+        for grapheme in span.content.graphemes(true) {
+            while next_rows.next_if(|start| column >= **start).is_some() {
+                if !text.is_empty() {
+                    let row = split.len() - 1;
+                    split[row].push_span(Span::styled(std::mem::take(&mut text), span.style));
+                }
+
+                split.push(Line::default());
+            }
+
+            column += grapheme.width();
+            text.push_str(grapheme);
+        }
+
+        if !text.is_empty() {
+            let row = split.len() - 1;
+            split[row].push_span(Span::styled(text, span.style));
+        }
+    }
+
+    split
+}
+
+// Returns the width of the line number column, which is hidden when no line numbers are shown
+fn gutter_width(options: &Options) -> usize {
+    if options.number || options.relative_number {
+        3
+    } else {
+        0
+    }
+}
+
+// Checks if the character is inside the visual selection between the start and the cursor
+fn is_selected(start: Cursor, cursor: Cursor, line_num: usize, num: usize) -> bool {
+    let (top, bottom) = if start.y < cursor.y || (start.y == cursor.y && start.x <= cursor.x) {
+        (start, cursor)
+    } else {
+        (cursor, start)
+    };
+
+    if line_num < top.y || line_num > bottom.y {
+        false
+    } else if top.y == bottom.y {
+        // Single line selection
+        num >= top.x && num <= bottom.x
+    } else if line_num == top.y {
+        // First line of multi-line selection
+        num >= top.x
+    } else if line_num == bottom.y {
+        // Last line of multi-line selection
+        num <= bottom.x
+    } else {
+        // Middle lines of multi-line selection
+        true
+    }
+}
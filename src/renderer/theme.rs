@@ -0,0 +1,209 @@
+use ratatui::style::{Color, Style};
+
+use std::fs;
+use std::path::Path;
+
+use crate::utils::xdg_dir;
+
+// ╭──────────────────────────────────────╮
+// │ Theme Structs                        │
+// ╰──────────────────────────────────────╯
+
+// The styles the renderer draws with. The theme is read from 'oxide/theme.toml' in the config
+// directory, which picks a built-in theme with 'base' and changes single styles in sections named
+// after them:
+//
+//   base = "light"
+//
+//   [cursor]
+//   fg = "black"
+//   bg = "#f2d5cf"
+//
+// Only the part of TOML a theme needs is read: comments, tables, and keys with string values. Like
+// in TOML, 'base' has to come before the first table, as keys after a table belong to it.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Theme {
+    pub cursor: Style,
+    pub selection: Style,
+    pub search_match: Style,
+    pub current_match: Style,
+    pub element: Style,
+    pub prefix: Style,
+    pub error: Style,
+    pub directory: Style,
+    pub preview: Style,
+    pub no_match: Style,
+    pub line_number: Style,
+    pub whitespace: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            cursor: Style::new()
+                .fg(Color::Black)
+                .bg(Color::Rgb(0xf2, 0xd5, 0xcf)),
+            selection: Style::new().bg(Color::Rgb(0x45, 0x47, 0x5a)),
+            search_match: Style::new().bg(Color::Rgb(0x58, 0x5b, 0x70)),
+            current_match: Style::new()
+                .fg(Color::Black)
+                .bg(Color::Rgb(0xf9, 0xe2, 0xaf)),
+            element: Style::new().bg(Color::Rgb(0x11, 0x11, 0x1b)),
+            prefix: Style::new().fg(Color::Black).bg(Color::Blue),
+            error: Style::new().fg(Color::Red),
+            directory: Style::new().fg(Color::Blue),
+            preview: Style::new().bg(Color::Rgb(0x18, 0x18, 0x25)),
+            no_match: Style::new().fg(Color::Black).bg(Color::Red),
+            line_number: Style::new().fg(Color::Rgb(0xf2, 0xd5, 0xcf)),
+            whitespace: Style::new().fg(Color::Rgb(0x58, 0x5b, 0x70)),
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            cursor: Style::new()
+                .fg(Color::White)
+                .bg(Color::Rgb(0x4c, 0x4f, 0x69)),
+            selection: Style::new().bg(Color::Rgb(0xcc, 0xd0, 0xda)),
+            search_match: Style::new().bg(Color::Rgb(0xbc, 0xc0, 0xcc)),
+            current_match: Style::new()
+                .fg(Color::White)
+                .bg(Color::Rgb(0xdf, 0x8e, 0x1d)),
+            element: Style::new().bg(Color::Rgb(0xe6, 0xe9, 0xef)),
+            prefix: Style::new().fg(Color::White).bg(Color::Blue),
+            error: Style::new().fg(Color::Red),
+            directory: Style::new().fg(Color::Blue),
+            preview: Style::new().bg(Color::Rgb(0xdc, 0xe0, 0xe8)),
+            no_match: Style::new().fg(Color::White).bg(Color::Red),
+            line_number: Style::new().fg(Color::Rgb(0x4c, 0x4f, 0x69)),
+            whitespace: Style::new().fg(Color::Rgb(0xac, 0xb0, 0xbe)),
+        }
+    }
+
+    // Loads the theme from the config directory. A missing file gives the default theme, and the
+    // values that can't be read keep their default, with a warning for each of them.
+    pub fn load() -> (Self, Vec<String>) {
+        match xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join("oxide").join("theme.toml"))
+        {
+            Some(path) if path.is_file() => Theme::from_file(&path),
+            _ => (Theme::default(), Vec::new()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> (Self, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(content) => Theme::parse(&content),
+            Err(error) => (
+                Theme::default(),
+                vec![format!("Failed to read {}: {}", path.display(), error)],
+            ),
+        }
+    }
+
+    // Reads the theme from the content of a theme file. Anything that can't be read is skipped with
+    // a warning, and the rest of the theme is still used.
+    pub fn parse(content: &str) -> (Self, Vec<String>) {
+        let mut theme = Theme::default();
+        let mut warnings: Vec<String> = Vec::new();
+        let mut section: Option<String> = None;
+
+        for (num, line) in content.lines().enumerate() {
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                let name = name.trim().to_string();
+
+                if theme.style_mut(&name).is_none() {
+                    warnings.push(format!("Unknown style in theme: {}", name));
+                }
+
+                section = Some(name);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                warnings.push(format!("Invalid line {} in theme: {}", num + 1, line));
+                continue;
+            };
+
+            let key = key.trim();
+            let value = value.trim().trim_matches('"');
+
+            match (&section, key) {
+                (None, "base") => match value {
+                    "dark" => theme = Theme::dark(),
+                    "light" => theme = Theme::light(),
+                    _ => warnings.push(format!("Unknown base theme: {}", value)),
+                },
+                (Some(name), "base") => warnings.push(format!(
+                    "The base theme has to be set before the first table, not in [{}]",
+                    name
+                )),
+                (Some(name), "fg" | "bg") => {
+                    let Some(style) = theme.style_mut(name) else {
+                        continue;
+                    };
+
+                    match value.parse::<Color>() {
+                        Ok(color) if key == "fg" => *style = style.fg(color),
+                        Ok(color) => *style = style.bg(color),
+                        Err(_) => warnings.push(format!("Invalid color for {}: {}", name, value)),
+                    }
+                }
+                _ => warnings.push(format!("Unknown key in theme: {}", key)),
+            }
+        }
+
+        (theme, warnings)
+    }
+
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        match name {
+            "cursor" => Some(&mut self.cursor),
+            "selection" => Some(&mut self.selection),
+            "search_match" => Some(&mut self.search_match),
+            "current_match" => Some(&mut self.current_match),
+            "element" => Some(&mut self.element),
+            "prefix" => Some(&mut self.prefix),
+            "error" => Some(&mut self.error),
+            "directory" => Some(&mut self.directory),
+            "preview" => Some(&mut self.preview),
+            "no_match" => Some(&mut self.no_match),
+            "line_number" => Some(&mut self.line_number),
+            "whitespace" => Some(&mut self.whitespace),
+            _ => None,
+        }
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Theme Helpers                        │
+// ╰──────────────────────────────────────╯
+
+// Cuts a comment off the end of the line. A '#' in a string, like in a hex color, isn't a comment.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+// ╭──────────────────────────────────────╮
+// │ Args Structs                         │
+// ╰──────────────────────────────────────╯
+
+// A file given on the command line, with the line and column to open it at. Both start at 1.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct FileArg {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+// ╭──────────────────────────────────────╮
+// │ Args Functions                       │
+// ╰──────────────────────────────────────╯
+
+// Reads the files to open from the command line arguments. A '+N' argument opens the file after it
+// at line N, and a file can be followed by ':line' or ':line:col' as well. A '+N' without a file
+// after it has nothing to apply to, and is ignored.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Vec<FileArg> {
+    let mut files = Vec::new();
+    let mut next_line: Option<usize> = None;
+
+    for arg in args {
+        if let Some(line) = arg.strip_prefix('+').and_then(|line| line.parse().ok()) {
+            next_line = Some(line);
+            continue;
+        }
+
+        let mut file = parse_position(&arg);
+
+        if let Some(line) = next_line.take() {
+            file.line = Some(line);
+        }
+
+        files.push(file);
+    }
+
+    files
+}
+
+// ╭──────────────────────────────────────╮
+// │ Args Helpers                         │
+// ╰──────────────────────────────────────╯
+
+// Splits the line and column off a 'file:line:col' argument. Files that exist are taken as they
+// are, so names with colons in them still open.
+fn parse_position(arg: &str) -> FileArg {
+    let file = |path: &str, line, column| FileArg {
+        path: PathBuf::from(path),
+        line,
+        column,
+    };
+
+    if Path::new(arg).exists() {
+        return file(arg, None, None);
+    }
+
+    let mut parts = arg.rsplitn(3, ':');
+    let last = parts.next().and_then(|part| part.parse::<usize>().ok());
+    let middle = parts.next();
+    let first = parts.next();
+
+    match (first, middle, last) {
+        (Some(path), Some(line), Some(column)) if !path.is_empty() => match line.parse() {
+            Ok(line) => file(path, Some(line), Some(column)),
+            Err(_) => file(&format!("{}:{}", path, line), Some(column), None),
+        },
+        (None, Some(path), Some(line)) if !path.is_empty() => file(path, Some(line), None),
+        _ => file(arg, None, None),
+    }
+}
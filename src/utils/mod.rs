@@ -1,4 +1,8 @@
+pub mod args;
 pub mod error;
 pub mod logging;
+pub mod paths;
 
+pub use args::*;
 pub use error::*;
+pub use paths::*;
@@ -0,0 +1,40 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+// ╭──────────────────────────────────────╮
+// │ Paths Functions                      │
+// ╰──────────────────────────────────────╯
+
+// Returns the XDG base directory in the environment variable, or the fallback under the home
+// directory when it isn't set, like '$XDG_CONFIG_HOME' or '~/.config'.
+pub fn xdg_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    match env::var(variable) {
+        Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home_dir().map(|home| home.join(fallback)),
+    }
+}
+
+// Expands a leading '~' to the home directory, like '~' or '~/notes.txt'. Other paths, and all of
+// them when the home directory isn't known, are left as they are.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), home_dir()) {
+        (Some(""), Some(home)) => home,
+        (Some(rest), Some(home)) if rest.starts_with('/') => PathBuf::from(format!(
+            "{}{}",
+            home.to_string_lossy().trim_end_matches('/'),
+            rest
+        )),
+        _ => PathBuf::from(path),
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Paths Helpers                        │
+// ╰──────────────────────────────────────╯
+
+fn home_dir() -> Option<PathBuf> {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() => Some(Path::new(&home).to_path_buf()),
+        _ => None,
+    }
+}
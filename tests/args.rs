@@ -0,0 +1,91 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use oxide::utils::{parse_args, FileArg};
+
+use common::temp_dir;
+
+fn args(args: &[&str]) -> Vec<FileArg> {
+    parse_args(args.iter().map(|arg| arg.to_string()))
+}
+
+fn file(path: &str, line: Option<usize>, column: Option<usize>) -> FileArg {
+    FileArg {
+        path: PathBuf::from(path),
+        line,
+        column,
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Parse Args                           │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn files_without_a_position() {
+    assert_eq!(
+        args(&["first.txt", "dir/second.txt"]),
+        vec![
+            file("first.txt", None, None),
+            file("dir/second.txt", None, None)
+        ]
+    );
+}
+
+// '+N' only applies to the file right after it.
+#[test]
+fn line_before_the_file() {
+    assert_eq!(
+        args(&["+12", "file.txt", "other.txt"]),
+        vec![
+            file("file.txt", Some(12), None),
+            file("other.txt", None, None)
+        ]
+    );
+}
+
+#[test]
+fn line_after_the_file() {
+    assert_eq!(
+        args(&["file.txt:12"]),
+        vec![file("file.txt", Some(12), None)]
+    );
+}
+
+#[test]
+fn line_and_column_after_the_file() {
+    assert_eq!(
+        args(&["file.txt:12:4"]),
+        vec![file("file.txt", Some(12), Some(4))]
+    );
+}
+
+// When the part before the last number isn't a number, it's part of the name of the file.
+#[test]
+fn missing_file_with_colon_in_its_name() {
+    assert_eq!(
+        args(&["dir/a:b.txt:3"]),
+        vec![file("dir/a:b.txt", Some(3), None)]
+    );
+}
+
+// A file that exists is opened as it is, even if its name looks like a position.
+#[test]
+fn existing_file_with_colon_in_its_name() {
+    let path = temp_dir("args-colon").join("notes:12");
+    fs::write(&path, "text").unwrap();
+    let arg = path.to_string_lossy().into_owned();
+
+    assert_eq!(args(&[&arg]), vec![file(&arg, None, None)]);
+}
+
+#[test]
+fn line_without_a_file_is_ignored() {
+    assert_eq!(
+        args(&["file.txt", "+12"]),
+        vec![file("file.txt", None, None)]
+    );
+    assert!(args(&["+12"]).is_empty());
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use oxide::buffer::{Buffer, Manipulation};
+use oxide::editor::{write_backup, Options};
+
+use common::temp_dir;
+
+// Edits the buffer and saves it the way the editor does, backing up the file before writing it.
+async fn edit_and_save(buffer: &mut Buffer, options: &Options) -> PathBuf {
+    let path = buffer.path.clone().unwrap();
+
+    buffer.remove_char().unwrap();
+
+    let backup = write_backup(&path, options).unwrap();
+    buffer.write_buffer(None, false).await.unwrap();
+
+    backup
+}
+
+fn backup_options() -> Options {
+    Options {
+        backup: true,
+        ..Options::default()
+    }
+}
+
+// ╭──────────────────────────────────────╮
+// │ Backups                              │
+// ╰──────────────────────────────────────╯
+
+#[tokio::test]
+async fn backup_matches_the_file_before_the_save() {
+    let dir = temp_dir("backup-plain");
+    let path = dir.join("file.txt");
+    fs::write(&path, "first\r\nsecond  \r\n").unwrap();
+
+    let mut buffer = Buffer::from_file(path.clone(), 20, 0).await.unwrap();
+    let backup = edit_and_save(&mut buffer, &backup_options()).await;
+
+    assert_eq!(backup, dir.join("file.txt~"));
+    assert_eq!(fs::read(&backup).unwrap(), b"first\r\nsecond  \r\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "irst\r\nsecond  \r\n");
+}
+
+// Each save keeps the version it overwrote in a new numbered backup.
+#[tokio::test]
+async fn numbered_backups_keep_every_version() {
+    let dir = temp_dir("backup-numbered");
+    let path = dir.join("file.txt");
+    fs::write(&path, "abc").unwrap();
+
+    let options = Options {
+        numbered_backups: true,
+        ..backup_options()
+    };
+    let mut buffer = Buffer::from_file(path.clone(), 20, 0).await.unwrap();
+
+    let first = edit_and_save(&mut buffer, &options).await;
+    let second = edit_and_save(&mut buffer, &options).await;
+
+    assert_eq!(first, dir.join("file.txt.~1~"));
+    assert_eq!(second, dir.join("file.txt.~2~"));
+    assert_eq!(fs::read_to_string(&first).unwrap(), "abc");
+    assert_eq!(fs::read_to_string(&second).unwrap(), "bc");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "c");
+}
+
+#[tokio::test]
+async fn backup_is_written_to_the_backup_directory() {
+    let dir = temp_dir("backup-dir");
+    let backup_dir = dir.join("backups");
+    let path = dir.join("file.txt");
+    fs::create_dir_all(&backup_dir).unwrap();
+    fs::write(&path, "text\n").unwrap();
+
+    let options = Options {
+        backup_dir: Some(backup_dir.clone()),
+        ..backup_options()
+    };
+    let mut buffer = Buffer::from_file(path.clone(), 20, 0).await.unwrap();
+    let backup = edit_and_save(&mut buffer, &options).await;
+
+    assert_eq!(backup, backup_dir.join("file.txt~"));
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "text\n");
+    assert!(!dir.join("file.txt~").exists());
+}
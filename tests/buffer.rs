@@ -0,0 +1,371 @@
+mod common;
+
+use std::fs::{self, OpenOptions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use oxide::buffer::{Buffer, Error, Manipulation, Navigation, VisualKind};
+use oxide::keybinding::{InsertDirection, ModeParams};
+
+use common::{buffer, cursor, temp_dir};
+
+fn file_buffer(lines: &[&str], path: &Path) -> Buffer {
+    let mut buffer = buffer(lines);
+    buffer.path = Some(path.to_path_buf());
+
+    buffer
+}
+
+// Writes the file and takes away its write permission. Returns false if the file can still be
+// written to, like when the tests run as root, as there's nothing to test then.
+fn read_only_file(path: &Path, content: &str) -> bool {
+    fs::write(path, content).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o444)).unwrap();
+
+    OpenOptions::new().append(true).open(path).is_err()
+}
+
+fn insert(buffer: &mut Buffer, insert_direction: InsertDirection) {
+    buffer.switch_mode(ModeParams::Insert { insert_direction });
+}
+
+// ╭──────────────────────────────────────╮
+// │ Cursor Limits                        │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn max_cursor_x_on_empty_line() {
+    let mut buffer = buffer(&[""]);
+
+    assert_eq!(buffer.max_cursor_x(), 0);
+
+    insert(&mut buffer, InsertDirection::After);
+    assert_eq!(buffer.max_cursor_x(), 0);
+    assert_eq!(buffer.cursor.x, 0);
+}
+
+#[test]
+fn max_cursor_x_on_one_character_line() {
+    let mut buffer = buffer(&["a"]);
+
+    assert_eq!(buffer.max_cursor_x(), 0);
+
+    buffer.move_cursor(1, 0);
+    assert_eq!(buffer.cursor.x, 0);
+
+    insert(&mut buffer, InsertDirection::After);
+    assert_eq!(buffer.max_cursor_x(), 1);
+    assert_eq!(buffer.cursor.x, 1);
+}
+
+#[test]
+fn max_cursor_x_on_multibyte_line() {
+    let mut buffer = buffer(&["añ漢"]);
+
+    assert_eq!(buffer.max_cursor_x(), 2);
+
+    insert(&mut buffer, InsertDirection::End);
+    assert_eq!(buffer.max_cursor_x(), "añ漢".len());
+}
+
+// Leaving Insert mode after the last character puts the cursor back on it.
+#[test]
+fn leaving_insert_mode_at_end_of_line() {
+    let mut buffer = buffer(&["hello"]);
+
+    insert(&mut buffer, InsertDirection::End);
+    assert_eq!(buffer.cursor.x, 5);
+
+    buffer.switch_mode(ModeParams::Normal);
+    assert_eq!(buffer.cursor.x, 4);
+    assert_eq!(buffer.cursor.desired_x, 4);
+}
+
+#[test]
+fn leaving_insert_mode_at_end_of_multibyte_line() {
+    let mut buffer = buffer(&["héllo wörld"]);
+
+    insert(&mut buffer, InsertDirection::End);
+    buffer.add_char('!').unwrap();
+    buffer.switch_mode(ModeParams::Normal);
+
+    assert_eq!(buffer.cursor.x, 11);
+}
+
+#[test]
+fn leaving_insert_mode_on_empty_line() {
+    let mut buffer = buffer(&["text", ""]);
+    buffer.cursor = cursor(0, 1);
+
+    insert(&mut buffer, InsertDirection::After);
+    buffer.switch_mode(ModeParams::Normal);
+
+    assert_eq!(buffer.cursor, cursor(0, 1));
+}
+
+#[test]
+fn moving_to_shorter_line_clamps_to_its_end() {
+    let mut buffer = buffer(&["a long line", "", "ab"]);
+    buffer.cursor = cursor(8, 0);
+
+    buffer.move_cursor(0, 1);
+    assert_eq!(buffer.cursor.x, 0);
+
+    buffer.move_cursor(0, 1);
+    assert_eq!(buffer.cursor.x, 1);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Selection                            │
+// ╰──────────────────────────────────────╯
+
+// The end of a selection past the end of the line is clamped to its characters, not its bytes.
+#[test]
+fn selection_end_is_clamped_to_multibyte_line() {
+    let mut buffer = buffer(&["héllo wörld"]);
+    buffer.cursor = cursor(6, 0);
+    buffer.switch_mode(ModeParams::Visual {
+        kind: VisualKind::Char,
+    });
+    buffer.cursor = cursor(11, 0);
+
+    assert_eq!(buffer.selection(), Some((cursor(6, 0), cursor(11, 0))));
+    assert_eq!(buffer.yank_selection().unwrap().text, vec!["wörld"]);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Modified                             │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn new_buffer_is_not_modified() {
+    let buffer = buffer(&["text"]);
+
+    assert!(!buffer.is_modified());
+    assert_eq!(buffer.display_title(), "test");
+}
+
+// A buffer without a file is modified once it's edited, so quitting asks about it.
+#[test]
+fn edited_buffer_without_a_file_is_modified() {
+    let mut buffer = buffer(&["text"]);
+
+    buffer.remove_char().unwrap();
+
+    assert!(buffer.is_modified());
+    assert_eq!(buffer.display_title(), "test [+]");
+}
+
+#[test]
+fn undo_back_to_the_loaded_content_is_not_modified() {
+    let mut buffer = buffer(&["text"]);
+    insert(&mut buffer, InsertDirection::End);
+    buffer.add_char('!').unwrap();
+    buffer.switch_mode(ModeParams::Normal);
+
+    assert!(buffer.is_modified());
+
+    buffer.undo().unwrap();
+    assert!(!buffer.is_modified());
+
+    buffer.redo().unwrap();
+    assert!(buffer.is_modified());
+}
+
+#[tokio::test]
+async fn undo_back_to_the_written_content_is_not_modified() {
+    let path = temp_dir("modified").join("file.txt");
+    let mut buffer = file_buffer(&["one"], &path);
+
+    buffer.remove_char().unwrap();
+    buffer.write_buffer(None, false).await.unwrap();
+    assert!(!buffer.is_modified());
+
+    buffer.remove_char().unwrap();
+    assert!(buffer.is_modified());
+
+    buffer.undo().unwrap();
+    assert!(!buffer.is_modified());
+
+    // The content before the write is a change from the file now
+    buffer.undo().unwrap();
+    assert!(buffer.is_modified());
+}
+
+// The flag is cached once it's updated, and only computed again after an edit or an undo.
+#[test]
+fn modified_flag_is_updated_after_changes() {
+    let mut buffer = buffer(&["text"]);
+
+    buffer.remove_char().unwrap();
+    buffer.update_modified();
+    assert!(!buffer.changed);
+    assert!(buffer.is_modified());
+
+    buffer.undo().unwrap();
+    assert!(!buffer.is_modified());
+
+    buffer.update_modified();
+    assert!(!buffer.changed);
+    assert!(!buffer.modified);
+}
+
+#[test]
+fn edited_scratch_buffer_is_not_modified() {
+    let mut buffer = Buffer::scratch(20, 0);
+
+    buffer.remove_char().unwrap();
+
+    assert!(!buffer.is_modified());
+}
+
+// ╭──────────────────────────────────────╮
+// │ Trim On Write                        │
+// ╰──────────────────────────────────────╯
+
+#[tokio::test]
+async fn write_trims_trailing_spaces_and_tabs() {
+    let path = temp_dir("trim").join("file.txt");
+    let mut buffer = file_buffer(&["fn main() {  ", "\tbody\t \t", "}"], &path);
+    buffer.options.trim_whitespace = true;
+
+    buffer.write_buffer(None, false).await.unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "fn main() {\n\tbody\n}\n"
+    );
+    assert_eq!(buffer.content, vec!["fn main() {", "\tbody", "}"]);
+    assert!(!buffer.is_modified());
+}
+
+#[tokio::test]
+async fn write_keeps_trailing_whitespace_without_trim() {
+    let path = temp_dir("no-trim").join("file.txt");
+    let mut buffer = file_buffer(&["text  ", ""], &path);
+
+    buffer.write_buffer(None, false).await.unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "text  \n\n");
+}
+
+// The trailing empty lines are only removed with 'fixendofline', which makes the file end in
+// exactly one newline.
+#[tokio::test]
+async fn write_trims_trailing_empty_lines_with_fixeol() {
+    let dir = temp_dir("trim-eol");
+    let kept = dir.join("kept.txt");
+    let fixed = dir.join("fixed.txt");
+
+    let mut buffer = file_buffer(&["text", "", ""], &kept);
+    buffer.options.trim_whitespace = true;
+    buffer.write_buffer(None, false).await.unwrap();
+
+    assert_eq!(fs::read_to_string(&kept).unwrap(), "text\n\n\n");
+
+    let mut buffer = file_buffer(&["text", "", ""], &fixed);
+    buffer.options.trim_whitespace = true;
+    buffer.options.end_of_line = false;
+    buffer.options.fix_end_of_line = true;
+    buffer.write_buffer(None, false).await.unwrap();
+
+    assert_eq!(fs::read_to_string(&fixed).unwrap(), "text\n");
+    assert_eq!(buffer.content, vec!["text"]);
+}
+
+#[tokio::test]
+async fn write_clamps_cursor_in_trimmed_region() {
+    let path = temp_dir("trim-cursor").join("file.txt");
+    let mut buffer = file_buffer(&["short", "word     "], &path);
+    buffer.options.trim_whitespace = true;
+    buffer.cursor = cursor(7, 1);
+
+    buffer.write_buffer(None, false).await.unwrap();
+
+    assert_eq!(buffer.cursor, cursor(3, 1));
+}
+
+#[tokio::test]
+async fn trimmed_file_reads_back_the_same() {
+    let path = temp_dir("trim-read").join("file.txt");
+    let mut buffer = file_buffer(&["  indented  ", "tabs\t\t", "plain"], &path);
+    buffer.options.trim_whitespace = true;
+
+    buffer.write_buffer(None, false).await.unwrap();
+
+    let read = Buffer::from_file(path, 20, 0).await.unwrap();
+
+    assert_eq!(read.content, vec!["  indented", "tabs", "plain"]);
+    assert!(read.options.end_of_line);
+}
+
+// The trim is undone in one step, separately from the edits before it.
+#[tokio::test]
+async fn trim_is_one_undo_step() {
+    let path = temp_dir("trim-undo").join("file.txt");
+    let mut buffer = file_buffer(&["a  ", "b  "], &path);
+    buffer.options.trim_whitespace = true;
+
+    buffer.write_buffer(None, false).await.unwrap();
+    buffer.undo().unwrap();
+
+    assert_eq!(buffer.content, vec!["a  ", "b  "]);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Write Errors                         │
+// ╰──────────────────────────────────────╯
+
+#[tokio::test]
+async fn write_to_read_only_file_needs_force() {
+    let path = temp_dir("read-only").join("file.txt");
+
+    if !read_only_file(&path, "old\n") {
+        return;
+    }
+
+    let mut buffer = Buffer::from_file(path.clone(), 20, 0).await.unwrap();
+    assert!(buffer.read_only);
+
+    buffer.remove_char().unwrap();
+
+    let result = buffer.write_buffer(None, false).await;
+
+    assert!(matches!(result, Err(Error::ReadOnlyFileError)));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+    assert!(buffer.is_modified());
+
+    buffer.write_buffer(None, true).await.unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "ld\n");
+    assert_eq!(
+        fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+        0o444
+    );
+    assert!(buffer.read_only);
+    assert!(!buffer.is_modified());
+}
+
+#[tokio::test]
+async fn write_to_missing_directory_needs_force() {
+    let path = temp_dir("missing-dir").join("new").join("file.txt");
+    let mut buffer = file_buffer(&["text"], &path);
+
+    let result = buffer.write_buffer(None, false).await;
+
+    assert!(matches!(result, Err(Error::NoDirectoryError)));
+    assert!(!path.exists());
+
+    buffer.write_buffer(None, true).await.unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "text\n");
+}
+
+#[tokio::test]
+async fn write_without_a_file_name_fails() {
+    let mut buffer = buffer(&["text"]);
+
+    let result = buffer.write_buffer(None, false).await;
+
+    assert!(matches!(result, Err(Error::NoFileNameError)));
+}
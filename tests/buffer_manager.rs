@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Component, Path, PathBuf};
+
+use oxide::editor::BufferManager;
+
+use common::{buffer, temp_dir};
+
+fn manager(paths: &[&Path]) -> BufferManager {
+    BufferManager {
+        buffers: paths
+            .iter()
+            .map(|path| {
+                let mut buffer = buffer(&[""]);
+                buffer.path = Some(path.to_path_buf());
+
+                buffer
+            })
+            .collect(),
+        active_buffer: 0,
+        recent_buffers: vec![0],
+    }
+}
+
+// Returns the absolute path relative to the current working directory, by going up to the root
+// first.
+fn relative(path: &Path) -> PathBuf {
+    let depth = std::env::current_dir().unwrap().components().count() - 1;
+    let mut relative: PathBuf = (0..depth).map(|_| "..").collect();
+
+    for component in path.components() {
+        if let Component::Normal(name) = component {
+            relative.push(name);
+        }
+    }
+
+    relative
+}
+
+// ╭──────────────────────────────────────╮
+// │ Find Buffer                          │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn relative_and_absolute_paths_find_the_same_buffer() {
+    let path = temp_dir("manager-relative").join("file.txt");
+    fs::write(&path, "text").unwrap();
+
+    let manager = manager(&[Path::new("/other.txt"), &path]);
+
+    assert!(relative(&path).is_relative());
+    assert_eq!(manager.find_buffer(&path), Some(1));
+    assert_eq!(manager.find_buffer(&relative(&path)), Some(1));
+
+    let manager = self::manager(&[&relative(&path)]);
+
+    assert_eq!(manager.find_buffer(&path), Some(0));
+}
+
+// A file that doesn't exist yet can't be resolved on disk, but it's still opened once.
+#[test]
+fn relative_and_absolute_paths_of_new_file_find_the_same_buffer() {
+    let path = temp_dir("manager-new").join("new.txt");
+
+    let manager = manager(&[&relative(&path)]);
+
+    assert_eq!(manager.find_buffer(&path), Some(0));
+}
+
+#[test]
+fn symlink_finds_the_buffer_of_its_target() {
+    let dir = temp_dir("manager-symlink");
+    let path = dir.join("file.txt");
+    let link = dir.join("link.txt");
+    fs::write(&path, "text").unwrap();
+    symlink(&path, &link).unwrap();
+
+    assert_eq!(manager(&[&path]).find_buffer(&link), Some(0));
+    assert_eq!(manager(&[&link]).find_buffer(&path), Some(0));
+}
+
+#[test]
+fn dot_components_find_the_same_buffer() {
+    let dir = temp_dir("manager-dots");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join("file.txt"), "text").unwrap();
+
+    let manager = manager(&[&dir.join("file.txt")]);
+
+    assert_eq!(manager.find_buffer(&dir.join("sub/../file.txt")), Some(0));
+    assert_eq!(manager.find_buffer(&dir.join("./file.txt")), Some(0));
+    assert_eq!(manager.find_buffer(&dir.join("other.txt")), None);
+}
@@ -0,0 +1,36 @@
+// The fixtures shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use oxide::buffer::{Buffer, BufferKind, BufferState, Cursor};
+
+// Returns a buffer with the lines in it, without a file.
+pub fn buffer<S: AsRef<str>>(lines: &[S]) -> Buffer {
+    Buffer::new(
+        "test".to_string(),
+        lines.iter().map(|line| line.as_ref().to_string()).collect(),
+        None,
+        BufferKind::Normal,
+        20,
+        0,
+        BufferState::default(),
+    )
+}
+
+pub fn cursor(x: usize, y: usize) -> Cursor {
+    Cursor { x, y, desired_x: x }
+}
+
+// Returns an empty directory for the test, which is removed first if an earlier run left it. The
+// process id is part of the name, so runs at the same time don't share the directory.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("oxide-{}-{}", process::id(), name));
+
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir.canonicalize().unwrap()
+}
@@ -0,0 +1,111 @@
+use oxide::keybinding::{Action, CommandParser, LineRange, OptionValue, Substitution};
+
+fn substitution(pattern: &str, replacement: &str, global: bool, range: LineRange) -> Vec<Action> {
+    vec![Action::Substitute(Substitution {
+        pattern: pattern.to_string(),
+        replacement: replacement.to_string(),
+        global,
+        range,
+    })]
+}
+
+fn error(message: &str) -> Vec<Action> {
+    vec![Action::Error(message.to_string())]
+}
+
+// ╭──────────────────────────────────────╮
+// │ Substitution                         │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn substitution_on_current_line() {
+    assert_eq!(
+        CommandParser::parse("s/old/new/"),
+        substitution("old", "new", false, LineRange::Current)
+    );
+    assert_eq!(
+        CommandParser::parse("s/old/new"),
+        substitution("old", "new", false, LineRange::Current)
+    );
+}
+
+#[test]
+fn escaped_slashes_are_part_of_the_pattern() {
+    assert_eq!(
+        CommandParser::parse(r"s/a\/b/c/"),
+        substitution("a/b", "c", false, LineRange::Current)
+    );
+    assert_eq!(
+        CommandParser::parse(r"s/a\\/c\/d/"),
+        substitution(r"a\", "c/d", false, LineRange::Current)
+    );
+}
+
+#[test]
+fn global_substitution_on_all_lines() {
+    assert_eq!(
+        CommandParser::parse("%s/x/y/g"),
+        substitution("x", "y", true, LineRange::All)
+    );
+}
+
+// The lines of a range can be given in either order.
+#[test]
+fn substitution_on_range_of_lines() {
+    assert_eq!(
+        CommandParser::parse("3,10s/x/y/"),
+        substitution("x", "y", false, LineRange::Lines(3, 10))
+    );
+    assert_eq!(
+        CommandParser::parse("10,3s/x/y/"),
+        substitution("x", "y", false, LineRange::Lines(3, 10))
+    );
+    assert_eq!(
+        CommandParser::parse("7s/x/y/"),
+        substitution("x", "y", false, LineRange::Lines(7, 7))
+    );
+}
+
+#[test]
+fn substitution_with_empty_pattern() {
+    assert_eq!(CommandParser::parse("s//x/"), error("Empty pattern"));
+}
+
+#[test]
+fn substitution_with_invalid_flag() {
+    assert_eq!(CommandParser::parse("s/x/y/q"), error("Invalid flag: q"));
+    assert_eq!(CommandParser::parse("s/x/y/gq"), error("Invalid flag: q"));
+}
+
+#[test]
+fn substitution_with_trailing_characters() {
+    assert_eq!(
+        CommandParser::parse("s/x/y/g/z"),
+        error("Trailing characters after the flags")
+    );
+}
+
+// ╭──────────────────────────────────────╮
+// │ Set                                  │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn set_without_options_shows_them() {
+    assert_eq!(CommandParser::parse("set"), vec![Action::ShowOptions]);
+}
+
+// A 'no' in front is left to the options to make sense of, since 'number' starts with it as well.
+#[test]
+fn set_options() {
+    assert_eq!(
+        CommandParser::parse("set nonumber tabwidth=abc list"),
+        vec![
+            Action::SetOption("nonumber".to_string(), OptionValue::On),
+            Action::SetOption(
+                "tabwidth".to_string(),
+                OptionValue::Value("abc".to_string())
+            ),
+            Action::SetOption("list".to_string(), OptionValue::On),
+        ]
+    );
+}
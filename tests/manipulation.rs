@@ -0,0 +1,583 @@
+mod common;
+
+use oxide::buffer::{
+    Buffer, Cursor, Manipulation, Mode, Navigation, Register, RegisterKind, VisualKind,
+};
+use oxide::keybinding::{CaseChange, InsertDirection, LineRange, ModeParams, Motion, Substitution};
+
+use common::{buffer, cursor};
+
+// Selects from the start to the end cursor, both included.
+fn select(buffer: &mut Buffer, kind: VisualKind, start: Cursor, end: Cursor) {
+    buffer.cursor = start;
+    buffer.switch_mode(ModeParams::Visual { kind });
+    buffer.cursor = end;
+}
+
+// Enters Insert mode before the character at the column.
+fn insert_at(buffer: &mut Buffer, x: usize, y: usize) {
+    buffer.cursor = cursor(x, y);
+    buffer.switch_mode(ModeParams::Insert {
+        insert_direction: InsertDirection::Before,
+    });
+}
+
+fn linewise(text: &[&str]) -> Register {
+    Register::new(
+        text.iter().map(|line| line.to_string()).collect(),
+        RegisterKind::Linewise,
+    )
+}
+
+fn charwise(text: &[&str]) -> Register {
+    Register::new(
+        text.iter().map(|line| line.to_string()).collect(),
+        RegisterKind::Charwise,
+    )
+}
+
+// ╭──────────────────────────────────────╮
+// │ Paste                                │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn paste_after_multibyte_character() {
+    let mut buffer = buffer(&["héllo"]);
+    buffer.cursor = cursor(2, 0);
+
+    buffer.paste(&charwise(&["X"]), false);
+
+    assert_eq!(buffer.content, vec!["hélXlo"]);
+    assert_eq!(buffer.cursor.x, 3);
+}
+
+#[test]
+fn paste_before_multibyte_characters() {
+    let mut buffer = buffer(&["ééé"]);
+    buffer.cursor = cursor(2, 0);
+
+    buffer.paste(&charwise(&["X"]), true);
+
+    assert_eq!(buffer.content, vec!["ééXé"]);
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+#[test]
+fn paste_multiple_lines_after_multibyte_character() {
+    let mut buffer = buffer(&["héllo"]);
+    buffer.cursor = cursor(1, 0);
+
+    buffer.paste(&charwise(&["ab", "cd"]), false);
+
+    assert_eq!(buffer.content, vec!["héab", "cdllo"]);
+    assert_eq!(buffer.cursor, cursor(2, 0));
+}
+
+#[test]
+fn paste_into_empty_line() {
+    let mut buffer = buffer(&[""]);
+
+    buffer.paste(&charwise(&["ü"]), false);
+
+    assert_eq!(buffer.content, vec!["ü"]);
+    assert_eq!(buffer.cursor.x, 0);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Join                                 │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn join_puts_cursor_at_join_point_after_multibyte_text() {
+    let mut buffer = buffer(&["héllo", "world"]);
+
+    buffer.join_line(true);
+
+    assert_eq!(buffer.content, vec!["héllo world"]);
+    assert_eq!(buffer.cursor.x, 5);
+}
+
+#[test]
+fn join_without_spaces_keeps_the_text_as_it_is() {
+    let mut buffer = buffer(&["漢字", "  かな"]);
+
+    buffer.join_line(false);
+
+    assert_eq!(buffer.content, vec!["漢字  かな"]);
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Paste Over Selection                 │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn paste_charwise_over_selection_after_multibyte_text() {
+    let mut buffer = buffer(&["héllo wörld"]);
+    select(&mut buffer, VisualKind::Char, cursor(6, 0), cursor(10, 0));
+
+    let replaced = buffer.paste_over_selection(&charwise(&["there"]));
+
+    assert_eq!(buffer.content, vec!["héllo there"]);
+    assert_eq!(replaced, Some(charwise(&["wörld"])));
+    assert_eq!(buffer.mode, Mode::Normal);
+}
+
+// Multi-line text splits the line around the selection, with the rest of the line after the last
+// pasted line.
+#[test]
+fn paste_multiline_charwise_over_selection() {
+    let mut buffer = buffer(&["añb ccc d"]);
+    select(&mut buffer, VisualKind::Char, cursor(4, 0), cursor(6, 0));
+
+    buffer.paste_over_selection(&charwise(&["one", "two"]));
+
+    assert_eq!(buffer.content, vec!["añb one", "two d"]);
+    assert_eq!(buffer.cursor, cursor(4, 0));
+}
+
+// Line-wise text replacing part of a line splits the line, and the lines go in between.
+#[test]
+fn paste_linewise_over_selection_in_middle_of_line() {
+    let mut buffer = buffer(&["añb ccc d"]);
+    select(&mut buffer, VisualKind::Char, cursor(4, 0), cursor(6, 0));
+
+    buffer.paste_over_selection(&linewise(&["new"]));
+
+    assert_eq!(buffer.content, vec!["añb ", "new", " d"]);
+    assert_eq!(buffer.cursor, cursor(0, 1));
+}
+
+#[test]
+fn paste_over_selection_is_one_undo_step() {
+    let mut buffer = buffer(&["añb ccc d"]);
+    select(&mut buffer, VisualKind::Char, cursor(4, 0), cursor(6, 0));
+
+    buffer.paste_over_selection(&linewise(&["new"]));
+    buffer.undo().unwrap();
+
+    assert_eq!(buffer.content, vec!["añb ccc d"]);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Undo                                 │
+// ╰──────────────────────────────────────╯
+
+// Typing, deleting and leaving Insert mode is one change, undone together with the cursor.
+#[test]
+fn insert_session_is_one_undo_step() {
+    let mut buffer = buffer(&["héllo world", "end"]);
+    insert_at(&mut buffer, 6, 0);
+
+    for c in "big ".chars() {
+        buffer.add_char(c).unwrap();
+    }
+    buffer.remove_char().unwrap();
+    buffer.remove_char().unwrap();
+    buffer.switch_mode(ModeParams::Normal);
+
+    assert_eq!(buffer.content, vec!["héllo biworld", "end"]);
+
+    buffer.undo().unwrap();
+
+    assert_eq!(buffer.content, vec!["héllo world", "end"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (6, 0));
+    assert!(buffer.undo().is_err());
+}
+
+// Moving the cursor in Insert mode splits the text typed before and after it.
+#[test]
+fn insert_session_split_by_cursor_move() {
+    let mut buffer = buffer(&["héllo world"]);
+    insert_at(&mut buffer, 5, 0);
+
+    buffer.add_char('!').unwrap();
+    buffer.move_cursor(-1, 0);
+    buffer.break_undo_sequence();
+    buffer.add_char('?').unwrap();
+    buffer.switch_mode(ModeParams::Normal);
+
+    assert_eq!(buffer.content, vec!["héllo?! world"]);
+
+    buffer.undo().unwrap();
+    assert_eq!(buffer.content, vec!["héllo! world"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (5, 0));
+
+    buffer.undo().unwrap();
+    assert_eq!(buffer.content, vec!["héllo world"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (5, 0));
+}
+
+// A substitution over many lines is one change, undone with the cursor where it was.
+#[test]
+fn substitution_is_one_undo_step() {
+    let mut buffer = buffer(&["a x x", "b", "x c", "x"]);
+    buffer.cursor = cursor(2, 2);
+
+    let counts = buffer.substitute(&Substitution {
+        pattern: "x".to_string(),
+        replacement: "yy".to_string(),
+        global: true,
+        range: LineRange::All,
+    });
+
+    assert_eq!(counts, (4, 3));
+    assert_eq!(buffer.content, vec!["a yy yy", "b", "yy c", "yy"]);
+
+    buffer.undo().unwrap();
+
+    assert_eq!(buffer.content, vec!["a x x", "b", "x c", "x"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (2, 2));
+    assert!(buffer.undo().is_err());
+}
+
+// ╭──────────────────────────────────────╮
+// │ Indent                               │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn indent_with_spaces_when_tabs_are_expanded() {
+    let mut buffer = buffer(&["fn main() {}"]);
+    buffer.options.expand_tab = true;
+
+    buffer.indent_line(4);
+
+    assert_eq!(buffer.content, vec!["    fn main() {}"]);
+    assert_eq!(buffer.cursor.x, 4);
+}
+
+#[test]
+fn indent_with_a_tab_when_tabs_are_not_expanded() {
+    let mut buffer = buffer(&["\tlet x = 1;"]);
+    buffer.options.expand_tab = false;
+
+    buffer.indent_line(4);
+
+    assert_eq!(buffer.content, vec!["\t\tlet x = 1;"]);
+    assert_eq!(buffer.cursor.x, 1);
+}
+
+#[test]
+fn indent_of_empty_line_adds_no_undo_step() {
+    let mut buffer = buffer(&[""]);
+
+    buffer.indent_line(4);
+
+    assert_eq!(buffer.content, vec![""]);
+    assert!(buffer.undo().is_err());
+}
+
+// ╭──────────────────────────────────────╮
+// │ Insert Mode Deletion                 │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn delete_word_before_removes_word_and_whitespace() {
+    let mut buffer = buffer(&["    let value = 1"]);
+    insert_at(&mut buffer, 14, 0);
+
+    buffer.delete_word_before().unwrap();
+
+    assert_eq!(buffer.content, vec!["    let = 1"]);
+    assert_eq!(buffer.cursor.x, 8);
+}
+
+#[test]
+fn delete_word_before_stops_at_punctuation() {
+    let mut buffer = buffer(&["foo.bar"]);
+    insert_at(&mut buffer, 7, 0);
+
+    buffer.delete_word_before().unwrap();
+    assert_eq!(buffer.content, vec!["foo."]);
+
+    buffer.delete_word_before().unwrap();
+    assert_eq!(buffer.content, vec!["foo"]);
+}
+
+#[test]
+fn delete_word_before_across_indentation() {
+    let mut buffer = buffer(&["fn main() {", "        body"]);
+    insert_at(&mut buffer, 8, 1);
+
+    buffer.delete_word_before().unwrap();
+
+    assert_eq!(buffer.content, vec!["fn main() {", "body"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (0, 1));
+}
+
+#[test]
+fn delete_word_before_at_column_0_joins_lines() {
+    let mut buffer = buffer(&["first", "second"]);
+    insert_at(&mut buffer, 0, 1);
+
+    buffer.delete_word_before().unwrap();
+
+    assert_eq!(buffer.content, vec!["firstsecond"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (5, 0));
+}
+
+#[test]
+fn delete_word_before_multibyte_word() {
+    let mut buffer = buffer(&["say héllo"]);
+    insert_at(&mut buffer, 9, 0);
+
+    buffer.delete_word_before().unwrap();
+
+    assert_eq!(buffer.content, vec!["say "]);
+    assert_eq!(buffer.cursor.x, 4);
+}
+
+#[test]
+fn delete_to_line_start_keeps_indentation() {
+    let mut buffer = buffer(&["    let x = 1;"]);
+    insert_at(&mut buffer, 10, 0);
+
+    buffer.delete_to_line_start().unwrap();
+
+    assert_eq!(buffer.content, vec!["    = 1;"]);
+    assert_eq!(buffer.cursor.x, 4);
+}
+
+#[test]
+fn delete_to_line_start_inside_indentation_removes_it() {
+    let mut buffer = buffer(&["    let x = 1;"]);
+    insert_at(&mut buffer, 2, 0);
+
+    buffer.delete_to_line_start().unwrap();
+
+    assert_eq!(buffer.content, vec!["  let x = 1;"]);
+    assert_eq!(buffer.cursor.x, 0);
+}
+
+#[test]
+fn delete_to_line_start_right_after_indentation_removes_it() {
+    let mut buffer = buffer(&["\t\tx"]);
+    insert_at(&mut buffer, 2, 0);
+
+    buffer.delete_to_line_start().unwrap();
+
+    assert_eq!(buffer.content, vec!["x"]);
+    assert_eq!(buffer.cursor.x, 0);
+}
+
+#[test]
+fn delete_to_line_start_at_column_0_joins_lines() {
+    let mut buffer = buffer(&["first", "  second"]);
+    insert_at(&mut buffer, 0, 1);
+
+    buffer.delete_to_line_start().unwrap();
+
+    assert_eq!(buffer.content, vec!["first  second"]);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (5, 0));
+}
+
+#[test]
+fn insert_mode_deletion_outside_insert_mode_is_an_error() {
+    let mut buffer = buffer(&["text"]);
+
+    assert!(buffer.delete_word_before().is_err());
+    assert!(buffer.delete_to_line_start().is_err());
+}
+
+// ╭──────────────────────────────────────╮
+// │ Smart Backspace                      │
+// ╰──────────────────────────────────────╯
+
+// Backspaces once at the column and returns the line and the cursor column after it.
+fn backspace_at(line: &str, x: usize) -> (String, usize) {
+    let mut buffer = buffer(&[line]);
+    buffer.options.tab_width = 4;
+    insert_at(&mut buffer, x, 0);
+
+    buffer.remove_char().unwrap();
+
+    (buffer.content[0].clone(), buffer.cursor.x)
+}
+
+#[test]
+fn backspace_at_column_1_of_indentation() {
+    assert_eq!(backspace_at(" x", 1), ("x".to_string(), 0));
+}
+
+#[test]
+fn backspace_exactly_on_a_tab_stop() {
+    assert_eq!(backspace_at("    x", 4), ("x".to_string(), 0));
+    assert_eq!(backspace_at("        x", 8), ("    x".to_string(), 4));
+}
+
+#[test]
+fn backspace_between_tab_stops_goes_back_to_previous_stop() {
+    assert_eq!(backspace_at("      x", 6), ("    x".to_string(), 4));
+    assert_eq!(backspace_at("     x", 5), ("    x".to_string(), 4));
+}
+
+// Only the spaces are removed to the tab stop; a tab before them stays, and a tab right before
+// the cursor is removed on its own.
+#[test]
+fn backspace_in_mixed_tab_and_space_indentation() {
+    assert_eq!(backspace_at("\t  x", 3), ("\tx".to_string(), 1));
+    assert_eq!(backspace_at(" \t  x", 4), (" \tx".to_string(), 2));
+    assert_eq!(backspace_at("  \tx", 3), ("  x".to_string(), 2));
+}
+
+#[test]
+fn backspace_after_text_removes_one_space() {
+    assert_eq!(backspace_at("x       y", 8), ("x      y".to_string(), 7));
+}
+
+// ╭──────────────────────────────────────╮
+// │ Wide And Combining Characters        │
+// ╰──────────────────────────────────────╯
+
+const CJK: &str = "漢字とかなのテスト";
+const EMOJI: &str = "a👨‍👩‍👧b👍🏽c";
+const MIXED: &str = "let café = \"日本\"; // é";
+
+// Runs the edits that work on the cursor position at every column of the lines. None of them may
+// panic, and the cursor has to end up on a character boundary.
+#[test]
+fn edits_at_every_column_of_multibyte_lines() {
+    let edits: Vec<(&str, fn(&mut Buffer))> = vec![
+        ("p", |buffer| buffer.paste(&charwise(&["X"]), false)),
+        ("P", |buffer| buffer.paste(&charwise(&["X"]), true)),
+        ("p lines", |buffer| {
+            buffer.paste(&charwise(&["X", "Y"]), false)
+        }),
+        ("p block", |buffer| {
+            let block = Register::new(vec!["X".to_string()], RegisterKind::Blockwise);
+            buffer.paste(&block, false)
+        }),
+        ("x", |buffer| buffer.remove_char().unwrap()),
+        ("r", |buffer| buffer.replace_char('X')),
+        ("r enter", |buffer| buffer.replace_char('\n')),
+        ("~", |buffer| buffer.change_case(CaseChange::Toggle)),
+        ("J", |buffer| buffer.join_line(true)),
+        (">>", |buffer| buffer.indent_line(4)),
+        ("gc", |buffer| buffer.toggle_comment()),
+        ("dw", |buffer| {
+            let _ = buffer.delete_motion(Motion::WordForward);
+        }),
+        ("d$", |buffer| {
+            let _ = buffer.delete_motion(Motion::LineEnd);
+        }),
+        ("cw", |buffer| {
+            let _ = buffer.change_motion(Motion::WordForward);
+            buffer.add_char('X').unwrap();
+        }),
+        ("a", |buffer| {
+            buffer.switch_mode(ModeParams::Insert {
+                insert_direction: InsertDirection::After,
+            });
+            buffer.add_char('X').unwrap();
+            buffer.remove_char().unwrap();
+            buffer.remove_char().unwrap();
+            buffer.delete_forward();
+            buffer.switch_mode(ModeParams::Normal);
+        }),
+        ("v p", |buffer| {
+            let start = buffer.cursor;
+            select(buffer, VisualKind::Char, start, cursor(start.x + 1, 0));
+            buffer.paste_over_selection(&linewise(&["X"]));
+        }),
+        ("ctrl-v p", |buffer| {
+            let start = buffer.cursor;
+            select(buffer, VisualKind::Block, start, cursor(start.x, 1));
+            buffer.paste_over_selection(&charwise(&["X"]));
+        }),
+    ];
+
+    for line in [CJK, EMOJI, MIXED] {
+        for x in 0..line.chars().count() {
+            for (name, edit) in &edits {
+                let mut buffer = buffer(&[line, line]);
+                buffer.cursor = cursor(x, 0);
+
+                edit(&mut buffer);
+
+                let line = &buffer.content[buffer.cursor.y];
+                let index = buffer.cursor_index();
+
+                assert!(
+                    line.is_char_boundary(index),
+                    "'{}' at {} left the cursor inside a character",
+                    name,
+                    x
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn delete_removes_whole_grapheme() {
+    let mut buffer = buffer(&[EMOJI]);
+    buffer.cursor = cursor(1, 0);
+
+    buffer.remove_char().unwrap();
+
+    assert_eq!(buffer.content, vec!["ab👍🏽c"]);
+    assert_eq!(buffer.cursor.x, 1);
+}
+
+#[test]
+fn delete_removes_combining_marks_with_their_character() {
+    let mut buffer = buffer(&["cafe\u{301}!"]);
+    buffer.cursor = cursor(3, 0);
+
+    buffer.remove_char().unwrap();
+
+    assert_eq!(buffer.content, vec!["caf!"]);
+}
+
+#[test]
+fn backspace_removes_whole_grapheme() {
+    let mut buffer = buffer(&[EMOJI]);
+    buffer.cursor = cursor("a👨‍👩‍👧".chars().count(), 0);
+    buffer.switch_mode(ModeParams::Insert {
+        insert_direction: InsertDirection::Before,
+    });
+
+    buffer.remove_char().unwrap();
+
+    assert_eq!(buffer.content, vec!["ab👍🏽c"]);
+    assert_eq!(buffer.cursor.x, 1);
+}
+
+#[test]
+fn backspace_after_cjk_character() {
+    let mut buffer = buffer(&[CJK]);
+    buffer.cursor = cursor(1, 0);
+    buffer.switch_mode(ModeParams::Insert {
+        insert_direction: InsertDirection::After,
+    });
+
+    buffer.remove_char().unwrap();
+
+    assert_eq!(buffer.content, vec!["漢とかなのテスト"]);
+    assert_eq!(buffer.cursor.x, "漢".len());
+}
+
+#[test]
+fn insert_after_emoji_sequence_and_back_to_normal() {
+    let mut buffer = buffer(&[EMOJI]);
+    buffer.cursor = cursor(1, 0);
+    buffer.switch_mode(ModeParams::Insert {
+        insert_direction: InsertDirection::After,
+    });
+
+    buffer.add_char('!').unwrap();
+    buffer.switch_mode(ModeParams::Normal);
+
+    assert_eq!(buffer.content, vec!["a👨‍👩‍👧!b👍🏽c"]);
+    assert_eq!(buffer.cursor.x, "a👨‍👩‍👧!".chars().count());
+}
+
+#[test]
+fn replace_mixed_ascii_and_combining_character() {
+    let mut buffer = buffer(&["cafe\u{301} au lait"]);
+    buffer.cursor = cursor(3, 0);
+
+    buffer.replace_char('é');
+
+    assert_eq!(buffer.content, vec!["café au lait"]);
+}
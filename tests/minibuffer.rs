@@ -0,0 +1,237 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use oxide::buffer::{Manipulation, Minibuffer, MinibufferKind, Navigation};
+
+use common::temp_dir;
+
+// Returns a directory with 'alpha/beta/notes.txt', 'alpha/ünï/file.md' and 'other.txt' in it,
+// which is made again if an earlier run left it.
+fn tree(name: &str) -> PathBuf {
+    let dir = temp_dir(&format!("minibuffer-{}", name));
+
+    fs::create_dir_all(dir.join("alpha/beta")).unwrap();
+    fs::create_dir_all(dir.join("alpha/ünï")).unwrap();
+    fs::write(dir.join("alpha/beta/notes.txt"), "notes").unwrap();
+    fs::write(dir.join("alpha/ünï/file.md"), "# file").unwrap();
+    fs::write(dir.join("other.txt"), "other").unwrap();
+
+    dir
+}
+
+fn find_file(dir: &Path) -> Minibuffer {
+    let mut minibuffer = Minibuffer {
+        kind: MinibufferKind::File(dir.to_path_buf()),
+        matched_input: dir
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect(),
+        ..Minibuffer::default()
+    };
+    minibuffer.fill().unwrap();
+
+    minibuffer
+}
+
+// Types the text one character at a time, filling the candidates after each like the editor does.
+fn type_text(minibuffer: &mut Minibuffer, text: &str) {
+    for c in text.chars() {
+        minibuffer.add_char(c).unwrap();
+        minibuffer.fill().unwrap();
+    }
+}
+
+fn backspace(minibuffer: &mut Minibuffer) {
+    minibuffer.remove_char().unwrap();
+    minibuffer.fill().unwrap();
+}
+
+fn path(minibuffer: &Minibuffer) -> PathBuf {
+    match &minibuffer.kind {
+        MinibufferKind::File(path) => path.clone(),
+        kind => panic!("not finding a file: {:?}", kind),
+    }
+}
+
+fn names(minibuffer: &Minibuffer) -> Vec<&str> {
+    minibuffer
+        .content
+        .iter()
+        .map(|candidate| candidate.name.as_str())
+        .collect()
+}
+
+// ╭──────────────────────────────────────╮
+// │ Find File                            │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn typing_a_directory_name_goes_into_it() {
+    let dir = tree("nested");
+    let mut minibuffer = find_file(&dir);
+
+    type_text(&mut minibuffer, "alpha");
+
+    assert_eq!(path(&minibuffer), dir.join("alpha"));
+    assert_eq!(minibuffer.matched_input.last().unwrap(), "alpha");
+    assert_eq!(minibuffer.input, "");
+    assert_eq!(minibuffer.cursor.x, 0);
+
+    type_text(&mut minibuffer, "beta");
+    type_text(&mut minibuffer, "no");
+
+    assert_eq!(path(&minibuffer), dir.join("alpha/beta"));
+    assert_eq!(names(&minibuffer), vec!["..", "notes.txt"]);
+    assert_eq!(minibuffer.input, "no");
+    assert_eq!(minibuffer.cursor.x, 2);
+}
+
+#[test]
+fn typing_a_multibyte_directory_name() {
+    let dir = tree("multibyte");
+    let mut minibuffer = find_file(&dir);
+
+    type_text(&mut minibuffer, "alpha");
+    type_text(&mut minibuffer, "ün");
+
+    assert_eq!(minibuffer.cursor.x, 2);
+    assert_eq!(names(&minibuffer), vec!["..", "ünï"]);
+
+    type_text(&mut minibuffer, "ï");
+
+    assert_eq!(path(&minibuffer), dir.join("alpha/ünï"));
+    assert_eq!(names(&minibuffer), vec!["..", "file.md"]);
+}
+
+// Backspace edits the input until it's empty, and only then goes up one directory at a time.
+#[test]
+fn backspace_across_the_matched_path() {
+    let dir = tree("backspace");
+    let mut minibuffer = find_file(&dir);
+    type_text(&mut minibuffer, "alpha");
+    type_text(&mut minibuffer, "beta");
+    type_text(&mut minibuffer, "n");
+
+    backspace(&mut minibuffer);
+
+    assert_eq!(path(&minibuffer), dir.join("alpha/beta"));
+    assert_eq!(minibuffer.input, "");
+
+    backspace(&mut minibuffer);
+
+    assert_eq!(path(&minibuffer), dir.join("alpha"));
+    assert_eq!(minibuffer.matched_input.last().unwrap(), "alpha");
+    assert_eq!(names(&minibuffer), vec!["..", "beta", "ünï"]);
+
+    backspace(&mut minibuffer);
+
+    assert_eq!(path(&minibuffer), dir);
+    assert_eq!(minibuffer.matched_input.len(), dir.iter().count());
+}
+
+#[test]
+fn backspace_in_the_middle_of_multibyte_input() {
+    let dir = tree("middle");
+    let mut minibuffer = find_file(&dir);
+    type_text(&mut minibuffer, "alpha");
+    type_text(&mut minibuffer, "üx");
+
+    minibuffer.move_cursor(-1, 0);
+    backspace(&mut minibuffer);
+
+    assert_eq!(minibuffer.input, "x");
+    assert_eq!(minibuffer.cursor.x, 0);
+    assert_eq!(path(&minibuffer), dir.join("alpha"));
+
+    // At the start of the input there's nothing before the cursor to remove
+    backspace(&mut minibuffer);
+
+    assert_eq!(minibuffer.input, "x");
+    assert_eq!(path(&minibuffer), dir.join("alpha"));
+}
+
+// Editing the input after it matched a file goes back to the directory of the file.
+#[test]
+fn backspace_after_matching_a_file() {
+    let dir = tree("file");
+    let mut minibuffer = find_file(&dir);
+
+    type_text(&mut minibuffer, "other.txt");
+
+    assert_eq!(path(&minibuffer), dir.join("other.txt"));
+
+    backspace(&mut minibuffer);
+
+    assert_eq!(path(&minibuffer), dir);
+    assert_eq!(minibuffer.input, "other.tx");
+    assert_eq!(names(&minibuffer), vec!["..", "other.txt"]);
+}
+
+#[test]
+fn backspace_stops_at_the_root() {
+    let mut minibuffer = find_file(Path::new("/"));
+
+    backspace(&mut minibuffer);
+    backspace(&mut minibuffer);
+
+    assert_eq!(path(&minibuffer), PathBuf::from("/"));
+    assert_eq!(minibuffer.matched_input, vec!["/"]);
+}
+
+// Typing '~/' goes to the home directory right away. A bare '~' could still become '~/', so it
+// only goes there on enter.
+#[test]
+fn tilde_goes_to_the_home_directory() {
+    let home = PathBuf::from(env::var("HOME").unwrap())
+        .canonicalize()
+        .unwrap();
+    let dir = tree("tilde");
+
+    let mut minibuffer = find_file(&dir);
+    type_text(&mut minibuffer, "~/");
+
+    assert_eq!(path(&minibuffer), home);
+    assert_eq!(minibuffer.input, "");
+
+    let mut minibuffer = find_file(&dir);
+    type_text(&mut minibuffer, "~");
+
+    assert_eq!(path(&minibuffer), dir);
+
+    assert_eq!(minibuffer.execute().unwrap(), None);
+    minibuffer.fill().unwrap();
+
+    assert_eq!(path(&minibuffer), home);
+    assert_eq!(minibuffer.input, "");
+}
+
+// ╭──────────────────────────────────────╮
+// │ Without A Runtime                    │
+// ╰──────────────────────────────────────╯
+
+// The candidates and the preview are read on the thread of the caller, so they don't need a tokio
+// runtime to run in.
+#[test]
+fn fill_without_a_runtime() {
+    assert!(tokio::runtime::Handle::try_current().is_err());
+
+    let dir = tree("runtime");
+    let mut minibuffer = find_file(&dir);
+
+    assert_eq!(names(&minibuffer), vec!["..", "alpha", "other.txt"]);
+
+    minibuffer.cycle(true);
+    minibuffer.fill().unwrap();
+
+    assert_eq!(
+        minibuffer.preview,
+        Some(vec!["beta".to_string(), "ünï".to_string()])
+    );
+
+    type_text(&mut minibuffer, "other.txt");
+
+    assert_eq!(minibuffer.preview, Some(vec!["other".to_string()]));
+}
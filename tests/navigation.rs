@@ -0,0 +1,373 @@
+mod common;
+
+use oxide::buffer::{display_width, next_grapheme, prev_grapheme, Buffer, Navigation};
+use oxide::editor::Options;
+use oxide::keybinding::{CharSearch, InsertDirection, ModeParams, SearchDirection};
+
+use common::{buffer, cursor};
+
+// ╭──────────────────────────────────────╮
+// │ Word Motions                         │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn word_stops_at_punctuation_but_big_word_does_not() {
+    let mut buffer = buffer(&["foo.bar(baz) qux"]);
+
+    buffer.move_word_forward();
+    assert_eq!(buffer.cursor.x, 3);
+
+    buffer.cursor = cursor(0, 0);
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor.x, 13);
+}
+
+#[test]
+fn big_word_over_punctuation_only_lines() {
+    let mut buffer = buffer(&["--> ::", "!!", "end"]);
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor, cursor(4, 0));
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor, cursor(0, 1));
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor, cursor(0, 2));
+
+    buffer.move_big_word_backward();
+    assert_eq!(buffer.cursor, cursor(0, 1));
+
+    buffer.move_big_word_backward();
+    assert_eq!(buffer.cursor, cursor(4, 0));
+}
+
+#[test]
+fn word_treats_a_punctuation_run_as_one_word() {
+    let mut buffer = buffer(&["a --> b"]);
+    buffer.cursor = cursor(2, 0);
+
+    buffer.move_word_forward();
+    assert_eq!(buffer.cursor.x, 6);
+
+    buffer.move_word_backward();
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+#[test]
+fn big_word_skips_leading_whitespace() {
+    let mut buffer = buffer(&["end", "    (indented) line"]);
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor, cursor(4, 1));
+
+    buffer.move_big_word_backward();
+    assert_eq!(buffer.cursor, cursor(0, 0));
+}
+
+#[test]
+fn big_word_backward_from_leading_whitespace_goes_to_previous_line() {
+    let mut buffer = buffer(&["a.b c.d", "   x"]);
+    buffer.cursor = cursor(2, 1);
+
+    buffer.move_big_word_backward();
+    assert_eq!(buffer.cursor, cursor(4, 0));
+}
+
+#[test]
+fn big_word_stops_on_empty_lines() {
+    let mut buffer = buffer(&["a.b", "", "  c.d"]);
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor, cursor(0, 1));
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor, cursor(2, 2));
+}
+
+#[test]
+fn big_word_forward_stays_put_at_the_last_word() {
+    let mut buffer = buffer(&["  last.word"]);
+    buffer.cursor = cursor(2, 0);
+
+    buffer.move_big_word_forward();
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Wide And Combining Characters        │
+// ╰──────────────────────────────────────╯
+
+const CJK: &str = "漢字とかな";
+const EMOJI: &str = "a👨‍👩‍👧b👍🏽c";
+const MIXED: &str = "cafe\u{301} 日本 ok";
+
+#[test]
+fn display_width_of_wide_and_combining_characters() {
+    assert_eq!(display_width(CJK, 4), 10);
+    assert_eq!(display_width(EMOJI, 4), 7);
+    assert_eq!(display_width(MIXED, 4), 12);
+    assert_eq!(display_width("\t漢", 4), 6);
+}
+
+#[test]
+fn graphemes_keep_emoji_sequences_together() {
+    let family = "👨‍👩‍👧";
+
+    assert_eq!(next_grapheme(EMOJI, 1), 1 + family.len());
+    assert_eq!(prev_grapheme(EMOJI, 1 + family.len()), 1);
+    assert_eq!(next_grapheme(EMOJI, EMOJI.len()), EMOJI.len());
+    assert_eq!(prev_grapheme(EMOJI, 0), 0);
+}
+
+#[test]
+fn cursor_moves_over_cjk_by_character_and_column() {
+    let mut buffer = buffer(&[CJK]);
+
+    buffer.move_cursor(2, 0);
+
+    assert_eq!(buffer.cursor.x, 2);
+    assert_eq!(buffer.cursor_column(), 4);
+}
+
+#[test]
+fn cursor_moves_over_emoji_sequence_in_one_step() {
+    let mut buffer = buffer(&[EMOJI]);
+
+    buffer.move_cursor(2, 0);
+
+    assert_eq!(buffer.cursor.x, "a👨‍👩‍👧".chars().count());
+    assert_eq!(buffer.cursor_column(), 3);
+
+    buffer.move_cursor(-1, 0);
+
+    assert_eq!(buffer.cursor.x, 1);
+    assert_eq!(buffer.cursor_column(), 1);
+}
+
+#[test]
+fn cursor_skips_combining_marks() {
+    let mut buffer = buffer(&[MIXED]);
+    buffer.cursor = cursor(3, 0);
+
+    buffer.move_cursor(1, 0);
+
+    assert_eq!(buffer.cursor.x, 5);
+    assert_eq!(buffer.cursor_column(), 4);
+}
+
+#[test]
+fn cursor_is_clamped_to_the_last_grapheme() {
+    let mut buffer = buffer(&["ok👍🏽"]);
+
+    buffer.move_cursor(10, 0);
+
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+// Vertical moves never leave the cursor inside an emoji sequence.
+#[test]
+fn vertical_move_lands_on_grapheme_start() {
+    let mut buffer = buffer(&["abcdef", EMOJI]);
+    buffer.cursor = cursor(3, 0);
+
+    buffer.move_cursor(0, 1);
+
+    assert_eq!(buffer.cursor.x, 1);
+}
+
+#[test]
+fn insert_mode_cursor_moves_by_graphemes() {
+    let mut buffer = buffer(&[MIXED]);
+    buffer.switch_mode(ModeParams::Insert {
+        insert_direction: InsertDirection::Before,
+    });
+
+    buffer.move_cursor(4, 0);
+
+    assert_eq!(buffer.cursor.x, "cafe\u{301}".len());
+    assert_eq!(buffer.cursor_column(), 4);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Wrapped Lines                        │
+// ╰──────────────────────────────────────╯
+
+// Returns the screen row of the cursor, counted from the top of the viewport.
+fn screen_row(buffer: &Buffer, width: usize) -> usize {
+    let above: usize = (buffer.viewport.top..buffer.cursor.y)
+        .map(|y| buffer.line_rows(y, width).len())
+        .sum();
+
+    above + buffer.cursor_row(width).0 - buffer.viewport.top_row
+}
+
+#[test]
+fn display_rows_of_wrapped_line() {
+    let mut buffer = buffer(&["0123456789abcdef", "xy"]);
+    buffer.cursor = cursor(2, 0);
+
+    assert_eq!(buffer.line_rows(0, 6), vec![0, 6, 12]);
+
+    buffer.move_display_row(true, 6);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (8, 0));
+
+    buffer.move_display_row(true, 6);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (14, 0));
+
+    // The next line is shorter than the column, so the cursor goes to its end
+    buffer.move_display_row(true, 6);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (1, 1));
+
+    buffer.move_display_row(false, 6);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (13, 0));
+
+    buffer.move_display_row(false, 6);
+    buffer.move_display_row(false, 6);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (1, 0));
+
+    // There's no row above the first one
+    buffer.move_display_row(false, 6);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (1, 0));
+}
+
+#[test]
+fn display_row_shorter_than_the_column() {
+    let mut buffer = buffer(&["0123456789abcdef"]);
+    buffer.cursor = cursor(5, 0);
+
+    buffer.move_display_row(true, 6);
+    buffer.move_display_row(true, 6);
+
+    assert_eq!(buffer.cursor.x, 15);
+}
+
+// The lines above the cursor take up more rows than the viewport is high, so it scrolls by them.
+#[test]
+fn cursor_stays_on_screen_across_wrapped_lines() {
+    let mut buffer = buffer(&vec!["x".repeat(30); 10]);
+    buffer.viewport.height = 5;
+
+    for _ in 0..29 {
+        buffer.move_display_row(true, 10);
+        buffer.scroll_to_cursor(10, true);
+
+        assert!(screen_row(&buffer, 10) < buffer.viewport.height);
+    }
+
+    assert_eq!((buffer.cursor.y, buffer.cursor_row(10).0), (9, 2));
+
+    for _ in 0..29 {
+        buffer.move_display_row(false, 10);
+        buffer.scroll_to_cursor(10, true);
+
+        assert!(buffer.viewport.top <= buffer.cursor.y);
+        assert!(screen_row(&buffer, 10) < buffer.viewport.height);
+    }
+
+    assert_eq!((buffer.viewport.top, buffer.viewport.top_row), (0, 0));
+}
+
+// A line with more rows than the viewport is high is scrolled within the line.
+#[test]
+fn cursor_stays_on_screen_in_line_taller_than_the_viewport() {
+    let mut buffer = buffer(&["x".repeat(100)]);
+    buffer.viewport.height = 5;
+
+    for _ in 0..7 {
+        buffer.move_display_row(true, 10);
+        buffer.scroll_to_cursor(10, true);
+    }
+
+    assert_eq!(buffer.cursor.x, 70);
+    assert_eq!(buffer.viewport.top_row, 3);
+    assert_eq!(screen_row(&buffer, 10), 4);
+
+    buffer.move_display_row(false, 10);
+    buffer.move_display_row(false, 10);
+    buffer.move_display_row(false, 10);
+    buffer.move_display_row(false, 10);
+    buffer.scroll_to_cursor(10, true);
+
+    assert_eq!(buffer.viewport.top_row, 3);
+    assert_eq!(screen_row(&buffer, 10), 0);
+}
+
+// Lines don't wrap by default, and without a width the cursor moves by lines and long lines scroll
+// sideways.
+#[test]
+fn lines_do_not_wrap_by_default() {
+    let mut buffer = buffer(&["x".repeat(100), "short".to_string(), "x".repeat(100)]);
+    buffer.cursor = cursor(50, 0);
+
+    assert!(!Options::default().wrap);
+
+    buffer.move_display_row(true, 0);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (4, 1));
+
+    buffer.move_display_row(true, 0);
+    assert_eq!((buffer.cursor.x, buffer.cursor.y), (50, 2));
+
+    buffer.scroll_to_cursor(20, false);
+
+    assert_eq!(buffer.viewport.top_row, 0);
+    assert!(buffer.viewport.left <= 50 && 50 < buffer.viewport.left + 20);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Find Char                            │
+// ╰──────────────────────────────────────╯
+
+fn till(character: char, direction: SearchDirection) -> CharSearch {
+    CharSearch {
+        character,
+        direction,
+        till: true,
+    }
+}
+
+// A till search for the character right next to the cursor has already arrived, so it stays put.
+// Only repeating it moves on to the next occurrence.
+#[test]
+fn till_forward_to_adjacent_character() {
+    let mut buffer = buffer(&["axbxc"]);
+    let search = till('x', SearchDirection::Forward);
+
+    buffer.find_char(search, false);
+    assert_eq!(buffer.cursor.x, 0);
+
+    buffer.find_char(search, true);
+    assert_eq!(buffer.cursor.x, 2);
+
+    buffer.find_char(search, true);
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+#[test]
+fn till_backward_to_adjacent_character() {
+    let mut buffer = buffer(&["axbxc"]);
+    buffer.cursor = cursor(4, 0);
+    let search = till('x', SearchDirection::Backward);
+
+    buffer.find_char(search, false);
+    assert_eq!(buffer.cursor.x, 4);
+
+    buffer.find_char(search, true);
+    assert_eq!(buffer.cursor.x, 2);
+}
+
+#[test]
+fn find_moves_onto_adjacent_character() {
+    let mut buffer = buffer(&["axbxc"]);
+    let search = CharSearch {
+        character: 'x',
+        direction: SearchDirection::Forward,
+        till: false,
+    };
+
+    buffer.find_char(search, false);
+    assert_eq!(buffer.cursor.x, 1);
+
+    buffer.find_char(search, true);
+    assert_eq!(buffer.cursor.x, 3);
+}
@@ -0,0 +1,112 @@
+use oxide::buffer::BufferOptions;
+use oxide::editor::Options;
+use oxide::keybinding::OptionValue;
+
+fn set(
+    options: &mut Options,
+    buffer: &mut BufferOptions,
+    name: &str,
+    value: OptionValue,
+) -> Result<(), String> {
+    options.set(buffer, name, &value)
+}
+
+fn value(value: &str) -> OptionValue {
+    OptionValue::Value(value.to_string())
+}
+
+// ╭──────────────────────────────────────╮
+// │ Set                                  │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn flags_are_turned_on_and_off() {
+    let mut options = Options::default();
+    let mut buffer = BufferOptions::default();
+
+    set(&mut options, &mut buffer, "nonumber", OptionValue::On).unwrap();
+    assert!(!options.number);
+
+    set(&mut options, &mut buffer, "number", OptionValue::On).unwrap();
+    assert!(options.number);
+
+    set(&mut options, &mut buffer, "nowrap", OptionValue::On).unwrap();
+    assert!(!options.wrap);
+}
+
+// Buffer options are set on the buffer and on the defaults for new buffers.
+#[test]
+fn numbers_are_set_on_the_buffer_and_the_defaults() {
+    let mut options = Options::default();
+    let mut buffer = BufferOptions::default();
+
+    set(&mut options, &mut buffer, "tabwidth", value("8")).unwrap();
+
+    assert_eq!(buffer.tab_width, 8);
+    assert_eq!(options.buffer.tab_width, 8);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Errors                               │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn invalid_number() {
+    let mut options = Options::default();
+    let mut buffer = BufferOptions::default();
+    let tab_width = buffer.tab_width;
+
+    assert_eq!(
+        set(&mut options, &mut buffer, "tabwidth", value("abc")),
+        Err("Invalid value for tabwidth: abc".to_string())
+    );
+    assert_eq!(
+        set(&mut options, &mut buffer, "tabwidth", value("0")),
+        Err("Invalid value for tabwidth: 0".to_string())
+    );
+    assert_eq!(
+        set(&mut options, &mut buffer, "tabwidth", OptionValue::On),
+        Err("Option tabwidth needs a value".to_string())
+    );
+    assert_eq!(buffer.tab_width, tab_width);
+}
+
+#[test]
+fn flag_with_a_value() {
+    let mut options = Options::default();
+    let mut buffer = BufferOptions::default();
+
+    assert_eq!(
+        set(&mut options, &mut buffer, "wrap", value("yes")),
+        Err("Option wrap takes no value".to_string())
+    );
+}
+
+// An unknown option is named the way it was typed, even when it starts with 'no'.
+#[test]
+fn unknown_option() {
+    let mut options = Options::default();
+    let mut buffer = BufferOptions::default();
+
+    assert_eq!(
+        set(&mut options, &mut buffer, "nonexistent", OptionValue::On),
+        Err("Unknown option: nonexistent".to_string())
+    );
+    assert_eq!(
+        set(&mut options, &mut buffer, "colors", value("dark")),
+        Err("Unknown option: colors".to_string())
+    );
+    assert_eq!(options, Options::default());
+}
+
+// 'no' only turns options off, so it can't be given a value.
+#[test]
+fn no_with_a_value() {
+    let mut options = Options::default();
+    let mut buffer = BufferOptions::default();
+
+    assert_eq!(
+        set(&mut options, &mut buffer, "notabwidth", OptionValue::On),
+        Err("Option tabwidth needs a value".to_string())
+    );
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use oxide::editor::{RecentFiles, MAX_RECENT_FILES};
+
+use common::temp_dir;
+
+// Writes the files in the directory, and returns their paths.
+fn files(dir: &Path, names: &[&str]) -> Vec<PathBuf> {
+    names
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            fs::write(&path, name).unwrap();
+
+            path
+        })
+        .collect()
+}
+
+// ╭──────────────────────────────────────╮
+// │ Recent Files                         │
+// ╰──────────────────────────────────────╯
+
+// The newest file comes first, and the list is kept for the next time the editor starts.
+#[test]
+fn newest_file_first() {
+    let dir = temp_dir("recent-order");
+    let store = dir.join("data").join("recent");
+    let files = files(&dir, &["a", "b", "c"]);
+
+    let mut recent = RecentFiles::load_from(store.clone());
+    for file in &files {
+        recent.add(file);
+    }
+
+    let newest_first = vec![files[2].clone(), files[1].clone(), files[0].clone()];
+
+    assert_eq!(recent.files, newest_first);
+    assert_eq!(RecentFiles::load_from(store).files, newest_first);
+}
+
+// Opening a file again moves it to the front, whichever way its path was written.
+#[test]
+fn files_are_listed_once() {
+    let dir = temp_dir("recent-dedup");
+    let files = files(&dir, &["a", "b"]);
+
+    let mut recent = RecentFiles::load_from(dir.join("recent"));
+    recent.add(&files[0]);
+    recent.add(&files[1]);
+    recent.add(&dir.join(".").join("a"));
+
+    assert_eq!(recent.files, vec![files[0].clone(), files[1].clone()]);
+}
+
+#[test]
+fn oldest_files_are_dropped_past_the_limit() {
+    let dir = temp_dir("recent-cap");
+    let store = dir.join("recent");
+
+    let mut recent = RecentFiles::load_from(store.clone());
+    for num in 0..MAX_RECENT_FILES + 5 {
+        recent.add(&dir.join(format!("{}.txt", num)));
+    }
+
+    let loaded = RecentFiles::load_from(store);
+
+    assert_eq!(loaded.files.len(), MAX_RECENT_FILES);
+    assert_eq!(
+        loaded.files.first(),
+        Some(&dir.join(format!("{}.txt", MAX_RECENT_FILES + 4)))
+    );
+    assert_eq!(loaded.files.last(), Some(&dir.join("5.txt")));
+}
+
+// Deleted files stay in the list until it's shown, and are removed from the kept list then.
+#[test]
+fn deleted_files_are_pruned_when_listed() {
+    let dir = temp_dir("recent-prune");
+    let store = dir.join("recent");
+    let files = files(&dir, &["a", "b"]);
+
+    let mut recent = RecentFiles::load_from(store.clone());
+    recent.add(&files[0]);
+    recent.add(&files[1]);
+    fs::remove_file(&files[0]).unwrap();
+
+    assert_eq!(recent.files.len(), 2);
+    assert_eq!(recent.existing(), vec![files[1].clone()]);
+    assert_eq!(RecentFiles::load_from(store).files, vec![files[1].clone()]);
+}
+
+#[test]
+fn missing_store_starts_empty() {
+    let dir = temp_dir("recent-missing");
+
+    assert!(RecentFiles::load_from(dir.join("recent")).files.is_empty());
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use oxide::buffer::{Buffer, Manipulation};
+use oxide::editor::{read_recovery, recovery_files, save_recovery, unreported_recovery_files};
+
+use common::temp_dir;
+
+fn buffer(lines: &[&str], path: Option<&Path>) -> Buffer {
+    let mut buffer = common::buffer(lines);
+    buffer.title = "file.txt".to_string();
+    buffer.path = path.map(Path::to_path_buf);
+
+    buffer
+}
+
+// ╭──────────────────────────────────────╮
+// │ Save And Read                        │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn recovery_keeps_the_path_of_the_file() {
+    let dir = temp_dir("recovery-path");
+    let path = dir.join("project").join("file.txt");
+    let mut buffer = buffer(&["first", "second"], Some(&path));
+    buffer.remove_char().unwrap();
+
+    let files = save_recovery(&dir, &[buffer]).unwrap();
+    let recovery = read_recovery(&files[0]).unwrap();
+
+    assert_eq!(recovery.title, "file.txt");
+    assert_eq!(recovery.path, Some(path));
+    assert_eq!(recovery.content, vec!["irst", "second"]);
+}
+
+#[test]
+fn recovery_of_buffer_without_a_file() {
+    let dir = temp_dir("recovery-no-path");
+    let mut buffer = buffer(&["ab", "", "text", ""], None);
+    buffer.remove_char().unwrap();
+
+    let files = save_recovery(&dir, &[buffer]).unwrap();
+    let recovery = read_recovery(&files[0]).unwrap();
+
+    assert_eq!(recovery.path, None);
+    assert_eq!(recovery.content, vec!["b", "", "text", ""]);
+}
+
+#[test]
+fn only_modified_buffers_are_saved() {
+    let dir = temp_dir("recovery-modified");
+    let mut modified = buffer(&["text"], None);
+    modified.remove_char().unwrap();
+
+    let files = save_recovery(&dir, &[buffer(&["text"], None), modified]).unwrap();
+
+    assert_eq!(files.len(), 1);
+    assert_eq!(recovery_files(&dir), files);
+}
+
+// ╭──────────────────────────────────────╮
+// │ Recover                              │
+// ╰──────────────────────────────────────╯
+
+// A recovered buffer is modified even if the file has the same content, until it's written.
+#[test]
+fn recovered_buffer_is_modified() {
+    let path = temp_dir("recovery-recovered").join("file.txt");
+    let buffer = Buffer::recovered(
+        "file.txt".to_string(),
+        vec!["text".to_string()],
+        Some(path.clone()),
+        20,
+        0,
+    );
+
+    assert!(buffer.is_modified());
+    assert_eq!(buffer.path, Some(path));
+}
+
+#[test]
+fn recovering_into_an_open_buffer_can_be_undone() {
+    let mut buffer = buffer(&["old", "lines"], None);
+    buffer.cursor.y = 1;
+
+    buffer.recover(vec!["new".to_string()]);
+
+    assert_eq!(buffer.content, vec!["new"]);
+    assert_eq!(buffer.cursor.y, 0);
+    assert!(buffer.is_modified());
+
+    buffer.undo().unwrap();
+
+    assert_eq!(buffer.content, vec!["old", "lines"]);
+    assert!(!buffer.is_modified());
+}
+
+// ╭──────────────────────────────────────╮
+// │ Reporting                            │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn recovery_files_are_only_reported_once() {
+    let dir = temp_dir("recovery-reported");
+    let mut buffer = buffer(&["text"], None);
+    buffer.remove_char().unwrap();
+
+    let first = save_recovery(&dir, std::slice::from_ref(&buffer)).unwrap();
+
+    assert_eq!(unreported_recovery_files(&dir), first);
+    assert!(unreported_recovery_files(&dir).is_empty());
+
+    // The files are still there to recover, without the marker of the last report
+    assert_eq!(recovery_files(&dir), first);
+
+    thread::sleep(Duration::from_millis(50));
+    let second = save_recovery(&dir, &[buffer]).unwrap();
+
+    assert_eq!(unreported_recovery_files(&dir), second);
+}
@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use ratatui::text::Line;
+
+mod common;
+
+use oxide::buffer::{Buffer, Cursor, Manipulation};
+use oxide::renderer::{format_buffer_line, statusline_segments, Theme};
+
+use common::buffer;
+
+fn file_buffer(lines: &[&str]) -> Buffer {
+    let mut buffer = buffer(lines);
+    buffer.path = Some(PathBuf::from("/project/src/deep/file.rs"));
+
+    buffer
+}
+
+fn text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+// ╭──────────────────────────────────────╮
+// │ Long Lines                           │
+// ╰──────────────────────────────────────╯
+
+// Only the visible columns of a line are formatted, so a screen of a 10k by 10k buffer scrolled to
+// its middle is formatted in about the time a screen of short lines is.
+#[test]
+fn visible_lines_of_10k_by_10k_buffer() {
+    let line: String = "0123456789".repeat(1_000);
+    let mut buffer = buffer(&vec![format!("{}  ", line); 10_000]);
+    let theme = Theme::default();
+    let width = 80;
+
+    buffer.viewport.height = 48;
+    buffer.viewport.top = 5_000;
+    buffer.viewport.left = 5_005;
+    buffer.cursor = Cursor {
+        x: 5_010,
+        y: 5_010,
+        desired_x: 5_010,
+    };
+
+    let started = Instant::now();
+
+    let lines: Vec<Line> = (buffer.viewport.top..buffer.viewport.bottom())
+        .map(|num| {
+            format_buffer_line(
+                &buffer.content[num],
+                num,
+                &buffer,
+                &[],
+                Some(width),
+                true,
+                &theme,
+            )
+        })
+        .collect();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(lines.len(), 48);
+
+    for line in &lines {
+        let text = text(line);
+
+        assert_eq!(text.chars().count(), width);
+        assert!(text.starts_with("567890123"));
+    }
+}
+
+#[test]
+fn trailing_spaces_are_marked_at_the_end_of_long_line() {
+    let mut buffer = buffer(&[format!("{}  ", "x".repeat(10_000))]);
+    buffer.viewport.left = 9_995;
+
+    let line = format_buffer_line(
+        &buffer.content[0],
+        1,
+        &buffer,
+        &[],
+        Some(10),
+        true,
+        &Theme::default(),
+    );
+
+    assert_eq!(text(&line), "xxxxx·· ");
+}
+
+// Without 'list' the trailing spaces are drawn as they are.
+#[test]
+fn trailing_spaces_without_list() {
+    let buffer = buffer(&["ab  "]);
+
+    let line = format_buffer_line(
+        &buffer.content[0],
+        1,
+        &buffer,
+        &[],
+        Some(10),
+        false,
+        &Theme::default(),
+    );
+
+    assert_eq!(text(&line), "ab   ");
+}
+
+#[test]
+fn inner_spaces_are_not_trailing() {
+    let buffer = buffer(&["a b c "]);
+
+    let line = format_buffer_line(
+        &buffer.content[0],
+        1,
+        &buffer,
+        &[],
+        None,
+        true,
+        &Theme::default(),
+    );
+
+    assert_eq!(text(&line), "a b c· ");
+}
+
+// ╭──────────────────────────────────────╮
+// │ Statusline                           │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn statusline_of_unchanged_file() {
+    let buffer = file_buffer(&["first", "second"]);
+
+    let [left, middle, right] = statusline_segments(&buffer, "", 120);
+
+    assert_eq!(left, " NORMAL ");
+    assert_eq!(middle, "/project/src/deep/file.rs");
+    assert!(right.starts_with(" rust  [1/2] :1 "));
+    assert!(right.ends_with(" 0% "));
+}
+
+#[test]
+fn statusline_flags() {
+    let mut buffer = file_buffer(&["text"]);
+    buffer.remove_char().unwrap();
+    buffer.read_only = true;
+
+    let [_, middle, _] = statusline_segments(&buffer, "", 120);
+
+    assert_eq!(middle, "/project/src/deep/file.rs [+] [RO]");
+}
+
+// The start of the path is cut off first, so the name of the file stays in sight.
+#[test]
+fn statusline_path_on_narrow_terminal() {
+    let mut buffer = file_buffer(&["text"]);
+    buffer.remove_char().unwrap();
+
+    let [_, middle, _] = statusline_segments(&buffer, "", 45);
+
+    assert_eq!(middle, "…ep/file.rs [+]");
+    assert_eq!(middle.chars().count(), 45 / 3);
+}
+
+// The parts on the right are dropped one at a time, keeping the position the longest.
+#[test]
+fn statusline_position_on_narrow_terminal() {
+    let buffer = file_buffer(&["first", "second"]);
+
+    let [_, _, right] = statusline_segments(&buffer, "ab", 45);
+
+    assert_eq!(right, " ab  [1/2] :1 ");
+
+    let [_, _, right] = statusline_segments(&buffer, "ab", 36);
+
+    assert_eq!(right, " [1/2] :1 ");
+}
+
+// The column is the one the cursor is displayed at, with tabs expanded and wide characters taking
+// two cells.
+#[test]
+fn statusline_column_with_tabs_and_wide_characters() {
+    let mut buffer = file_buffer(&["\t漢字x"]);
+    buffer.options.tab_width = 4;
+    buffer.cursor.x = 3;
+
+    let [_, _, right] = statusline_segments(&buffer, "", 120);
+
+    assert!(right.starts_with(" rust  [1/1] :9 "));
+}
@@ -0,0 +1,61 @@
+use oxide::editor::run_shell;
+
+// ╭──────────────────────────────────────╮
+// │ Run Shell                            │
+// ╰──────────────────────────────────────╯
+
+#[tokio::test]
+async fn output_of_successful_command() {
+    let output = run_shell("echo first; echo second").await.unwrap();
+
+    assert_eq!(output.stdout, vec!["first", "second"]);
+    assert!(output.stderr.is_empty());
+    assert_eq!(output.status, Some(0));
+    assert!(output.success);
+    assert_eq!(output.error_message(), None);
+}
+
+// The exit status is reported with the last line the command wrote to stderr.
+#[tokio::test]
+async fn failed_command_reports_status_and_stderr() {
+    let output = run_shell("echo out; echo x >&2; exit 3").await.unwrap();
+
+    assert_eq!(output.stdout, vec!["out"]);
+    assert_eq!(output.stderr, vec!["x"]);
+    assert_eq!(output.status, Some(3));
+    assert!(!output.success);
+    assert_eq!(
+        output.error_message(),
+        Some("Command exited with status 3: x".to_string())
+    );
+}
+
+#[tokio::test]
+async fn failed_command_without_stderr() {
+    let output = run_shell("exit 1").await.unwrap();
+
+    assert_eq!(
+        output.error_message(),
+        Some("Command exited with status 1".to_string())
+    );
+}
+
+// A command can warn on stderr and still succeed, which is shown without a status.
+#[tokio::test]
+async fn successful_command_with_stderr() {
+    let output = run_shell("echo warning >&2; echo >&2").await.unwrap();
+
+    assert!(output.success);
+    assert_eq!(output.error_message(), Some("warning".to_string()));
+}
+
+#[tokio::test]
+async fn terminated_command() {
+    let output = run_shell("kill -9 $$").await.unwrap();
+
+    assert_eq!(output.status, None);
+    assert_eq!(
+        output.error_message(),
+        Some("Command was terminated".to_string())
+    );
+}
@@ -0,0 +1,115 @@
+use ratatui::style::{Color, Style};
+
+use oxide::renderer::Theme;
+
+// ╭──────────────────────────────────────╮
+// │ Parse                                │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn styles_are_changed_in_their_tables() {
+    let (theme, warnings) = Theme::parse(
+        r##"
+[cursor]
+fg = "white"
+bg = "#102030"
+
+[error]
+bg = "red"
+"##,
+    );
+
+    assert!(warnings.is_empty());
+    assert_eq!(
+        theme.cursor,
+        Style::new()
+            .fg(Color::White)
+            .bg(Color::Rgb(0x10, 0x20, 0x30))
+    );
+    assert_eq!(theme.error, Theme::dark().error.bg(Color::Red));
+    assert_eq!(theme.selection, Theme::dark().selection);
+}
+
+#[test]
+fn base_theme() {
+    let (theme, warnings) = Theme::parse("base = \"light\"\n\n[error]\nfg = \"blue\"");
+
+    assert!(warnings.is_empty());
+    assert_eq!(theme.cursor, Theme::light().cursor);
+    assert_eq!(theme.error, Theme::light().error.fg(Color::Blue));
+}
+
+#[test]
+fn comments_at_the_end_of_lines() {
+    let (theme, warnings) = Theme::parse(
+        r##"
+# A theme with comments
+base = "light" # the base
+[cursor] # the cursor
+fg = "red" # a named color
+bg = "#102030" # a hex color
+"##,
+    );
+
+    assert!(warnings.is_empty());
+    assert_eq!(
+        theme.cursor,
+        Style::new().fg(Color::Red).bg(Color::Rgb(0x10, 0x20, 0x30))
+    );
+}
+
+// ╭──────────────────────────────────────╮
+// │ Warnings                             │
+// ╰──────────────────────────────────────╯
+
+// A color that can't be read keeps the color of the base theme.
+#[test]
+fn invalid_color_keeps_the_default() {
+    let (theme, warnings) = Theme::parse("[cursor]\nfg = \"reddish\"\nbg = \"blue\"");
+
+    assert_eq!(warnings, vec!["Invalid color for cursor: reddish"]);
+    assert_eq!(theme.cursor, Theme::dark().cursor.bg(Color::Blue));
+}
+
+#[test]
+fn unknown_style() {
+    let (theme, warnings) = Theme::parse("[gutter]\nfg = \"red\"");
+
+    assert_eq!(warnings, vec!["Unknown style in theme: gutter"]);
+    assert_eq!(theme, Theme::dark());
+}
+
+#[test]
+fn unknown_key() {
+    let (theme, warnings) = Theme::parse("[cursor]\nunderline = \"true\"");
+
+    assert_eq!(warnings, vec!["Unknown key in theme: underline"]);
+    assert_eq!(theme, Theme::dark());
+}
+
+#[test]
+fn unknown_base_theme() {
+    let (theme, warnings) = Theme::parse("base = \"solarized\"");
+
+    assert_eq!(warnings, vec!["Unknown base theme: solarized"]);
+    assert_eq!(theme, Theme::dark());
+}
+
+// Like in TOML, a key after a table belongs to the table, so the base has to come first.
+#[test]
+fn base_theme_in_a_table() {
+    let (theme, warnings) = Theme::parse("[cursor]\nbase = \"light\"");
+
+    assert_eq!(
+        warnings,
+        vec!["The base theme has to be set before the first table, not in [cursor]"]
+    );
+    assert_eq!(theme, Theme::dark());
+}
+
+#[test]
+fn line_without_a_value() {
+    let (_, warnings) = Theme::parse("[cursor]\nfg");
+
+    assert_eq!(warnings, vec!["Invalid line 2 in theme: fg"]);
+}
@@ -0,0 +1,77 @@
+use oxide::buffer::Viewport;
+
+// ╭──────────────────────────────────────╮
+// │ Scrolloff                            │
+// ╰──────────────────────────────────────╯
+
+#[test]
+fn scrolloff_keeps_lines_below_the_cursor() {
+    let mut viewport = Viewport::new(10, 3);
+
+    viewport.adjust(7, 100);
+
+    assert_eq!(viewport.top, 1);
+}
+
+#[test]
+fn scrolloff_keeps_lines_above_the_cursor() {
+    let mut viewport = Viewport::new(10, 3);
+    viewport.top = 20;
+
+    viewport.adjust(21, 100);
+
+    assert_eq!(viewport.top, 18);
+}
+
+// A margin larger than half the viewport is capped, so moving one line scrolls one line instead of
+// jumping back and forth.
+#[test]
+fn scrolloff_larger_than_half_the_viewport_is_capped() {
+    let mut viewport = Viewport::new(5, 10);
+
+    for cursor_y in 0..20 {
+        viewport.adjust(cursor_y, 20);
+
+        assert!(viewport.top <= cursor_y && cursor_y < viewport.bottom());
+    }
+
+    assert_eq!(viewport.top, 15);
+
+    viewport.adjust(17, 20);
+    assert_eq!(viewport.top, 15);
+
+    viewport.adjust(16, 20);
+    assert_eq!(viewport.top, 14);
+}
+
+// A buffer shorter than the viewport never scrolls, however large the margin is.
+#[test]
+fn scrolloff_in_buffer_shorter_than_the_viewport() {
+    let mut viewport = Viewport::new(10, 5);
+
+    for cursor_y in [0, 3, 5, 2, 0] {
+        viewport.adjust(cursor_y, 6);
+
+        assert_eq!(viewport.top, 0);
+    }
+}
+
+#[test]
+fn scrolloff_with_viewport_of_one_line() {
+    let mut viewport = Viewport::new(1, 5);
+
+    viewport.adjust(4, 10);
+    assert_eq!(viewport.top, 4);
+
+    viewport.adjust(3, 10);
+    assert_eq!(viewport.top, 3);
+}
+
+#[test]
+fn scrolloff_does_not_scroll_past_the_end() {
+    let mut viewport = Viewport::new(10, 3);
+
+    viewport.adjust(11, 12);
+
+    assert_eq!(viewport.top, 2);
+}